@@ -2,16 +2,16 @@
 use crate::window::TuixWindow;
 use crate::Renderer;
 use baseview::WindowScalePolicy;
-use femtovg::Canvas;
 use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+use std::time::Instant;
 use tuix_core::events::{Event, Propagation};
-use tuix_core::state::hierarchy::IntoHierarchyIterator;
 use tuix_core::state::mouse::{MouseButton, MouseButtonState};
 use tuix_core::state::Fonts;
 use tuix_core::window::WindowWidget;
+use tuix_core::renderer::{Canvas, FemtovgCanvas};
 use tuix_core::{
-    Entity, EventManager, Hierarchy, Length, PropSet, Size, State, Visibility, WindowDescription,
-    WindowEvent,
+    CursorIcon, Entity, EventManager, EventProxy, Hierarchy, Length, Size, State,
+    SystemClipboard, WindowDescription, WindowEvent,
 };
 
 pub struct Application<F>
@@ -20,6 +20,8 @@ where
     F: 'static + Send,
 {
     app: F,
+    // Created before the window is opened so that event proxies can be handed out beforehand
+    state: State,
 }
 
 impl<F> Application<F>
@@ -28,7 +30,19 @@ where
     F: 'static + Send,
 {
     pub fn new(app: F) -> Self {
-        Self { app }
+        let mut state = State::new();
+        state.set_clipboard(SystemClipboard::new());
+        state.hierarchy.add(Entity::root(), None);
+
+        Self { app, state }
+    }
+
+    /// Returns a handle which can be used to send events to the application from other threads
+    ///
+    /// Events sent through the proxy are processed on the next frame of the window. Proxies
+    /// can be created before the window is opened and stay connected once it is.
+    pub fn event_proxy(&self) -> EventProxy {
+        self.state.event_proxy()
     }
 
     /// Open a new window that blocks the current thread until the window is destroyed.
@@ -38,7 +52,7 @@ where
     ///
     /// * `app` - The Tuix application builder.
     pub fn run(self) {
-        TuixWindow::open_blocking(self.app)
+        TuixWindow::open_blocking(self.state, self.app)
     }
 
     /// Open a new child window.
@@ -49,7 +63,7 @@ where
     /// * `parent` - The parent window.
     /// * `app` - The Tuix application builder.
     pub fn open_parented<P: HasRawWindowHandle>(self, parent: &P) {
-        TuixWindow::open_parented(parent, self.app)
+        TuixWindow::open_parented(parent, self.state, self.app)
     }
 
     /// Open a new window as if it had a parent window.
@@ -59,26 +73,29 @@ where
     ///
     /// * `app` - The Tuix application builder.
     pub fn open_as_if_parented(self) -> RawWindowHandle {
-        TuixWindow::open_as_if_parented(self.app)
+        TuixWindow::open_as_if_parented(self.state, self.app)
     }
 }
 
 pub(crate) struct ApplicationRunner {
     state: State,
     event_manager: EventManager,
-    canvas: Canvas<Renderer>,
+    canvas: FemtovgCanvas<Renderer>,
     hierarchy: Hierarchy,
     pos: (f32, f32),
     should_redraw: bool,
     scale_policy: WindowScalePolicy,
     scale_factor: f64,
+    // The cursor icon shown by the window
+    window_cursor: CursorIcon,
 }
 
 impl ApplicationRunner {
     pub fn new(mut state: State, win_desc: WindowDescription, renderer: Renderer) -> Self {
         let event_manager = EventManager::new();
 
-        let mut canvas = Canvas::new(renderer).expect("Cannot create canvas");
+        let mut canvas =
+            FemtovgCanvas::new(femtovg::Canvas::new(renderer).expect("Cannot create canvas"));
 
         // TODO: Get scale policy from `win_desc`.
         let scale_policy = WindowScalePolicy::SystemScaleFactor;
@@ -148,6 +165,7 @@ impl ApplicationRunner {
             should_redraw: true,
             scale_policy,
             scale_factor: scale,
+            window_cursor: CursorIcon::default(),
         }
     }
 
@@ -166,6 +184,11 @@ impl ApplicationRunner {
     */
 
     pub fn on_frame_update(&mut self) {
+        // Events and values from other threads and timers are checked on every frame
+        self.state.receive_proxy_events();
+        self.state.receive_values();
+        self.state.update_timers(Instant::now());
+
         if self.state.apply_animations() {
             self.state.insert_event(
                 Event::new(WindowEvent::Relayout)
@@ -181,6 +204,21 @@ impl ApplicationRunner {
                 self.should_redraw = true;
             }
         }
+
+        // Window commands are not supported by this backend, as documented on WindowCommand, so
+        // they are discarded rather than left to pile up
+        self.state.take_window_commands();
+    }
+
+    /// Returns the cursor icon if it has changed since it was last returned
+    pub fn cursor_changed(&mut self) -> Option<CursorIcon> {
+        let cursor = self.state.cursor();
+        if cursor != self.window_cursor {
+            self.window_cursor = cursor;
+            Some(cursor)
+        } else {
+            None
+        }
     }
 
     pub fn render(&mut self) -> bool {
@@ -195,9 +233,13 @@ impl ApplicationRunner {
     }
 
     pub fn handle_event(&mut self, event: baseview::Event, should_quit: &mut bool) {
+        // Closing cannot be cancelled with baseview, so there is no close request to veto
         if requests_exit(&event) {
-            self.state
-                .insert_event(Event::new(WindowEvent::WindowClose));
+            self.state.insert_event(
+                Event::new(WindowEvent::WindowClose)
+                    .target(Entity::root())
+                    .propagate(Propagation::All),
+            );
             *should_quit = true;
         }
 
@@ -207,267 +249,18 @@ impl ApplicationRunner {
                     let cursorx = (position.x) as f32;
                     let cursory = (position.y) as f32;
 
-                    self.state.mouse.cursorx = cursorx;
-                    self.state.mouse.cursory = cursory;
-
-                    let mut hovered_widget = Entity::root();
-
-                    // This only really needs to be computed when the hierarchy changes
-                    // Can be optimised
-                    let mut draw_hierarchy: Vec<Entity> =
-                        self.state.hierarchy.into_iter().collect();
-
-                    draw_hierarchy
-                        .sort_by_cached_key(|entity| self.state.data.get_z_order(*entity));
-
-                    for widget in draw_hierarchy.into_iter() {
-                        // Skip invisible widgets
-                        if self.state.data.get_visibility(widget) == Visibility::Invisible {
-                            continue;
-                        }
-
-                        // This shouldn't be here but there's a bug if it isn't
-                        if self.state.data.get_opacity(widget) == 0.0 {
-                            continue;
-                        }
-
-                        // Skip non-hoverable widgets
-                        if self.state.data.get_hoverability(widget) != true {
-                            continue;
-                        }
-
-                        let border_width = match self
-                            .state
-                            .style
-                            .border_width
-                            .get(widget)
-                            .cloned()
-                            .unwrap_or_default()
-                        {
-                            Length::Pixels(val) => val,
-                            //Length::Percentage(val) => parent_width * val,
-                            _ => 0.0,
-                        };
-
-                        let posx = self.state.data.get_posx(widget) - (border_width / 2.0);
-                        let posy = self.state.data.get_posy(widget) - (border_width / 2.0);
-                        let width = self.state.data.get_width(widget) + (border_width);
-                        let height = self.state.data.get_height(widget) + (border_width);
-
-                        let clip_widget = self.state.data.get_clip_widget(widget);
-
-                        let clip_posx = self.state.data.get_posx(clip_widget);
-                        let clip_posy = self.state.data.get_posy(clip_widget);
-                        let clip_width = self.state.data.get_width(clip_widget);
-                        let clip_height = self.state.data.get_height(clip_widget);
-
-                        if cursorx >= posx
-                            && cursorx >= clip_posx
-                            && cursorx < (posx + width)
-                            && cursorx < (clip_posx + clip_width)
-                            && cursory >= posy
-                            && cursory >= clip_posy
-                            && cursory < (posy + height)
-                            && cursory < (clip_posy + clip_height)
-                        {
-                            hovered_widget = widget;
-                            if let Some(pseudo_classes) =
-                                self.state.style.pseudo_classes.get_mut(hovered_widget)
-                            {
-                                pseudo_classes.set_over(true);
-                            }
-                        } else {
-                            if let Some(pseudo_classes) =
-                                self.state.style.pseudo_classes.get_mut(hovered_widget)
-                            {
-                                pseudo_classes.set_over(false);
-                            }
-                        }
-                    }
-
-                    if hovered_widget != self.state.hovered {
-                        // Useful for debugging
-
-                        // println!(
-                        //     "Hover changed to {:?} parent: {:?}, posx: {}, posy: {} width: {} height: {} z_order: {}",
-                        //     hovered_widget,
-                        //     self.state.hierarchy.get_parent(hovered_widget),
-                        //     self.state.transform.get_posx(hovered_widget),
-                        //     self.state.transform.get_posy(hovered_widget),
-                        //     self.state.transform.get_width(hovered_widget),
-                        //     self.state.transform.get_height(hovered_widget),
-                        //     self.state.transform.get_z_order(hovered_widget),
-                        // );
-
-                        if let Some(pseudo_classes) =
-                            self.state.style.pseudo_classes.get_mut(hovered_widget)
-                        {
-                            pseudo_classes.set_hover(true);
-                        }
-
-                        if let Some(pseudo_classes) =
-                            self.state.style.pseudo_classes.get_mut(self.state.hovered)
-                        {
-                            pseudo_classes.set_hover(false);
-                        }
-
-                        self.state.insert_event(
-                            Event::new(WindowEvent::MouseOver).target(hovered_widget),
-                        );
-                        self.state.insert_event(
-                            Event::new(WindowEvent::MouseOut).target(self.state.hovered),
-                        );
-
-                        self.state
-                            .insert_event(Event::new(WindowEvent::Restyle).origin(hovered_widget));
-                        self.state.insert_event(
-                            Event::new(WindowEvent::Restyle).origin(self.state.hovered),
-                        );
-
-                        self.state.hovered = hovered_widget;
-                        self.state.active = Entity::null();
-
-                        self.state.insert_event(Event::new(WindowEvent::Redraw));
-                    }
-
-                    if self.state.captured != Entity::null() {
-                        self.state.insert_event(
-                            Event::new(WindowEvent::MouseMove(cursorx, cursory))
-                                .target(self.state.captured)
-                                .propagate(Propagation::Direct),
-                        );
-                    } else if self.state.hovered != Entity::root() {
-                        self.state.insert_event(
-                            Event::new(WindowEvent::MouseMove(cursorx, cursory))
-                                .target(self.state.hovered),
-                        );
-                    }
+                    self.state
+                        .dispatch_input(WindowEvent::MouseMove(cursorx, cursory));
 
                     self.pos = (cursorx, cursory);
                 }
                 baseview::MouseEvent::ButtonPressed(button) => {
-                    let b = match button {
-                        baseview::MouseButton::Left => MouseButton::Left,
-                        baseview::MouseButton::Right => MouseButton::Right,
-                        baseview::MouseButton::Middle => MouseButton::Middle,
-                        baseview::MouseButton::Other(id) => MouseButton::Other(id as u16),
-                        baseview::MouseButton::Back => MouseButton::Other(4),
-                        baseview::MouseButton::Forward => MouseButton::Other(5),
-                    };
-
-                    match b {
-                        MouseButton::Left => {
-                            self.state.mouse.left.state = MouseButtonState::Pressed;
-                        }
-                        MouseButton::Right => {
-                            self.state.mouse.right.state = MouseButtonState::Pressed;
-                        }
-                        MouseButton::Middle => {
-                            self.state.mouse.middle.state = MouseButtonState::Pressed;
-                        }
-                        _ => {}
-                    };
-
-                    if self.state.hovered != Entity::null()
-                        && self.state.active != self.state.hovered
-                    {
-                        self.state.active = self.state.hovered;
-                        self.state.insert_event(Event::new(WindowEvent::Restyle));
-                    }
-
-                    if self.state.captured != Entity::null() {
-                        self.state.insert_event(
-                            Event::new(WindowEvent::MouseDown(b))
-                                .target(self.state.captured)
-                                .propagate(Propagation::Direct),
-                        );
-                    } else {
-                        self.state.insert_event(
-                            Event::new(WindowEvent::MouseDown(b)).target(self.state.hovered),
-                        );
-                    }
-
-                    match b {
-                        MouseButton::Left => {
-                            self.state.mouse.left.pos_down =
-                                (self.state.mouse.cursorx, self.state.mouse.cursory);
-                            self.state.mouse.left.pressed = self.state.hovered;
-                        }
-
-                        MouseButton::Middle => {
-                            self.state.mouse.middle.pos_down =
-                                (self.state.mouse.cursorx, self.state.mouse.cursory);
-                            self.state.mouse.left.pressed = self.state.hovered;
-                        }
-
-                        MouseButton::Right => {
-                            self.state.mouse.right.pos_down =
-                                (self.state.mouse.cursorx, self.state.mouse.cursory);
-                            self.state.mouse.left.pressed = self.state.hovered;
-                        }
-
-                        _ => {}
-                    }
+                    let b = translate_mouse_button(button);
+                    self.state.dispatch_input(WindowEvent::MouseDown(b));
                 }
                 baseview::MouseEvent::ButtonReleased(button) => {
-                    let b = match button {
-                        baseview::MouseButton::Left => MouseButton::Left,
-                        baseview::MouseButton::Right => MouseButton::Right,
-                        baseview::MouseButton::Middle => MouseButton::Middle,
-                        baseview::MouseButton::Other(id) => MouseButton::Other(id as u16),
-                        baseview::MouseButton::Back => MouseButton::Other(4),
-                        baseview::MouseButton::Forward => MouseButton::Other(5),
-                    };
-
-                    match b {
-                        MouseButton::Left => {
-                            self.state.mouse.left.state = MouseButtonState::Released;
-                        }
-                        MouseButton::Right => {
-                            self.state.mouse.right.state = MouseButtonState::Released;
-                        }
-                        MouseButton::Middle => {
-                            self.state.mouse.middle.state = MouseButtonState::Released;
-                        }
-                        _ => {}
-                    };
-
-                    self.state.active = Entity::null();
-                    self.state.insert_event(Event::new(WindowEvent::Restyle));
-
-                    if self.state.captured != Entity::null() {
-                        self.state.insert_event(
-                            Event::new(WindowEvent::MouseUp(b))
-                                .target(self.state.captured)
-                                .propagate(Propagation::Direct),
-                        );
-                    } else {
-                        self.state.insert_event(
-                            Event::new(WindowEvent::MouseUp(b)).target(self.state.hovered),
-                        );
-                    }
-
-                    match b {
-                        MouseButton::Left => {
-                            self.state.mouse.left.pos_up =
-                                (self.state.mouse.cursorx, self.state.mouse.cursory);
-                            self.state.mouse.left.released = self.state.hovered;
-                        }
-
-                        MouseButton::Middle => {
-                            self.state.mouse.middle.pos_up =
-                                (self.state.mouse.cursorx, self.state.mouse.cursory);
-                            self.state.mouse.left.released = self.state.hovered;
-                        }
-
-                        MouseButton::Right => {
-                            self.state.mouse.right.pos_up =
-                                (self.state.mouse.cursorx, self.state.mouse.cursory);
-                            self.state.mouse.left.released = self.state.hovered;
-                        }
-
-                        _ => {}
-                    }
+                    let b = translate_mouse_button(button);
+                    self.state.dispatch_input(WindowEvent::MouseUp(b));
                 }
                 baseview::MouseEvent::WheelScrolled(scroll_delta) => {
                     let (lines_x, lines_y) = match scroll_delta {
@@ -490,141 +283,40 @@ impl ApplicationRunner {
                         ),
                     };
 
-                    if self.state.captured != Entity::null() {
-                        self.state.insert_event(
-                            Event::new(WindowEvent::MouseScroll(lines_x, lines_y))
-                                .target(self.state.captured)
-                                .propagate(Propagation::Direct),
-                        );
-                    } else {
-                        self.state.insert_event(
-                            Event::new(WindowEvent::MouseScroll(lines_x, lines_y))
-                                .target(self.state.hovered),
-                        );
-                    }
+                    self.state
+                        .dispatch_input(WindowEvent::MouseScroll(lines_x, lines_y));
                 }
                 _ => {}
             },
             baseview::Event::Keyboard(event) => {
                 use keyboard_types::Code;
 
-                let (s, pressed) = match event.state {
-                    keyboard_types::KeyState::Down => (MouseButtonState::Pressed, true),
-                    keyboard_types::KeyState::Up => (MouseButtonState::Released, false),
+                let s = match event.state {
+                    keyboard_types::KeyState::Down => MouseButtonState::Pressed,
+                    keyboard_types::KeyState::Up => MouseButtonState::Released,
                 };
 
-                match event.code {
-                    Code::ShiftLeft | Code::ShiftRight => self.state.modifiers.shift = pressed,
-                    Code::ControlLeft | Code::ControlRight => self.state.modifiers.ctrl = pressed,
-                    Code::AltLeft | Code::AltRight => self.state.modifiers.alt = pressed,
-                    Code::MetaLeft | Code::MetaRight => self.state.modifiers.logo = pressed,
-                    _ => (),
-                }
-
                 if event.code == Code::F5 && s == MouseButtonState::Pressed {
                     self.state.reload_styles().unwrap();
                 }
 
-                if event.code == Code::Tab && s == MouseButtonState::Pressed {
-                    let next_focus = self
-                        .state
-                        .style
-                        .focus_order
-                        .get(self.state.focused)
-                        .cloned()
-                        .unwrap_or_default()
-                        .next;
-                    let prev_focus = self
-                        .state
-                        .style
-                        .focus_order
-                        .get(self.state.focused)
-                        .cloned()
-                        .unwrap_or_default()
-                        .prev;
-
-                    if self.state.modifiers.shift {
-                        if prev_focus != Entity::null() {
-                            self.state.focused.set_focus(&mut self.state, false);
-                            self.state.focused = prev_focus;
-                            self.state.focused.set_focus(&mut self.state, true);
-                        } else {
-                            // TODO impliment reverse iterator for hierarchy
-                            // state.focused = match state.focused.into_iter(&state.hierarchy).next() {
-                            //     Some(val) => val,
-                            //     None => Entity::root(),
-                            // };
-                        }
-                    } else {
-                        if next_focus != Entity::null() {
-                            self.state.focused.set_focus(&mut self.state, false);
-                            self.state.focused = next_focus;
-                            self.state.focused.set_focus(&mut self.state, true);
-                        } else {
-                            self.state.focused.set_focus(&mut self.state, false);
-                            self.state.focused =
-                                match self.state.focused.into_iter(&self.hierarchy).next() {
-                                    Some(val) => val,
-                                    None => Entity::root(),
-                                };
-                            self.state.focused.set_focus(&mut self.state, true);
-                        }
-                    }
-
-                    self.state.insert_event(
-                        Event::new(WindowEvent::Restyle)
-                            .target(Entity::root())
-                            .origin(Entity::root()),
-                    );
-                }
-
                 match s {
                     MouseButtonState::Pressed => {
-                        if self.state.focused != Entity::null() {
-                            self.state.insert_event(
-                                Event::new(WindowEvent::KeyDown(
-                                    event.code,
-                                    Some(event.key.clone()),
-                                ))
-                                .target(self.state.focused)
-                                .propagate(Propagation::DownUp),
-                            );
-                        } else {
-                            self.state.insert_event(
-                                Event::new(WindowEvent::KeyDown(
-                                    event.code,
-                                    Some(event.key.clone()),
-                                ))
-                                .target(self.state.hovered)
-                                .propagate(Propagation::DownUp),
-                            );
-                        }
+                        self.state.dispatch_input(WindowEvent::KeyDown(
+                            event.code,
+                            Some(event.key.clone()),
+                        ));
 
                         if let keyboard_types::Key::Character(written) = &event.key {
                             for chr in written.chars() {
-                                self.state.insert_event(
-                                    Event::new(WindowEvent::CharInput(chr))
-                                        .target(self.state.focused)
-                                        .propagate(Propagation::Down),
-                                );
+                                self.state.dispatch_input(WindowEvent::CharInput(chr));
                             }
                         }
                     }
 
                     MouseButtonState::Released => {
-                        if self.state.focused != Entity::null() {
-                            self.state.insert_event(
-                                Event::new(WindowEvent::KeyUp(event.code, Some(event.key)))
-                                    .target(self.state.focused)
-                                    .propagate(Propagation::DownUp),
-                            );
-                        } else {
-                            self.state.insert_event(
-                                Event::new(WindowEvent::KeyUp(event.code, Some(event.key)))
-                                    .target(self.state.hovered)
-                                    .propagate(Propagation::DownUp),
-                            );
-                        }
+                        self.state
+                            .dispatch_input(WindowEvent::KeyUp(event.code, Some(event.key)));
                     }
                 }
             }
@@ -652,27 +344,18 @@ impl ApplicationRunner {
                         window_info.physical_size().height,
                     );
 
-                    self.state
-                        .style
-                        .width
-                        .insert(Entity::root(), Length::Pixels(logical_size.0 as f32));
-                    self.state
-                        .style
-                        .height
-                        .insert(Entity::root(), Length::Pixels(logical_size.1 as f32));
+                    self.state.dispatch_input(WindowEvent::WindowResize(
+                        logical_size.0 as f32,
+                        logical_size.1 as f32,
+                    ));
 
+                    // The root data is in physical pixels
                     self.state
                         .data
                         .set_width(Entity::root(), physical_size.0 as f32);
                     self.state
                         .data
                         .set_height(Entity::root(), physical_size.1 as f32);
-
-                    self.state
-                        .insert_event(Event::new(WindowEvent::Restyle).origin(Entity::root()));
-                    self.state
-                        .insert_event(Event::new(WindowEvent::Relayout).target(Entity::null()));
-                    self.state.insert_event(Event::new(WindowEvent::Redraw));
                 }
                 baseview::WindowEvent::WillClose => {
                     self.state
@@ -704,3 +387,14 @@ pub fn requests_exit(event: &baseview::Event) -> bool {
         _ => false,
     }
 }
+
+fn translate_mouse_button(button: baseview::MouseButton) -> MouseButton {
+    match button {
+        baseview::MouseButton::Left => MouseButton::Left,
+        baseview::MouseButton::Right => MouseButton::Right,
+        baseview::MouseButton::Middle => MouseButton::Middle,
+        baseview::MouseButton::Other(id) => MouseButton::Other(id as u16),
+        baseview::MouseButton::Back => MouseButton::Other(4),
+        baseview::MouseButton::Forward => MouseButton::Other(5),
+    }
+}
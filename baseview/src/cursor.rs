@@ -0,0 +1,32 @@
+use baseview::MouseCursor;
+use tuix_core::CursorIcon;
+
+pub fn mouse_cursor(cursor: CursorIcon) -> MouseCursor {
+    match cursor {
+        CursorIcon::Arrow => MouseCursor::Default,
+        CursorIcon::Hand => MouseCursor::Hand,
+        CursorIcon::Text => MouseCursor::Text,
+        CursorIcon::Crosshair => MouseCursor::Crosshair,
+        CursorIcon::Move => MouseCursor::Move,
+        // There is no open hand cursor
+        CursorIcon::Grab => MouseCursor::Hand,
+        CursorIcon::Grabbing => MouseCursor::HandGrabbing,
+        CursorIcon::NotAllowed => MouseCursor::NotAllowed,
+        CursorIcon::Wait => MouseCursor::Working,
+        CursorIcon::Help => MouseCursor::Help,
+        CursorIcon::NResize => MouseCursor::NResize,
+        CursorIcon::EResize => MouseCursor::EResize,
+        CursorIcon::SResize => MouseCursor::SResize,
+        CursorIcon::WResize => MouseCursor::WResize,
+        CursorIcon::NeResize => MouseCursor::NeResize,
+        CursorIcon::NwResize => MouseCursor::NwResize,
+        CursorIcon::SeResize => MouseCursor::SeResize,
+        CursorIcon::SwResize => MouseCursor::SwResize,
+        CursorIcon::EwResize => MouseCursor::EwResize,
+        CursorIcon::NsResize => MouseCursor::NsResize,
+        CursorIcon::NeswResize => MouseCursor::NeswResize,
+        CursorIcon::NwseResize => MouseCursor::NwseResize,
+        CursorIcon::ColResize => MouseCursor::ColResize,
+        CursorIcon::RowResize => MouseCursor::RowResize,
+    }
+}
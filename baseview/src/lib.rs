@@ -1,4 +1,5 @@
 mod application;
+mod cursor;
 mod window;
 
 pub use application::Application;
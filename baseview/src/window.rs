@@ -1,3 +1,4 @@
+use crate::cursor::mouse_cursor;
 use crate::{application::ApplicationRunner, Renderer};
 use baseview::{Event, EventStatus, Window, WindowHandler, WindowOpenOptions, WindowScalePolicy};
 use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
@@ -24,18 +25,15 @@ impl TuixWindow {
     /// Open a new child window.
     ///
     /// * `parent` - The parent window.
+    /// * `state` - The state of the application, created by `Application::new`.
     /// * `app` - The Tuix application builder.
-    pub fn open_parented<P, F>(parent: &P, mut app: F)
+    pub fn open_parented<P, F>(parent: &P, mut state: State, mut app: F)
     where
         P: HasRawWindowHandle,
         F: FnMut(WindowDescription, &mut State, Entity) -> WindowDescription,
         F: 'static + Send,
     {
-        let mut state = State::new();
-
         let root = Entity::root();
-        state.hierarchy.add(Entity::root(), None);
-
         let win_desc = WindowDescription::new();
         let win_desc = (app)(win_desc, &mut state, root);
 
@@ -59,17 +57,14 @@ impl TuixWindow {
 
     /// Open a new window as if it had a parent window.
     ///
+    /// * `state` - The state of the application, created by `Application::new`.
     /// * `app` - The Tuix application builder.
-    pub fn open_as_if_parented<F>(mut app: F) -> RawWindowHandle
+    pub fn open_as_if_parented<F>(mut state: State, mut app: F) -> RawWindowHandle
     where
         F: FnMut(WindowDescription, &mut State, Entity) -> WindowDescription,
         F: 'static + Send,
     {
-        let mut state = State::new();
-
         let root = Entity::root();
-        state.hierarchy.add(Entity::root(), None);
-
         let win_desc = WindowDescription::new();
         let win_desc = (app)(win_desc, &mut state, root);
 
@@ -92,17 +87,14 @@ impl TuixWindow {
 
     /// Open a new window that blocks the current thread until the window is destroyed.
     ///
+    /// * `state` - The state of the application, created by `Application::new`.
     /// * `app` - The Tuix application builder.
-    pub fn open_blocking<F>(mut app: F)
+    pub fn open_blocking<F>(mut state: State, mut app: F)
     where
         F: FnMut(WindowDescription, &mut State, Entity) -> WindowDescription,
         F: 'static + Send,
     {
-        let mut state = State::new();
-
         let root = Entity::root();
-        state.hierarchy.add(Entity::root(), None);
-
         let win_desc = WindowDescription::new();
         let win_desc = (app)(win_desc, &mut state, root);
 
@@ -125,9 +117,13 @@ impl TuixWindow {
 }
 
 impl WindowHandler for TuixWindow {
-    fn on_frame(&mut self, _window: &mut Window) {
+    fn on_frame(&mut self, window: &mut Window) {
         self.application.on_frame_update();
 
+        if let Some(cursor) = self.application.cursor_changed() {
+            window.set_mouse_cursor(mouse_cursor(cursor));
+        }
+
         self.context.make_current();
 
         if self.application.render() {
@@ -1,4 +1,4 @@
-use crate::{Entity, EventHandler, State};
+use crate::{CursorIcon, Entity, EventHandler, State};
 
 use crate::state::style::*;
 
@@ -45,7 +45,7 @@ impl<'a> Builder<'a> {
     }
 
     pub fn set_id(mut self, id: &str) -> Self {
-        self.state.style.insert_id(self.entity, id);
+        self.entity.set_id(self.state, id);
 
         self
     }
@@ -490,6 +490,18 @@ impl<'a> Builder<'a> {
         self
     }
 
+    pub fn set_tab_index(mut self, val: i32) -> Self {
+        self.state.style.tab_index.insert(self.entity, val);
+
+        self
+    }
+
+    pub fn set_cursor(mut self, val: CursorIcon) -> Self {
+        self.state.style.cursor.insert(self.entity, val);
+
+        self
+    }
+
     pub fn set_rotate(mut self, rotate: f32) -> Self {
         self.state.style.rotate.insert(self.entity, rotate);
 
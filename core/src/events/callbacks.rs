@@ -0,0 +1,54 @@
+// Callbacks identified by an id, in the order they were added
+//
+// While the callbacks are being called they are taken out, so callbacks added or removed from
+// within a callback are recorded and applied when they are put back.
+pub(crate) struct Callbacks<F> {
+    callbacks: Vec<(u64, F)>,
+    // Ids of callbacks removed while they were taken out
+    removed: Vec<u64>,
+    taken: bool,
+}
+
+impl<F> Callbacks<F> {
+    pub fn new() -> Self {
+        Callbacks {
+            callbacks: Vec::new(),
+            removed: Vec::new(),
+            taken: false,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.callbacks.is_empty() && !self.taken
+    }
+
+    pub fn push(&mut self, id: u64, callback: F) {
+        self.callbacks.push((id, callback));
+    }
+
+    pub fn remove(&mut self, id: u64) {
+        let len = self.callbacks.len();
+        self.callbacks.retain(|(callback, _)| *callback != id);
+
+        if self.callbacks.len() == len && self.taken && !self.removed.contains(&id) {
+            self.removed.push(id);
+        }
+    }
+
+    pub fn take(&mut self) -> Vec<(u64, F)> {
+        self.taken = true;
+        std::mem::replace(&mut self.callbacks, Vec::new())
+    }
+
+    // Puts back callbacks which were taken, keeping any added in the meantime after them
+    pub fn restore(&mut self, mut callbacks: Vec<(u64, F)>) {
+        if !self.removed.is_empty() {
+            let removed = std::mem::replace(&mut self.removed, Vec::new());
+            callbacks.retain(|(id, _)| !removed.contains(id));
+        }
+
+        callbacks.extend(self.callbacks.drain(..));
+        self.callbacks = callbacks;
+        self.taken = false;
+    }
+}
@@ -15,6 +15,24 @@ pub enum Propagation {
     All,
 }
 
+/// The phase of the dispatch of an event, following the DOM event model
+///
+/// With `Propagation::Down`, `Propagation::Up` and `Propagation::DownUp` the ancestors of the
+/// target receive the event in the capture phase on the way down and in the bubble phase on the
+/// way up. Events with `Propagation::Direct` and `Propagation::Fall` are received in the target
+/// phase by every entity they reach.
+///
+/// `Propagation::All` broadcasts the event to every entity, first in the capture phase going down
+/// the hierarchy from the root and then in the bubble phase going back up to the root, so that a
+/// parent sees the event before and after its children. Stopping the propagation in either phase
+/// stops the rest of the broadcast.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventPhase {
+    Capture,
+    Target,
+    Bubble,
+}
+
 // A message is a wrapper around an Any but with the added ability to Clone the message
 pub trait Message: Any + MessageClone + Debug + Send {
     // An &Any can be cast to a reference to a concrete type.
@@ -22,6 +40,9 @@ pub trait Message: Any + MessageClone + Debug + Send {
 
     // Perform the test.
     fn equals_a(&self, _: &dyn Message) -> bool;
+
+    // The name of the message type, used when tracing events
+    fn type_name(&self) -> &'static str;
 }
 
 // An Any is not normally clonable. This is a way around that.
@@ -86,6 +107,10 @@ impl<S: 'static + PartialEq + std::fmt::Debug + Clone + Send> Message for S {
             .downcast_ref::<S>()
             .map_or(false, |a| self == a)
     }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<S>()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -100,6 +125,10 @@ pub struct Event {
     pub consumable: bool,
     // Determines whether the event should continue to be propagated
     pub(crate) consumed: bool,
+    // Determines whether the remaining listeners of the current entity should be skipped
+    pub(crate) stopped_immediately: bool,
+    // The phase of the dispatch, set by the event manager before each entity receives the event
+    pub phase: EventPhase,
     // Whether the event is unique (only the latest copy can exist in a queue at a time)
     pub unique: bool,
     // Specifies an order index which is used to sort the event queue
@@ -128,6 +157,8 @@ impl Event {
             propagation: Propagation::DownUp,
             consumable: true,
             consumed: false,
+            stopped_immediately: false,
+            phase: EventPhase::Target,
             unique: true,
             order: 0,
             message: Box::new(message),
@@ -161,7 +192,28 @@ impl Event {
     }
 
     /// Consume the event
+    ///
+    /// This is the same as `stop_propagation`.
     pub fn consume(&mut self) {
         self.consumed = true;
     }
+
+    /// Stops the event from being sent to any further entities
+    ///
+    /// The listeners of the current entity are still called.
+    pub fn stop_propagation(&mut self) {
+        self.consumed = true;
+    }
+
+    /// Stops the event from being sent to any further entities or listeners, including the
+    /// remaining listeners of the current entity
+    pub fn stop_immediate(&mut self) {
+        self.consumed = true;
+        self.stopped_immediately = true;
+    }
+
+    /// Returns true if the propagation of the event has been stopped
+    pub fn is_propagation_stopped(&self) -> bool {
+        self.consumed
+    }
 }
@@ -0,0 +1,185 @@
+use crate::events::{Callbacks, Event};
+use crate::state::State;
+
+/// The result of an event filter, which determines whether the event is dispatched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventFilterResult {
+    // Pass the event, which may have been modified, on to the next filter and then dispatch it
+    Pass,
+    // Drop the event without calling any further filters or dispatching it
+    Swallow,
+}
+
+/// A handle to an event filter added with `State::add_event_filter`, which can be used to remove it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EventFilterHandle(u64);
+
+type EventFilterFn = Box<dyn FnMut(&mut State, &mut Event) -> EventFilterResult + Send>;
+
+// Stores the event filters in the order they were added
+pub(crate) struct EventFilters {
+    next_handle: u64,
+    filters: Callbacks<EventFilterFn>,
+}
+
+impl EventFilters {
+    pub fn new() -> Self {
+        EventFilters {
+            next_handle: 0,
+            filters: Callbacks::new(),
+        }
+    }
+
+    pub fn add(&mut self, filter: EventFilterFn) -> EventFilterHandle {
+        let handle = EventFilterHandle(self.next_handle);
+        self.next_handle += 1;
+
+        self.filters.push(handle.0, filter);
+
+        handle
+    }
+
+    pub fn remove(&mut self, handle: EventFilterHandle) {
+        self.filters.remove(handle.0);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    pub fn take(&mut self) -> Vec<(u64, EventFilterFn)> {
+        self.filters.take()
+    }
+
+    pub fn restore(&mut self, filters: Vec<(u64, EventFilterFn)>) {
+        self.filters.restore(filters);
+    }
+}
+
+// Passes an event through the filters and returns false if it was swallowed
+pub(crate) fn apply_event_filters(state: &mut State, event: &mut Event) -> bool {
+    if state.event_filters.is_empty() {
+        return true;
+    }
+
+    let mut filters = state.event_filters.take();
+
+    let mut pass = true;
+    for (_, filter) in filters.iter_mut() {
+        if (filter)(state, event) == EventFilterResult::Swallow {
+            pass = false;
+            break;
+        }
+    }
+
+    state.event_filters.restore(filters);
+
+    pass
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{EventManager, Propagation};
+    use crate::Entity;
+
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Ping(u32);
+
+    // Two widgets which record the pings they receive
+    fn setup() -> (State, Entity, Entity, Arc<Mutex<Vec<(Entity, u32)>>>) {
+        let mut state = State::new();
+        state.hierarchy.add(Entity::root(), None);
+        let first = state.add(Entity::root());
+        let second = state.add(Entity::root());
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        for entity in [first, second].iter() {
+            let log = received.clone();
+            state.on::<Ping, _>(*entity, move |_, entity, ping, _| {
+                log.lock().unwrap().push((entity, ping.0));
+            });
+        }
+
+        (state, first, second, received)
+    }
+
+    fn ping(state: &mut State, target: Entity, id: u32) {
+        state.insert_event(Event::new(Ping(id)).target(target).propagate(Propagation::Direct));
+    }
+
+    #[test]
+    fn swallowed_events_are_not_dispatched() {
+        let (mut state, first, _, received) = setup();
+        let calls = Arc::new(Mutex::new(0));
+
+        state.add_event_filter(|_, event| match event.message.downcast::<Ping>() {
+            Some(Ping(1)) => EventFilterResult::Swallow,
+            _ => EventFilterResult::Pass,
+        });
+        let log = calls.clone();
+        state.add_event_filter(move |_, event| {
+            if event.message.downcast::<Ping>().is_some() {
+                *log.lock().unwrap() += 1;
+            }
+            EventFilterResult::Pass
+        });
+
+        ping(&mut state, first, 1);
+        ping(&mut state, first, 2);
+        EventManager::new().flush_events(&mut state);
+
+        // The swallowed event is not passed to the later filter either
+        assert_eq!(*received.lock().unwrap(), vec![(first, 2)]);
+        assert_eq!(*calls.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn filters_can_redirect_events() {
+        let (mut state, first, second, received) = setup();
+
+        state.add_event_filter(move |_, event| {
+            if event.target == first {
+                event.target = second;
+            }
+            EventFilterResult::Pass
+        });
+
+        ping(&mut state, first, 1);
+        EventManager::new().flush_events(&mut state);
+
+        assert_eq!(*received.lock().unwrap(), vec![(second, 1)]);
+    }
+
+    #[test]
+    fn filter_can_remove_itself() {
+        let (mut state, first, _, received) = setup();
+        let handle = Arc::new(Mutex::new(None));
+        let calls = Arc::new(Mutex::new(0));
+
+        // Swallows the first ping and removes itself
+        let (own_handle, log) = (handle.clone(), calls.clone());
+        let filter = state.add_event_filter(move |state, event| {
+            if event.message.downcast::<Ping>().is_none() {
+                return EventFilterResult::Pass;
+            }
+
+            *log.lock().unwrap() += 1;
+            if let Some(handle) = *own_handle.lock().unwrap() {
+                state.remove_event_filter(handle);
+            }
+            EventFilterResult::Swallow
+        });
+        *handle.lock().unwrap() = Some(filter);
+
+        ping(&mut state, first, 1);
+        ping(&mut state, first, 2);
+        EventManager::new().flush_events(&mut state);
+
+        assert_eq!(*calls.lock().unwrap(), 1);
+        assert_eq!(*received.lock().unwrap(), vec![(first, 2)]);
+        assert!(state.event_filters.is_empty());
+    }
+}
@@ -9,23 +9,18 @@ use crate::{Entity, Hierarchy, State};
 
 use std::collections::{HashMap, VecDeque};
 
-use femtovg::{
-    renderer::OpenGl, Align, Baseline, FillRule, FontId, ImageFlags, ImageId, LineCap, LineJoin,
-    Paint, Path, Renderer, Solidity,
-};
+use crate::renderer::{Canvas, Paint, Path, Solidity, TextAlign, TextBaseline};
 
 use crate::style::{Justify, Length, Visibility, Direction};
 
 use std::any::{Any, TypeId};
 
-pub type Canvas = femtovg::Canvas<OpenGl>;
-
 pub trait EventHandler: Any + Send {
     // Called when events are flushed
     fn on_event(&mut self, state: &mut State, entity: Entity, event: &mut Event) {}
 
     // Called when a redraw occurs
-    fn on_draw(&mut self, state: &mut State, entity: Entity, canvas: &mut Canvas) {
+    fn on_draw(&mut self, state: &mut State, entity: Entity, canvas: &mut dyn Canvas) {
         //println!("Redraw");
 
 
@@ -169,14 +164,11 @@ pub trait EventHandler: Any + Send {
 
         let opacity = state.data.get_opacity(entity);
 
-        let mut background_color: femtovg::Color = background_color.into();
-        background_color.set_alphaf(background_color.a * opacity);
+        let background_color = background_color.with_opacity(opacity);
 
-        let mut border_color: femtovg::Color = border_color.into();
-        border_color.set_alphaf(border_color.a * opacity);
+        let border_color = border_color.with_opacity(opacity);
 
-        let mut shadow_color: femtovg::Color = shadow_color.into();
-        shadow_color.set_alphaf(shadow_color.a * opacity);
+        let shadow_color = shadow_color.with_opacity(opacity);
 
         let border_width = match state
             .style
@@ -266,8 +258,7 @@ pub trait EventHandler: Any + Send {
             .cloned()
             .unwrap_or_default();
 
-        let mut shadow_color: femtovg::Color = shadow_color.into();
-        shadow_color.set_alphaf(shadow_color.a * opacity);
+        let shadow_color = shadow_color.with_opacity(opacity);
 
         // Draw shadow (TODO)
         let mut path = Path::new();
@@ -290,7 +281,7 @@ pub trait EventHandler: Any + Send {
         path.solidity(Solidity::Hole);
         //let mut paint = Paint::color(shadow_color);
 
-        let mut paint = Paint::box_gradient(
+        let paint = Paint::box_gradient(
             0.0 + shadow_h_offset,
             0.0 + shadow_v_offset,
             width,
@@ -298,10 +289,10 @@ pub trait EventHandler: Any + Send {
             border_radius_top_left,
             shadow_blur,
             shadow_color,
-            femtovg::Color::rgba(0, 0, 0, 0),
+            crate::Color::rgba(0, 0, 0, 0),
         );
 
-        canvas.fill_path(&mut path, paint);
+        canvas.fill_path(&path, &paint);
 
         let mut path = Path::new();
 
@@ -341,20 +332,17 @@ pub trait EventHandler: Any + Send {
             };
 
             paint = Paint::linear_gradient_stops(start_x, start_y, end_x, end_y, 
-                background_gradient.get_stops(parent_width).iter().map(|stop| {
-                    let col: femtovg::Color = stop.1.into();
-                    (stop.0, col)
-                }).collect::<Vec<_>>().as_slice());
+                background_gradient.get_stops(parent_width).as_slice());
         }
 
         
 
-        canvas.fill_path(&mut path, paint);
+        canvas.fill_path(&path, &paint);
 
         // Draw border
         let mut paint = Paint::color(border_color);
         paint.set_line_width(border_width);
-        canvas.stroke_path(&mut path, paint);
+        canvas.stroke_path(&path, &paint);
 
         
 
@@ -392,46 +380,45 @@ pub trait EventHandler: Any + Send {
             let align = match text_justify {
                 Justify::Start => {
                     x += padding_left;
-                    Align::Left
+                    TextAlign::Left
                 }
                 Justify::Center => {
                     x += 0.5 * width;
-                    Align::Center
+                    TextAlign::Center
                 }
                 Justify::End => {
                     x += width - padding_right;
-                    Align::Right
+                    TextAlign::Right
                 }
             };
 
             let baseline = match text_align {
                 crate::Align::Start => {
                     y += padding_top;
-                    Baseline::Top
+                    TextBaseline::Top
                 }
                 crate::Align::Center => {
                     y += 0.5 * height;
-                    Baseline::Middle
+                    TextBaseline::Middle
                 }
                 crate::Align::End => {
                     y += height - padding_bottom;
-                    Baseline::Bottom
+                    TextBaseline::Bottom
                 }
             };
 
-            let mut font_color: femtovg::Color = font_color.into();
-            font_color.set_alphaf(font_color.a * opacity);
+            let font_color = font_color.with_opacity(opacity);
 
             let font_size = state.style.font_size.get(entity).cloned().unwrap_or(16.0);
 
             let mut paint = Paint::color(font_color);
             paint.set_font_size(font_size);
-            paint.set_font(&[font_id]);
+            paint.set_font(font_id);
             paint.set_text_align(align);
             paint.set_text_baseline(baseline);
             paint.set_anti_alias(false);
 
-            canvas.fill_text(x, y, &text_string, paint);
+            canvas.fill_text(x, y, &text_string, &paint);
         }
 
         canvas.translate(-posx, -posy);
@@ -845,6 +832,18 @@ impl dyn EventHandler {
             None
         }
     }
+
+    // Casts an event handler to a reference of the specified type if it is of that type
+    pub fn downcast_ref<T>(&self) -> Option<&T>
+    where
+        T: EventHandler + 'static,
+    {
+        if self.is::<T>() {
+            unsafe { Some(&*(self as *const dyn EventHandler as *const T)) }
+        } else {
+            None
+        }
+    }
 }
 
 // pub trait AsAny: Any {
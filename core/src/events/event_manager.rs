@@ -1,7 +1,7 @@
 use crate::{
     BuildHandler, Builder, CursorIcon, Entity, Event, EventHandler, Hierarchy, HierarchyTree,
     IntoBranchIterator, IntoHierarchyIterator, IntoParentIterator, PropSet, Propagation, State,
-    WindowEvent,
+    WindowCommand, WindowEvent,
 };
 use std::{
     collections::{HashMap, VecDeque},
@@ -10,16 +10,10 @@ use std::{
 
 use std::time::{Duration, Instant};
 
-use femtovg::{
-    renderer::OpenGl, Align, Baseline, Canvas, Color, FillRule, FontId, ImageFlags, ImageId,
-    LineCap, LineJoin, Paint, Path, Renderer, Solidity,
-};
-
-use fnv::FnvHashMap;
+use crate::events::{apply_event_filters, EventPhase, EventTrace, EventTraceStatus};
+use crate::renderer::Canvas;
 
 pub struct EventManager {
-    pub event_handlers: FnvHashMap<Entity, Box<dyn EventHandler>>,
-
     // Queue of events to be processed
     pub event_queue: Vec<Event>,
 
@@ -31,7 +25,6 @@ pub struct EventManager {
 impl EventManager {
     pub fn new() -> Self {
         EventManager {
-            event_handlers: FnvHashMap::default(),
             event_queue: Vec::new(),
 
             prev_width: 0.0,
@@ -54,15 +47,26 @@ impl EventManager {
         // Clear the event queue in the event manager
         self.event_queue.clear();
 
-        // Move event handlers from state to event manager
-        self.event_handlers.extend(state.event_handlers.drain());
+        // Notify removed widgets and then drop their event handlers and listeners
+        let removed_entities = std::mem::replace(&mut state.removed_entities, Vec::new());
+        for entity in removed_entities.into_iter() {
+            let mut event = Event::new(WindowEvent::WidgetRemoved)
+                .target(entity)
+                .origin(entity)
+                .propagate(Propagation::Direct);
+            let mut trace = state.event_tracer.as_ref().map(|_| EventTrace::new(&event));
 
-        // Remove widgets that should be removed
-        for entity in state.removed_entities.iter() {
-            self.event_handlers.remove(entity);            
-        }
+            send_event(state, entity, EventPhase::Target, &mut event, &mut trace);
 
-        state.removed_entities.clear();
+            state.event_handlers.remove(&entity);
+            state.listeners.remove_entity(entity);
+
+            if let (Some(tracer), Some(trace)) = (state.event_tracer.as_mut(), trace) {
+                if !trace.handlers.is_empty() {
+                    tracer.record(trace);
+                }
+            }
+        }
 
 
         // Clone events from state into event manager
@@ -76,154 +80,66 @@ impl EventManager {
         state.event_queue.clear();
 
         // Loop over the events in the event manager queue
-        'events: for event in self.event_queue.iter_mut() {
-            //println!("Event: {:?}", event);
+        for event in self.event_queue.iter_mut() {
+            // Trace the dispatch of the event if tracing is enabled
+            let mut trace = state.event_tracer.as_ref().map(|_| EventTrace::new(event));
 
-            if event.target == Entity::null() {
-                continue 'events;
-            }
-
-            if let Some(window_event) = event.message.downcast::<WindowEvent>() {
-                match window_event {
-                    WindowEvent::Redraw => {
-                        needs_redraw = true;
-                    }
-
-                    /*
-                    WindowEvent::SetCursor(cursor_icon) => match cursor_icon {
-                        CursorIcon::Arrow => {
-                            window
-                                .handle
-                                .window()
-                                .set_cursor_icon(glutin::window::CursorIcon::Arrow);
-                        }
-
-                        CursorIcon::NResize => {
-                            window
-                                .handle
-                                .window()
-                                .set_cursor_icon(glutin::window::CursorIcon::NResize);
+            if !apply_event_filters(state, event) {
+                if let Some(trace) = trace.as_mut() {
+                    trace.status = EventTraceStatus::Filtered;
+                }
+            } else if event.target == Entity::null() {
+                if let Some(trace) = trace.as_mut() {
+                    trace.status = EventTraceStatus::NoTarget;
+                }
+            } else if !state.is_alive(event.target) {
+                // Skip events sent to entities which have been removed
+                if let Some(trace) = trace.as_mut() {
+                    trace.status = EventTraceStatus::TargetRemoved;
+                }
+            } else {
+                if let Some(window_event) = event.message.downcast::<WindowEvent>() {
+                    match window_event {
+                        WindowEvent::Redraw => {
+                            needs_redraw = true;
                         }
 
-                        CursorIcon::EResize => {
-                            window
-                                .handle
-                                .window()
-                                .set_cursor_icon(glutin::window::CursorIcon::EResize);
+                        WindowEvent::SetCursor(cursor) => {
+                            state.set_cursor(*cursor);
                         }
-                    },
-                    */
-                    _ => {}
-                }
-            }
 
-            // Define the target to prevent multiple mutable borrows error
-            let target = event.target;
-
-            // A null entity as target means send event to all entities
-            if event.propagation == Propagation::All {
-                for entity in hierarchy.into_iter() {
-                    if let Some(event_handler) = self.event_handlers.get_mut(&entity) {
-                        event_handler.on_event(state, entity, event);
-
-                        if event.consumed {
-                            break;
-                        }
+                        _ => {}
                     }
                 }
-                continue 'events;
-            }
 
-            // Propagate down from root to target (not including target)
-            if event.propagation == Propagation::Down || event.propagation == Propagation::DownUp {
-                // Construct the list of widgets to walk down by going up from the target
-                let ancestors: Vec<Entity> = event
-                    .target
-                    .parent_iter(&hierarchy)
-                    .collect::<Vec<Entity>>();
-
-                // Walk down the list of ancestors
-                for entity in ancestors.iter().rev() {
-                    // Skip the window
-                    if *entity == Entity::root() {
-                        continue;
-                    }
-
-                    // Stop before the target entity
-                    if *entity == event.target {
-                        break;
-                    }
-
-                    // Send event to all ancestors before the target
-                    if let Some(event_handler) = self.event_handlers.get_mut(&entity) {
-                        event_handler.on_event(state, *entity, event);
-
-                        // Skip to the next event if the current event is consumed
-                        if event.consumed {
-                            continue 'events;
-                        }
-                    }
+                if let Some(window_command) = event.message.downcast::<WindowCommand>() {
+                    state.window_command(window_command.clone());
                 }
-            }
-
-            if event.propagation != Propagation::Fall {
-                // Send event to target
-                if let Some(event_handler) = self.event_handlers.get_mut(&event.target) {
-                    event_handler.on_event(state, event.target, event);
 
-                    if event.consumed {
-                        continue 'events;
-                    }
+                // Filters may have redirected the event
+                if let Some(trace) = trace.as_mut() {
+                    trace.target = event.target;
                 }
-            }
 
-            // Propagate up from target to root (not including target)
-            if event.propagation == Propagation::Up || event.propagation == Propagation::DownUp {
-                // Walk up the hierarchy from parent to parent
-                for entity in target.parent_iter(&hierarchy) {
-                    // Skip the target entity
-                    if entity == event.target {
-                        continue;
-                    }
-
-                    // Send event to all entities before the target
-                    if let Some(event_handler) = self.event_handlers.get_mut(&entity) {
-                        event_handler.on_event(state, entity, event);
+                dispatch_event(state, &hierarchy, event, &mut trace);
 
-                        // Skip to the next event if the current event is consumed
-                        if event.consumed {
-                            continue 'events;
-                        }
+                // The window closes unless a handler consumed the close request
+                if let Some(WindowEvent::WindowCloseRequested) = event.message.downcast() {
+                    if !event.consumed {
+                        state.window_command(WindowCommand::Close);
                     }
                 }
             }
 
-            // Propagate down from target to leaf of current branch
-            if event.propagation == Propagation::Fall {
-                // Walk hierarchy from the target down the branch
-                for entity in target.branch_iter(&hierarchy) {
-                    // Skip the target entity
-                    if entity == event.target {
-                        continue;
-                    }
-
-                    // Send event to all entities after the target on the same branch
-                    if let Some(event_handler) = self.event_handlers.get_mut(&entity) {
-                        event_handler.on_event(state, entity, event);
-
-                        // Skip to the next event if the current event is consumed
-                        if event.consumed {
-                            continue 'events;
-                        }
-                    }
-                }
+            if let (Some(tracer), Some(trace)) = (state.event_tracer.as_mut(), trace) {
+                tracer.record(trace);
             }
         }
 
         return needs_redraw;
     }
 
-    pub fn draw(&mut self, state: &mut State, hierarchy: &Hierarchy, canvas: &mut Canvas<OpenGl>) {
+    pub fn draw(&mut self, state: &mut State, hierarchy: &Hierarchy, canvas: &mut dyn Canvas) {
         //let dpi_factor = window.handle.window().scale_factor();
         //let size = window.handle.window().inner_size();
 
@@ -241,13 +157,12 @@ impl EventManager {
         }
 
         // Get the desired window background color
-        let background_color: femtovg::Color = state
+        let background_color = state
             .style
             .background_color
             .get(Entity::root())
             .cloned()
-            .unwrap_or_default()
-            .into();
+            .unwrap_or_default();
 
         // Clear the canvas
         canvas.clear_rect(0, 0, width as u32, height as u32, background_color);
@@ -261,8 +176,9 @@ impl EventManager {
 
         // Call the on_draw() method for each widget
         for widget in draw_hierarchy.into_iter() {
-            if let Some(event_handler) = self.event_handlers.get_mut(&widget) {
+            if let Some(mut event_handler) = state.event_handlers.remove(&widget) {
                 event_handler.on_draw(state, widget, canvas);
+                state.event_handlers.entry(widget).or_insert(event_handler);
             }
         }
 
@@ -270,3 +186,334 @@ impl EventManager {
         canvas.flush();
     }
 }
+
+// Sends an event to the event handler and listeners of an entity in the given phase and returns
+// true if the propagation of the event was stopped
+fn send_event(
+    state: &mut State,
+    entity: Entity,
+    phase: EventPhase,
+    event: &mut Event,
+    trace: &mut Option<EventTrace>,
+) -> bool {
+    let mut handled = false;
+
+    event.phase = phase;
+
+    // The event handler is taken out while it is called so that other widgets can be accessed
+    // through the state, and is put back unless the entity was given a new one in the meantime
+    if let Some(mut event_handler) = state.event_handlers.remove(&entity) {
+        event_handler.on_event(state, entity, event);
+        state.event_handlers.entry(entity).or_insert(event_handler);
+        handled = true;
+    }
+
+    // The listeners are taken out while they are called so that they can access the state
+    if !event.stopped_immediately {
+        if let Some(mut listeners) = state.listeners.take(entity) {
+            for (_, listener) in listeners.iter_mut() {
+                (listener)(state, entity, event);
+
+                if event.stopped_immediately {
+                    break;
+                }
+            }
+            state.listeners.restore(entity, listeners);
+            handled = true;
+        }
+    }
+
+    if !handled {
+        return false;
+    }
+
+    if let Some(trace) = trace.as_mut() {
+        trace.handlers.push(entity);
+        if event.consumed {
+            trace.status = EventTraceStatus::Consumed(entity);
+        }
+    }
+
+    event.consumed
+}
+
+// Sends an event along its propagation path, stopping when its propagation is stopped
+fn dispatch_event(
+    state: &mut State,
+    hierarchy: &Hierarchy,
+    event: &mut Event,
+    trace: &mut Option<EventTrace>,
+) {
+    // Define the target to prevent multiple mutable borrows error
+    let target = event.target;
+
+    // Broadcast: capture down the whole hierarchy from the root, then bubble back up to it
+    if event.propagation == Propagation::All {
+        let entities = hierarchy.into_iter().collect::<Vec<Entity>>();
+
+        for entity in entities.iter() {
+            if send_event(state, *entity, EventPhase::Capture, event, trace) {
+                return;
+            }
+        }
+
+        for entity in entities.iter().rev() {
+            if send_event(state, *entity, EventPhase::Bubble, event, trace) {
+                return;
+            }
+        }
+
+        return;
+    }
+
+    // Capture phase: propagate down from root to target (not including target)
+    if event.propagation == Propagation::Down || event.propagation == Propagation::DownUp {
+        // Construct the list of widgets to walk down by going up from the target
+        let ancestors: Vec<Entity> = target.parent_iter(hierarchy).collect::<Vec<Entity>>();
+
+        // Walk down the list of ancestors
+        for entity in ancestors.into_iter().rev() {
+            // Skip the window
+            if entity == Entity::root() {
+                continue;
+            }
+
+            // Stop before the target entity
+            if entity == target {
+                break;
+            }
+
+            // Send event to all ancestors before the target
+            if send_event(state, entity, EventPhase::Capture, event, trace) {
+                return;
+            }
+        }
+    }
+
+    // Target phase
+    if event.propagation != Propagation::Fall {
+        // Send event to target
+        if send_event(state, target, EventPhase::Target, event, trace) {
+            return;
+        }
+    }
+
+    // Bubble phase: propagate up from target to root (not including target)
+    if event.propagation == Propagation::Up || event.propagation == Propagation::DownUp {
+        // Walk up the hierarchy from parent to parent
+        for entity in target.parent_iter(hierarchy) {
+            // Skip the target entity
+            if entity == target {
+                continue;
+            }
+
+            // Send event to all entities before the target
+            if send_event(state, entity, EventPhase::Bubble, event, trace) {
+                return;
+            }
+        }
+    }
+
+    // Propagate down from target to leaf of current branch
+    if event.propagation == Propagation::Fall {
+        // Walk hierarchy from the target down the branch
+        for entity in target.branch_iter(hierarchy) {
+            // Skip the target entity
+            if entity == target {
+                continue;
+            }
+
+            // Send event to all entities after the target on the same branch
+            if send_event(state, entity, EventPhase::Target, event, trace) {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Ping;
+
+    type Log = Arc<Mutex<Vec<(Entity, EventPhase)>>>;
+
+    // Records the pings an entity receives, stopping their propagation in the given phase
+    struct Recorder {
+        log: Log,
+        stop_in: Option<EventPhase>,
+    }
+
+    impl EventHandler for Recorder {
+        fn on_event(&mut self, _: &mut State, entity: Entity, event: &mut Event) {
+            if event.message.downcast::<Ping>().is_some() {
+                self.log.lock().unwrap().push((entity, event.phase));
+                if self.stop_in == Some(event.phase) {
+                    event.stop_propagation();
+                }
+            }
+        }
+    }
+
+    // A window containing a panel, which contains a button, and a sibling of the panel
+    fn tree(log: &Log) -> (State, Entity, Entity, Entity) {
+        let mut state = State::new();
+        state.hierarchy.add(Entity::root(), None);
+        let panel = state.add(Entity::root());
+        let button = state.add(panel);
+        let sibling = state.add(Entity::root());
+
+        for entity in [Entity::root(), panel, button, sibling].iter() {
+            state.build(
+                *entity,
+                Recorder {
+                    log: log.clone(),
+                    stop_in: None,
+                },
+            );
+        }
+
+        (state, panel, button, sibling)
+    }
+
+    fn dispatch(state: &mut State, event: Event) {
+        state.insert_event(event);
+        EventManager::new().flush_events(state);
+    }
+
+    #[test]
+    fn down_up_captures_then_bubbles() {
+        let log = Log::default();
+        let (mut state, panel, button, _) = tree(&log);
+
+        dispatch(&mut state, Event::new(Ping).target(button));
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                (panel, EventPhase::Capture),
+                (button, EventPhase::Target),
+                (panel, EventPhase::Bubble),
+                (Entity::root(), EventPhase::Bubble),
+            ]
+        );
+    }
+
+    #[test]
+    fn all_captures_down_the_hierarchy_then_bubbles_up() {
+        let log = Log::default();
+        let (mut state, panel, button, sibling) = tree(&log);
+
+        dispatch(
+            &mut state,
+            Event::new(Ping)
+                .target(Entity::root())
+                .propagate(Propagation::All),
+        );
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                (Entity::root(), EventPhase::Capture),
+                (panel, EventPhase::Capture),
+                (button, EventPhase::Capture),
+                (sibling, EventPhase::Capture),
+                (sibling, EventPhase::Bubble),
+                (button, EventPhase::Bubble),
+                (panel, EventPhase::Bubble),
+                (Entity::root(), EventPhase::Bubble),
+            ]
+        );
+    }
+
+    #[test]
+    fn all_stops_in_the_phase_it_is_consumed() {
+        let log = Log::default();
+        let (mut state, panel, button, sibling) = tree(&log);
+        let all = Event::new(Ping)
+            .target(Entity::root())
+            .propagate(Propagation::All);
+
+        // Consumed on the way down, so the descendants and the bubble phase are skipped
+        state.build(
+            panel,
+            Recorder {
+                log: log.clone(),
+                stop_in: Some(EventPhase::Capture),
+            },
+        );
+        dispatch(&mut state, all.clone());
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                (Entity::root(), EventPhase::Capture),
+                (panel, EventPhase::Capture),
+            ]
+        );
+
+        // Consumed on the way up, so every entity has seen it once
+        log.lock().unwrap().clear();
+        state.build(
+            panel,
+            Recorder {
+                log: log.clone(),
+                stop_in: Some(EventPhase::Bubble),
+            },
+        );
+        dispatch(&mut state, all);
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                (Entity::root(), EventPhase::Capture),
+                (panel, EventPhase::Capture),
+                (button, EventPhase::Capture),
+                (sibling, EventPhase::Capture),
+                (sibling, EventPhase::Bubble),
+                (button, EventPhase::Bubble),
+                (panel, EventPhase::Bubble),
+            ]
+        );
+    }
+
+    #[test]
+    fn stop_propagation_calls_remaining_listeners_but_stop_immediate_does_not() {
+        for &immediate in [false, true].iter() {
+            let log = Log::default();
+            let (mut state, panel, button, _) = tree(&log);
+            let calls = Arc::new(Mutex::new(Vec::new()));
+
+            let first = calls.clone();
+            state.on::<Ping, _>(button, move |_, _, _, context| {
+                first.lock().unwrap().push("first");
+                if immediate {
+                    context.stop_immediate();
+                } else {
+                    context.stop_propagation();
+                }
+            });
+            let second = calls.clone();
+            state.on::<Ping, _>(button, move |_, _, _, _| {
+                second.lock().unwrap().push("second");
+            });
+
+            dispatch(&mut state, Event::new(Ping).target(button));
+
+            let expected = if immediate {
+                vec!["first"]
+            } else {
+                vec!["first", "second"]
+            };
+            assert_eq!(*calls.lock().unwrap(), expected);
+
+            // Either way the event does not bubble past the button
+            assert_eq!(
+                *log.lock().unwrap(),
+                vec![(panel, EventPhase::Capture), (button, EventPhase::Target)]
+            );
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::events::Event;
+
+use std::sync::mpsc::{channel, Receiver, SendError, Sender};
+use std::sync::{Arc, Mutex};
+
+// A function provided by the backend which wakes the event loop
+type Waker = Box<dyn Fn() + Send>;
+
+/// A handle for sending events to the application from other threads
+///
+/// Proxies are created with `State::event_proxy` and can be cloned and moved to other threads.
+/// Events sent through a proxy are added to the event queue at the start of the next iteration
+/// of the event loop, and backends which sleep while idle are woken up to process them.
+///
+/// # Examples
+///
+/// ```ignore
+/// let proxy = state.event_proxy();
+///
+/// std::thread::spawn(move || {
+///     let samples = load_file("audio.wav");
+///     proxy.send(Event::new(AppEvent::FileLoaded(samples)).target(entity)).ok();
+/// });
+/// ```
+#[derive(Clone)]
+pub struct EventProxy {
+    sender: Sender<Event>,
+    waker: Arc<Mutex<Option<Waker>>>,
+}
+
+impl EventProxy {
+    /// Sends an event to the application and wakes the event loop
+    ///
+    /// Returns the event as an error if the application has closed.
+    pub fn send(&self, event: Event) -> Result<(), SendError<Event>> {
+        self.sender.send(event)?;
+
+        if let Ok(waker) = self.waker.lock() {
+            if let Some(wake) = waker.as_ref() {
+                (wake)();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// The receiving end of the event proxies, owned by State
+pub(crate) struct EventProxyReceiver {
+    sender: Sender<Event>,
+    receiver: Receiver<Event>,
+    waker: Arc<Mutex<Option<Waker>>>,
+}
+
+impl EventProxyReceiver {
+    pub fn new() -> Self {
+        let (sender, receiver) = channel();
+
+        EventProxyReceiver {
+            sender,
+            receiver,
+            waker: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn proxy(&self) -> EventProxy {
+        EventProxy {
+            sender: self.sender.clone(),
+            waker: self.waker.clone(),
+        }
+    }
+
+    pub fn set_waker(&self, waker: Waker) {
+        if let Ok(mut current) = self.waker.lock() {
+            *current = Some(waker);
+        }
+    }
+
+    pub fn try_iter(&self) -> impl Iterator<Item = Event> + '_ {
+        self.receiver.try_iter()
+    }
+}
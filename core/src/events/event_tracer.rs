@@ -0,0 +1,188 @@
+use crate::entity::Entity;
+use crate::events::{Event, Propagation};
+
+use std::collections::VecDeque;
+use std::fmt;
+
+/// What happened to a traced event
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventTraceStatus {
+    // The event was sent to every handler on its propagation path
+    Dispatched,
+    // The event was consumed by the entity and not sent any further
+    Consumed(Entity),
+    // The event had a null target and was dropped
+    NoTarget,
+    // The target of the event had been removed and the event was dropped
+    TargetRemoved,
+    // The event was swallowed by an event filter
+    Filtered,
+}
+
+/// A record of how an event was dispatched by the event manager
+#[derive(Debug, Clone)]
+pub struct EventTrace {
+    /// The number of the trace, counting from when tracing was enabled
+    pub index: u64,
+    /// The type name of the event message
+    pub message_type: &'static str,
+    /// The event message formatted with `Debug`
+    pub message: String,
+    pub origin: Entity,
+    pub target: Entity,
+    pub propagation: Propagation,
+    /// The entities whose `on_event` was called, in the order they were called
+    pub handlers: Vec<Entity>,
+    pub status: EventTraceStatus,
+}
+
+impl EventTrace {
+    pub(crate) fn new(event: &Event) -> Self {
+        EventTrace {
+            index: 0,
+            message_type: event.message.type_name(),
+            message: format!("{:?}", event.message),
+            origin: event.origin,
+            target: event.target,
+            propagation: event.propagation.clone(),
+            handlers: Vec::new(),
+            status: EventTraceStatus::Dispatched,
+        }
+    }
+
+    /// Returns true if the `on_event` of the entity was called with the event
+    pub fn reached(&self, entity: Entity) -> bool {
+        self.handlers.contains(&entity)
+    }
+
+    /// Returns the entity which consumed the event, if any
+    pub fn consumed_by(&self) -> Option<Entity> {
+        match self.status {
+            EventTraceStatus::Consumed(entity) => Some(entity),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for EventTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} {} ({}) origin: {} target: {} propagation: {:?} handlers: [",
+            self.index, self.message, self.message_type, self.origin, self.target, self.propagation
+        )?;
+
+        for (index, entity) in self.handlers.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", entity)?;
+        }
+
+        write!(f, "]")?;
+
+        match self.status {
+            EventTraceStatus::Dispatched => Ok(()),
+            EventTraceStatus::Consumed(entity) => write!(f, " consumed by: {}", entity),
+            EventTraceStatus::NoTarget => write!(f, " dropped: no target"),
+            EventTraceStatus::TargetRemoved => write!(f, " dropped: target removed"),
+            EventTraceStatus::Filtered => write!(f, " dropped: filtered"),
+        }
+    }
+}
+
+/// Records the dispatch of events into a ring buffer and an optional sink
+///
+/// Tracing is enabled with `State::enable_event_tracing`. The buffer keeps the most recent traces,
+/// dropping the oldest when it is full, so a debug panel can show why an event did or did not
+/// reach a widget. The sink is called with every trace as it is recorded.
+///
+/// # Examples
+///
+/// ```ignore
+/// state
+///     .enable_event_tracing(256)
+///     .set_sink(|trace| println!("{}", trace));
+///
+/// // Later
+/// if let Some(tracer) = state.event_tracer() {
+///     for trace in tracer.traces().filter(|trace| trace.target == button) {
+///         println!("{:?} reached: {:?}", trace.message, trace.handlers);
+///     }
+/// }
+/// ```
+pub struct EventTracer {
+    capacity: usize,
+    next_index: u64,
+    traces: VecDeque<EventTrace>,
+    sink: Option<Box<dyn FnMut(&EventTrace) + Send>>,
+}
+
+impl EventTracer {
+    pub fn new(capacity: usize) -> Self {
+        EventTracer {
+            capacity,
+            next_index: 0,
+            traces: VecDeque::with_capacity(capacity),
+            sink: None,
+        }
+    }
+
+    /// Sets a function which is called with each trace as it is recorded
+    pub fn set_sink<F>(&mut self, sink: F) -> &mut Self
+    where
+        F: FnMut(&EventTrace),
+        F: 'static + Send,
+    {
+        self.sink = Some(Box::new(sink));
+
+        self
+    }
+
+    /// The maximum number of traces kept in the buffer
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Iterates the traces in the buffer from oldest to newest
+    pub fn traces(&self) -> impl DoubleEndedIterator<Item = &EventTrace> {
+        self.traces.iter()
+    }
+
+    /// Returns the most recent trace
+    pub fn last(&self) -> Option<&EventTrace> {
+        self.traces.back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.traces.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.traces.is_empty()
+    }
+
+    /// Removes the traces from the buffer
+    pub fn clear(&mut self) {
+        self.traces.clear();
+    }
+
+    pub(crate) fn record(&mut self, mut trace: EventTrace) {
+        trace.index = self.next_index;
+        self.next_index += 1;
+
+        if let Some(sink) = self.sink.as_mut() {
+            (sink)(&trace);
+        }
+
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.traces.len() == self.capacity {
+            self.traces.pop_front();
+        }
+
+        self.traces.push_back(trace);
+    }
+}
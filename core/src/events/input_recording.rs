@@ -0,0 +1,720 @@
+use crate::state::mouse::MouseButton;
+use crate::state::State;
+use crate::window_event::WindowEvent;
+
+use keyboard_types::{Code, Key};
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// An input and the time at which it happened, relative to the start of the recording
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedInput {
+    pub time: Duration,
+    pub input: WindowEvent,
+}
+
+/// A sequence of timestamped inputs which can be saved to a file and replayed
+///
+/// Recordings are saved as text with one input per line, a time in seconds followed by the
+/// input, so that they can be attached to bug reports and read or edited by hand:
+///
+/// ```text
+/// 0.016000 MouseMove 120 48.5
+/// 0.250000 MouseDown Left
+/// 0.310000 MouseUp Left
+/// 1.200000 KeyDown KeyA a
+/// 1.200000 CharInput 97
+/// 1.280000 KeyUp KeyA a
+/// 2.000000 ImePreedit 6 6 かな
+/// 2.100000 ImeCommit 仮名
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputRecording {
+    pub inputs: Vec<RecordedInput>,
+}
+
+impl InputRecording {
+    pub fn new() -> Self {
+        InputRecording { inputs: Vec::new() }
+    }
+
+    pub fn push(&mut self, time: Duration, input: WindowEvent) {
+        self.inputs.push(RecordedInput { time, input });
+    }
+
+    /// The time of the last input
+    pub fn duration(&self) -> Duration {
+        self.inputs
+            .last()
+            .map(|input| input.time)
+            .unwrap_or_default()
+    }
+
+    /// Parses a recording from text in the format written by `save`
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn parse(text: &str) -> Result<Self, RecordingParseError> {
+        let mut recording = InputRecording::new();
+
+        for (index, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |message: &str| RecordingParseError {
+                line: index + 1,
+                message: message.to_string(),
+            };
+
+            let (time, rest) = split_field(line).ok_or_else(|| error("Missing input"))?;
+            let time = time
+                .parse::<f64>()
+                .ok()
+                .filter(|time| time.is_finite() && *time >= 0.0)
+                .ok_or_else(|| error("Invalid time"))?;
+            let input = parse_input(rest).ok_or_else(|| error("Invalid input"))?;
+
+            recording.push(Duration::from_secs_f64(time), input);
+        }
+
+        Ok(recording)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        InputRecording::parse(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        write!(writer, "{}", self)?;
+        writer.flush()
+    }
+}
+
+impl fmt::Display for InputRecording {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for recorded in self.inputs.iter() {
+            write!(f, "{:.6} ", recorded.time.as_secs_f64())?;
+
+            match &recorded.input {
+                WindowEvent::MouseMove(x, y) => write!(f, "MouseMove {} {}", x, y)?,
+                WindowEvent::MouseDown(button) => write!(f, "MouseDown {}", ButtonName(*button))?,
+                WindowEvent::MouseUp(button) => write!(f, "MouseUp {}", ButtonName(*button))?,
+                WindowEvent::MouseScroll(x, y) => write!(f, "MouseScroll {} {}", x, y)?,
+                WindowEvent::KeyDown(code, key) => write!(f, "KeyDown {}{}", code, KeyName(key))?,
+                WindowEvent::KeyUp(code, key) => write!(f, "KeyUp {}{}", code, KeyName(key))?,
+                WindowEvent::CharInput(character) => write!(f, "CharInput {}", *character as u32)?,
+                WindowEvent::ImePreedit { text, cursor_range } => {
+                    match cursor_range {
+                        Some((start, end)) => write!(f, "ImePreedit {} {}", start, end)?,
+                        None => write!(f, "ImePreedit -")?,
+                    }
+
+                    write!(f, "{}", EscapedText(text))?
+                }
+                WindowEvent::ImeCommit(text) => write!(f, "ImeCommit{}", EscapedText(text))?,
+                WindowEvent::WindowResize(width, height) => {
+                    write!(f, "WindowResize {} {}", width, height)?
+                }
+                // Only inputs are recorded
+                _ => {}
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returned when a recording file cannot be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordingParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for RecordingParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} on line {}", self.message, self.line)
+    }
+}
+
+impl std::error::Error for RecordingParseError {}
+
+/// Records the inputs passed to `State::dispatch_input`
+///
+/// Started with `State::start_recording` and finished with `State::stop_recording`.
+pub struct InputRecorder {
+    start: Instant,
+    recording: InputRecording,
+}
+
+impl InputRecorder {
+    pub fn new() -> Self {
+        InputRecorder {
+            start: Instant::now(),
+            recording: InputRecording::new(),
+        }
+    }
+
+    pub fn record(&mut self, input: WindowEvent) {
+        self.recording.push(self.start.elapsed(), input);
+    }
+
+    pub fn recording(&self) -> &InputRecording {
+        &self.recording
+    }
+
+    pub fn finish(self) -> InputRecording {
+        self.recording
+    }
+}
+
+/// Replays a recording by passing its inputs to `State::dispatch_input`
+///
+/// Inputs can be replayed one at a time with `step`, to inspect the state after each input, or
+/// in time with `play_until`. Events sent by the inputs are processed by the event manager as
+/// usual.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut player = InputPlayer::new(InputRecording::load("bug_report.txt")?);
+///
+/// while let Some(input) = player.step(&mut app.state) {
+///     app.update();
+///     println!("{:?} hovered: {}", input, app.state.hovered);
+/// }
+/// ```
+pub struct InputPlayer {
+    recording: InputRecording,
+    position: usize,
+}
+
+impl InputPlayer {
+    pub fn new(recording: InputRecording) -> Self {
+        InputPlayer {
+            recording,
+            position: 0,
+        }
+    }
+
+    /// The index of the next input to be replayed
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.recording.inputs.len()
+    }
+
+    /// The time of the next input to be replayed
+    pub fn next_time(&self) -> Option<Duration> {
+        self.recording
+            .inputs
+            .get(self.position)
+            .map(|input| input.time)
+    }
+
+    /// Replays the next input, returning it or None if the recording has finished
+    pub fn step(&mut self, state: &mut State) -> Option<RecordedInput> {
+        let recorded = self.recording.inputs.get(self.position)?.clone();
+        self.position += 1;
+
+        state.dispatch_input(recorded.input.clone());
+
+        Some(recorded)
+    }
+
+    /// Replays the inputs with a time up to and including `time`, returning the number replayed
+    pub fn play_until(&mut self, state: &mut State, time: Duration) -> usize {
+        let mut count = 0;
+        while self.next_time().map_or(false, |next| next <= time) {
+            self.step(state);
+            count += 1;
+        }
+
+        count
+    }
+
+    /// Moves back to the start of the recording
+    pub fn rewind(&mut self) {
+        self.position = 0;
+    }
+}
+
+struct ButtonName(MouseButton);
+
+impl fmt::Display for ButtonName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            MouseButton::Left => write!(f, "Left"),
+            MouseButton::Right => write!(f, "Right"),
+            MouseButton::Middle => write!(f, "Middle"),
+            MouseButton::Other(id) => write!(f, "Other{}", id),
+        }
+    }
+}
+
+// Writes the key after a space, with line breaks escaped so that it stays on one line
+struct KeyName<'a>(&'a Option<Key>);
+
+impl<'a> fmt::Display for KeyName<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(key) = self.0 {
+            write!(f, "{}", EscapedText(&key.to_string()))?;
+        }
+
+        Ok(())
+    }
+}
+
+// Writes text after a space, with line breaks escaped so that it stays on one line
+struct EscapedText<'a>(&'a str);
+
+impl<'a> fmt::Display for EscapedText<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = self
+            .0
+            .replace('\\', "\\\\")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r");
+        write!(f, " {}", text)
+    }
+}
+
+// Splits the first space separated field from the rest of the line
+fn split_field(text: &str) -> Option<(&str, &str)> {
+    let mut fields = text.splitn(2, ' ');
+    let first = fields.next()?;
+    Some((first, fields.next().unwrap_or("")))
+}
+
+fn parse_input(text: &str) -> Option<WindowEvent> {
+    let (name, args) = split_field(text)?;
+
+    let input = match name {
+        "MouseMove" => {
+            let (x, y) = parse_pair(args)?;
+            WindowEvent::MouseMove(x, y)
+        }
+        "MouseDown" => WindowEvent::MouseDown(parse_button(args)?),
+        "MouseUp" => WindowEvent::MouseUp(parse_button(args)?),
+        "MouseScroll" => {
+            let (x, y) = parse_pair(args)?;
+            WindowEvent::MouseScroll(x, y)
+        }
+        "KeyDown" => {
+            let (code, key) = parse_key(args)?;
+            WindowEvent::KeyDown(code, key)
+        }
+        "KeyUp" => {
+            let (code, key) = parse_key(args)?;
+            WindowEvent::KeyUp(code, key)
+        }
+        "CharInput" => WindowEvent::CharInput(std::char::from_u32(args.trim().parse().ok()?)?),
+        "ImePreedit" => {
+            let (start, rest) = split_field(args)?;
+            let (cursor_range, text) = if start == "-" {
+                (None, rest)
+            } else {
+                let (end, text) = split_field(rest)?;
+                (Some((start.parse().ok()?, end.parse().ok()?)), text)
+            };
+
+            WindowEvent::ImePreedit {
+                text: unescape(text)?,
+                cursor_range,
+            }
+        }
+        "ImeCommit" => WindowEvent::ImeCommit(unescape(args)?),
+        "WindowResize" => {
+            let (width, height) = parse_pair(args)?;
+            WindowEvent::WindowResize(width, height)
+        }
+        _ => return None,
+    };
+
+    Some(input)
+}
+
+fn parse_pair(text: &str) -> Option<(f32, f32)> {
+    let mut values = text.split_whitespace().map(|value| value.parse::<f32>());
+    let pair = (values.next()?.ok()?, values.next()?.ok()?);
+    if values.next().is_some() {
+        return None;
+    }
+
+    Some(pair)
+}
+
+fn parse_button(text: &str) -> Option<MouseButton> {
+    match text.trim() {
+        "Left" => Some(MouseButton::Left),
+        "Right" => Some(MouseButton::Right),
+        "Middle" => Some(MouseButton::Middle),
+        other => Some(MouseButton::Other(
+            other.strip_prefix("Other")?.parse().ok()?,
+        )),
+    }
+}
+
+// The key is the rest of the line after the code, since a character key can be a space
+fn parse_key(text: &str) -> Option<(Code, Option<Key>)> {
+    let (code, key) = match text.find(' ') {
+        Some(index) => (&text[..index], Some(&text[index + 1..])),
+        None => (text, None),
+    };
+
+    let code = parse_code(code)?;
+    let key = match key {
+        Some(key) => {
+            let unescaped = unescape(key)?;
+            Some(unescaped.parse::<Key>().unwrap_or(Key::Character(unescaped)))
+        }
+        None => None,
+    };
+
+    Some((code, key))
+}
+
+// The inverse of EscapedText, without the leading space
+fn unescape(text: &str) -> Option<String> {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+    while let Some(character) = chars.next() {
+        if character == '\\' {
+            match chars.next()? {
+                'n' => unescaped.push('\n'),
+                'r' => unescaped.push('\r'),
+                other => unescaped.push(other),
+            }
+        } else {
+            unescaped.push(character);
+        }
+    }
+
+    Some(unescaped)
+}
+
+// The inverse of the Display implementation of Code
+pub(crate) fn parse_code(name: &str) -> Option<Code> {
+    use Code::*;
+    let code = match name {
+        "Backquote" => Backquote,
+        "Backslash" => Backslash,
+        "BracketLeft" => BracketLeft,
+        "BracketRight" => BracketRight,
+        "Comma" => Comma,
+        "Digit0" => Digit0,
+        "Digit1" => Digit1,
+        "Digit2" => Digit2,
+        "Digit3" => Digit3,
+        "Digit4" => Digit4,
+        "Digit5" => Digit5,
+        "Digit6" => Digit6,
+        "Digit7" => Digit7,
+        "Digit8" => Digit8,
+        "Digit9" => Digit9,
+        "Equal" => Equal,
+        "IntlBackslash" => IntlBackslash,
+        "IntlRo" => IntlRo,
+        "IntlYen" => IntlYen,
+        "KeyA" => KeyA,
+        "KeyB" => KeyB,
+        "KeyC" => KeyC,
+        "KeyD" => KeyD,
+        "KeyE" => KeyE,
+        "KeyF" => KeyF,
+        "KeyG" => KeyG,
+        "KeyH" => KeyH,
+        "KeyI" => KeyI,
+        "KeyJ" => KeyJ,
+        "KeyK" => KeyK,
+        "KeyL" => KeyL,
+        "KeyM" => KeyM,
+        "KeyN" => KeyN,
+        "KeyO" => KeyO,
+        "KeyP" => KeyP,
+        "KeyQ" => KeyQ,
+        "KeyR" => KeyR,
+        "KeyS" => KeyS,
+        "KeyT" => KeyT,
+        "KeyU" => KeyU,
+        "KeyV" => KeyV,
+        "KeyW" => KeyW,
+        "KeyX" => KeyX,
+        "KeyY" => KeyY,
+        "KeyZ" => KeyZ,
+        "Minus" => Minus,
+        "Period" => Period,
+        "Quote" => Quote,
+        "Semicolon" => Semicolon,
+        "Slash" => Slash,
+        "AltLeft" => AltLeft,
+        "AltRight" => AltRight,
+        "Backspace" => Backspace,
+        "CapsLock" => CapsLock,
+        "ContextMenu" => ContextMenu,
+        "ControlLeft" => ControlLeft,
+        "ControlRight" => ControlRight,
+        "Enter" => Enter,
+        "MetaLeft" => MetaLeft,
+        "MetaRight" => MetaRight,
+        "ShiftLeft" => ShiftLeft,
+        "ShiftRight" => ShiftRight,
+        "Space" => Space,
+        "Tab" => Tab,
+        "Convert" => Convert,
+        "KanaMode" => KanaMode,
+        "Lang1" => Lang1,
+        "Lang2" => Lang2,
+        "Lang3" => Lang3,
+        "Lang4" => Lang4,
+        "Lang5" => Lang5,
+        "NonConvert" => NonConvert,
+        "Delete" => Delete,
+        "End" => End,
+        "Help" => Help,
+        "Home" => Home,
+        "Insert" => Insert,
+        "PageDown" => PageDown,
+        "PageUp" => PageUp,
+        "ArrowDown" => ArrowDown,
+        "ArrowLeft" => ArrowLeft,
+        "ArrowRight" => ArrowRight,
+        "ArrowUp" => ArrowUp,
+        "NumLock" => NumLock,
+        "Numpad0" => Numpad0,
+        "Numpad1" => Numpad1,
+        "Numpad2" => Numpad2,
+        "Numpad3" => Numpad3,
+        "Numpad4" => Numpad4,
+        "Numpad5" => Numpad5,
+        "Numpad6" => Numpad6,
+        "Numpad7" => Numpad7,
+        "Numpad8" => Numpad8,
+        "Numpad9" => Numpad9,
+        "NumpadAdd" => NumpadAdd,
+        "NumpadBackspace" => NumpadBackspace,
+        "NumpadClear" => NumpadClear,
+        "NumpadClearEntry" => NumpadClearEntry,
+        "NumpadComma" => NumpadComma,
+        "NumpadDecimal" => NumpadDecimal,
+        "NumpadDivide" => NumpadDivide,
+        "NumpadEnter" => NumpadEnter,
+        "NumpadEqual" => NumpadEqual,
+        "NumpadHash" => NumpadHash,
+        "NumpadMemoryAdd" => NumpadMemoryAdd,
+        "NumpadMemoryClear" => NumpadMemoryClear,
+        "NumpadMemoryRecall" => NumpadMemoryRecall,
+        "NumpadMemoryStore" => NumpadMemoryStore,
+        "NumpadMemorySubtract" => NumpadMemorySubtract,
+        "NumpadMultiply" => NumpadMultiply,
+        "NumpadParenLeft" => NumpadParenLeft,
+        "NumpadParenRight" => NumpadParenRight,
+        "NumpadStar" => NumpadStar,
+        "NumpadSubtract" => NumpadSubtract,
+        "Escape" => Escape,
+        "F1" => F1,
+        "F2" => F2,
+        "F3" => F3,
+        "F4" => F4,
+        "F5" => F5,
+        "F6" => F6,
+        "F7" => F7,
+        "F8" => F8,
+        "F9" => F9,
+        "F10" => F10,
+        "F11" => F11,
+        "F12" => F12,
+        "Fn" => Fn,
+        "FnLock" => FnLock,
+        "PrintScreen" => PrintScreen,
+        "ScrollLock" => ScrollLock,
+        "Pause" => Pause,
+        "BrowserBack" => BrowserBack,
+        "BrowserFavorites" => BrowserFavorites,
+        "BrowserForward" => BrowserForward,
+        "BrowserHome" => BrowserHome,
+        "BrowserRefresh" => BrowserRefresh,
+        "BrowserSearch" => BrowserSearch,
+        "BrowserStop" => BrowserStop,
+        "Eject" => Eject,
+        "LaunchApp1" => LaunchApp1,
+        "LaunchApp2" => LaunchApp2,
+        "LaunchMail" => LaunchMail,
+        "MediaPlayPause" => MediaPlayPause,
+        "MediaSelect" => MediaSelect,
+        "MediaStop" => MediaStop,
+        "MediaTrackNext" => MediaTrackNext,
+        "MediaTrackPrevious" => MediaTrackPrevious,
+        "Power" => Power,
+        "Sleep" => Sleep,
+        "AudioVolumeDown" => AudioVolumeDown,
+        "AudioVolumeMute" => AudioVolumeMute,
+        "AudioVolumeUp" => AudioVolumeUp,
+        "WakeUp" => WakeUp,
+        "Hyper" => Hyper,
+        "Super" => Super,
+        "Turbo" => Turbo,
+        "Abort" => Abort,
+        "Resume" => Resume,
+        "Suspend" => Suspend,
+        "Again" => Again,
+        "Copy" => Copy,
+        "Cut" => Cut,
+        "Find" => Find,
+        "Open" => Open,
+        "Paste" => Paste,
+        "Props" => Props,
+        "Select" => Select,
+        "Undo" => Undo,
+        "Hiragana" => Hiragana,
+        "Katakana" => Katakana,
+        "Unidentified" => Unidentified,
+        _ => return None,
+    };
+
+    Some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Entity;
+
+    // One of each recorded input, with the values which are hard to write and read back
+    fn every_input() -> Vec<WindowEvent> {
+        vec![
+            WindowEvent::MouseMove(120.0, 48.5),
+            WindowEvent::MouseMove(-3.25, 0.0),
+            WindowEvent::MouseDown(MouseButton::Left),
+            WindowEvent::MouseDown(MouseButton::Right),
+            WindowEvent::MouseDown(MouseButton::Middle),
+            WindowEvent::MouseDown(MouseButton::Other(7)),
+            WindowEvent::MouseUp(MouseButton::Left),
+            WindowEvent::MouseUp(MouseButton::Other(7)),
+            WindowEvent::MouseScroll(0.0, -1.5),
+            WindowEvent::KeyDown(Code::KeyA, Some(Key::Character("a".to_string()))),
+            WindowEvent::KeyDown(Code::Space, Some(Key::Character(" ".to_string()))),
+            WindowEvent::KeyDown(Code::Backslash, Some(Key::Character("\\".to_string()))),
+            WindowEvent::KeyDown(Code::Enter, Some(Key::Enter)),
+            WindowEvent::KeyDown(Code::NumpadEnter, Some(Key::Enter)),
+            WindowEvent::KeyDown(Code::F12, None),
+            WindowEvent::KeyUp(Code::ShiftLeft, Some(Key::Shift)),
+            WindowEvent::KeyUp(Code::KeyA, None),
+            WindowEvent::CharInput('a'),
+            WindowEvent::CharInput(' '),
+            WindowEvent::CharInput('\n'),
+            WindowEvent::CharInput('仮'),
+            WindowEvent::ImePreedit {
+                text: "かな".to_string(),
+                cursor_range: Some((6, 6)),
+            },
+            WindowEvent::ImePreedit {
+                text: "a b\\c\nd".to_string(),
+                cursor_range: None,
+            },
+            WindowEvent::ImePreedit {
+                text: String::new(),
+                cursor_range: None,
+            },
+            WindowEvent::ImeCommit("仮名".to_string()),
+            WindowEvent::ImeCommit(" leading space\r\n".to_string()),
+            WindowEvent::WindowResize(800.0, 600.0),
+        ]
+    }
+
+    fn recording() -> InputRecording {
+        let mut recording = InputRecording::new();
+        for (index, input) in every_input().into_iter().enumerate() {
+            // Quarter seconds are written exactly with six decimal places
+            recording.push(Duration::from_millis(index as u64 * 250), input);
+        }
+
+        recording
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let recording = recording();
+        let parsed = InputRecording::parse(&recording.to_string()).unwrap();
+
+        assert_eq!(parsed, recording);
+    }
+
+    #[test]
+    fn file_round_trip_and_replay() {
+        let recording = recording();
+        let path = std::env::temp_dir().join(format!(
+            "tuix_input_recording_{}.txt",
+            std::process::id()
+        ));
+
+        recording.save(&path).unwrap();
+        let loaded = InputRecording::load(&path);
+        std::fs::remove_file(&path).ok();
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded, recording);
+
+        // Replaying dispatches the same inputs in the same order
+        let mut state = State::new();
+        state.hierarchy.add(Entity::root(), None);
+        state.start_recording();
+
+        let mut player = InputPlayer::new(loaded);
+        assert_eq!(player.play_until(&mut state, Duration::from_millis(500)), 3);
+        while player.step(&mut state).is_some() {}
+        assert!(player.is_finished());
+
+        let replayed = state.stop_recording().unwrap();
+        let inputs = |recording: &InputRecording| {
+            recording
+                .inputs
+                .iter()
+                .map(|recorded| recorded.input.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(inputs(&replayed), inputs(&recording));
+    }
+
+    #[test]
+    fn parse_code_is_inverse_of_display() {
+        let source = include_str!("input_recording.rs");
+        let start = source.find("fn parse_code").unwrap();
+        let end = start + source[start..].find("_ => return None").unwrap();
+        let body = &source[start..end];
+        let names: Vec<&str> = body
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix('"'))
+            .filter_map(|line| line.split('"').next())
+            .collect();
+
+        // Every variant of Code in keyboard-types 0.5
+        assert_eq!(names.len(), 172);
+
+        for name in names {
+            let code = parse_code(name).unwrap();
+            assert_eq!(code.to_string(), name);
+        }
+    }
+
+    #[test]
+    fn parse_errors_report_line() {
+        let error = InputRecording::parse("0.1 MouseMove 1 2\n\n0.2 MouseDown Sideways\n")
+            .unwrap_err();
+
+        assert_eq!(error.line, 3);
+        assert!(InputRecording::parse("-1 MouseMove 1 2").is_err());
+        assert!(InputRecording::parse("0.1 MouseMove 1 2 3").is_err());
+        assert!(InputRecording::parse("0.1 KeyDown NotACode").is_err());
+    }
+}
@@ -0,0 +1,214 @@
+use crate::entity::Entity;
+use crate::events::{Callbacks, Event, EventPhase};
+use crate::state::State;
+
+use fnv::FnvHashMap;
+
+// A listener callback, wrapped to downcast the message to the type it listens for
+type ListenerFn = Box<dyn FnMut(&mut State, Entity, &mut Event) + Send>;
+
+/// The event being dispatched to a listener, whose message is passed to the listener separately
+///
+/// Stopping the propagation of the event from a listener works the same as from an event handler.
+pub struct ListenerContext<'a> {
+    /// The entity that produced the event
+    pub origin: Entity,
+    /// The entity the event was sent to
+    pub target: Entity,
+    /// The phase of the dispatch in which the listener is called
+    pub phase: EventPhase,
+    consumed: &'a mut bool,
+    stopped_immediately: &'a mut bool,
+}
+
+impl<'a> ListenerContext<'a> {
+    // Borrows everything but the message of an event, which is borrowed by the listener
+    pub(crate) fn new(
+        origin: Entity,
+        target: Entity,
+        phase: EventPhase,
+        consumed: &'a mut bool,
+        stopped_immediately: &'a mut bool,
+    ) -> Self {
+        ListenerContext {
+            origin,
+            target,
+            phase,
+            consumed,
+            stopped_immediately,
+        }
+    }
+
+    /// Consume the event
+    ///
+    /// This is the same as `stop_propagation`.
+    pub fn consume(&mut self) {
+        *self.consumed = true;
+    }
+
+    /// Stops the event from being sent to any further entities
+    ///
+    /// The remaining listeners of the current entity are still called.
+    pub fn stop_propagation(&mut self) {
+        *self.consumed = true;
+    }
+
+    /// Stops the event from being sent to any further entities or listeners, including the
+    /// remaining listeners of the current entity
+    pub fn stop_immediate(&mut self) {
+        *self.consumed = true;
+        *self.stopped_immediately = true;
+    }
+
+    /// Returns true if the propagation of the event has been stopped
+    pub fn is_propagation_stopped(&self) -> bool {
+        *self.consumed
+    }
+}
+
+/// A handle to a listener added with `State::on`, which can be used to remove it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ListenerHandle {
+    entity: Entity,
+    id: u64,
+}
+
+impl ListenerHandle {
+    /// The entity the listener is attached to
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+}
+
+// Stores the listeners of every entity
+pub(crate) struct Listeners {
+    next_id: u64,
+    listeners: FnvHashMap<Entity, Callbacks<ListenerFn>>,
+}
+
+impl Listeners {
+    pub fn new() -> Self {
+        Listeners {
+            next_id: 0,
+            listeners: FnvHashMap::default(),
+        }
+    }
+
+    pub fn add(&mut self, entity: Entity, listener: ListenerFn) -> ListenerHandle {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.listeners
+            .entry(entity)
+            .or_insert_with(Callbacks::new)
+            .push(id, listener);
+
+        ListenerHandle { entity, id }
+    }
+
+    pub fn remove(&mut self, handle: ListenerHandle) {
+        if let Some(listeners) = self.listeners.get_mut(&handle.entity) {
+            listeners.remove(handle.id);
+            if listeners.is_empty() {
+                self.listeners.remove(&handle.entity);
+            }
+        }
+    }
+
+    pub fn remove_entity(&mut self, entity: Entity) {
+        self.listeners.remove(&entity);
+    }
+
+    pub fn take(&mut self, entity: Entity) -> Option<Vec<(u64, ListenerFn)>> {
+        self.listeners.get_mut(&entity).map(|listeners| listeners.take())
+    }
+
+    pub fn restore(&mut self, entity: Entity, listeners: Vec<(u64, ListenerFn)>) {
+        // The entity may have been removed by one of its listeners
+        if let Some(entry) = self.listeners.get_mut(&entity) {
+            entry.restore(listeners);
+            if entry.is_empty() {
+                self.listeners.remove(&entity);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::events::{Event, EventManager, EventPhase, Propagation};
+    use crate::state::State;
+    use crate::Entity;
+
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Ping;
+
+    // A window containing a panel containing a button
+    fn tree() -> (State, Entity, Entity) {
+        let mut state = State::new();
+        state.hierarchy.add(Entity::root(), None);
+        let panel = state.add(Entity::root());
+        let button = state.add(panel);
+
+        (state, panel, button)
+    }
+
+    #[test]
+    fn parent_listener_fires_once_for_down_up_event() {
+        let (mut state, panel, button) = tree();
+        let calls = Arc::new(Mutex::new(Vec::new()));
+
+        let log = calls.clone();
+        state.on::<Ping, _>(panel, move |_, _, _, context| {
+            log.lock().unwrap().push(context.phase);
+        });
+
+        state.insert_event(Event::new(Ping).target(button));
+        EventManager::new().flush_events(&mut state);
+
+        assert_eq!(*calls.lock().unwrap(), vec![EventPhase::Bubble]);
+    }
+
+    #[test]
+    fn listener_stops_propagation() {
+        let (mut state, panel, button) = tree();
+        let calls = Arc::new(Mutex::new(Vec::new()));
+
+        let log = calls.clone();
+        state.on::<Ping, _>(button, move |_, entity, _, context| {
+            log.lock().unwrap().push(entity);
+            context.stop_propagation();
+        });
+        let log = calls.clone();
+        state.on::<Ping, _>(button, move |_, entity, _, _| {
+            log.lock().unwrap().push(entity);
+        });
+        let log = calls.clone();
+        state.on::<Ping, _>(panel, move |_, entity, _, _| {
+            log.lock().unwrap().push(entity);
+        });
+
+        state.insert_event(Event::new(Ping).target(button).propagate(Propagation::Up));
+        EventManager::new().flush_events(&mut state);
+
+        // Both listeners of the button are called but the panel is not reached
+        assert_eq!(*calls.lock().unwrap(), vec![button, button]);
+    }
+
+    #[test]
+    fn listener_stops_immediately() {
+        let (mut state, _, button) = tree();
+        let calls = Arc::new(Mutex::new(0));
+
+        state.on::<Ping, _>(button, |_, _, _, context| context.stop_immediate());
+        let log = calls.clone();
+        state.on::<Ping, _>(button, move |_, _, _, _| *log.lock().unwrap() += 1);
+
+        state.insert_event(Event::new(Ping).target(button));
+        EventManager::new().flush_events(&mut state);
+
+        assert_eq!(*calls.lock().unwrap(), 0);
+    }
+}
@@ -6,6 +6,27 @@ pub use event_manager::*;
 pub mod event;
 pub use event::*;
 
+pub mod callbacks;
+pub use callbacks::*;
+
+pub mod event_filter;
+pub use event_filter::*;
+
+pub mod listener;
+pub use listener::*;
+
+pub mod event_tracer;
+pub use event_tracer::*;
+
+pub mod event_proxy;
+pub use event_proxy::*;
+
+pub mod value_channel;
+pub use value_channel::*;
+
+pub mod input_recording;
+pub use input_recording::*;
+
 pub mod event_handler;
 pub use event_handler::*;
 
@@ -0,0 +1,207 @@
+use crate::entity::Entity;
+use crate::events::{Event, Propagation};
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+
+// A value and a flag which is set when the value changes
+struct ValueSlot {
+    value: AtomicU32,
+    changed: AtomicBool,
+}
+
+/// Creates a channel for sending a fixed number of `f32` values from a realtime thread to the UI
+///
+/// Each value is stored in an atomic slot, so sending a value never allocates, locks or waits
+/// and can be done from an audio callback. Only the latest value of each slot is kept, so the
+/// sender should send whatever summary the UI needs, such as the peak level of the last buffer.
+///
+/// The receiver is added to the state with `State::add_value_receiver` and the backends deliver
+/// any changed values to the bound widgets once per frame. Sending a value does not wake a
+/// backend which is waiting for window events, so with the glutin backend the values are
+/// delivered on the next iteration of the event loop.
+///
+/// # Examples
+///
+/// ```ignore
+/// let (sender, mut receiver) = value_channel(2);
+///
+/// receiver.bind(0, left_meter, |level| Event::new(AudioLevelEvent::SetLevel(level)));
+/// receiver.bind(1, gain_knob, |gain| Event::new(SliderEvent::SetValue(gain)));
+/// state.add_value_receiver(receiver);
+///
+/// // In the audio callback
+/// sender.send(0, peak_level);
+/// sender.send(1, gain_parameter);
+/// ```
+pub fn value_channel(len: usize) -> (ValueSender, ValueReceiver) {
+    let slots: Arc<[ValueSlot]> = (0..len)
+        .map(|_| ValueSlot {
+            value: AtomicU32::new(0.0f32.to_bits()),
+            changed: AtomicBool::new(false),
+        })
+        .collect::<Vec<_>>()
+        .into();
+
+    (
+        ValueSender {
+            slots: slots.clone(),
+        },
+        ValueReceiver {
+            slots,
+            bindings: Vec::new(),
+        },
+    )
+}
+
+/// The realtime end of a value channel
+///
+/// A channel has a single sender, as a value and its changed flag are written separately and
+/// values sent to the same slot from two threads could be received in either order.
+pub struct ValueSender {
+    slots: Arc<[ValueSlot]>,
+}
+
+impl ValueSender {
+    /// Sets the value of a slot, replacing any value which has not yet been received
+    ///
+    /// Values sent to a slot outside of the channel are ignored.
+    pub fn send(&self, index: usize, value: f32) {
+        if let Some(slot) = self.slots.get(index) {
+            slot.value.store(value.to_bits(), Ordering::Relaxed);
+            slot.changed.store(true, Ordering::Release);
+        }
+    }
+
+    /// The number of values in the channel
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+}
+
+struct ValueBinding {
+    index: usize,
+    entity: Entity,
+    message: Box<dyn Fn(f32) -> Event + Send>,
+}
+
+/// The UI end of a value channel
+pub struct ValueReceiver {
+    slots: Arc<[ValueSlot]>,
+    bindings: Vec<ValueBinding>,
+}
+
+impl ValueReceiver {
+    /// Binds a slot to a widget, which receives the event returned by `message` when the value changes
+    ///
+    /// A slot can be bound to more than one widget.
+    pub fn bind<F>(&mut self, index: usize, entity: Entity, message: F) -> &mut Self
+    where
+        F: Fn(f32) -> Event,
+        F: 'static + Send,
+    {
+        self.bindings.push(ValueBinding {
+            index,
+            entity,
+            message: Box::new(message),
+        });
+
+        self
+    }
+
+    /// Returns the value of a slot if it has changed since it was last received
+    pub fn receive(&self, index: usize) -> Option<f32> {
+        let slot = self.slots.get(index)?;
+        if slot.changed.swap(false, Ordering::Acquire) {
+            Some(f32::from_bits(slot.value.load(Ordering::Relaxed)))
+        } else {
+            None
+        }
+    }
+
+    // Returns the events for the bound widgets of the slots which have changed
+    pub(crate) fn events(&self) -> Vec<Event> {
+        let mut events = Vec::new();
+        for index in 0..self.slots.len() {
+            if let Some(value) = self.receive(index) {
+                for binding in self.bindings.iter().filter(|binding| binding.index == index) {
+                    events.push(
+                        (binding.message)(value)
+                            .target(binding.entity)
+                            .propagate(Propagation::Direct),
+                    );
+                }
+            }
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Level(f32);
+
+    #[test]
+    fn receive_returns_only_changed_values() {
+        let (sender, receiver) = value_channel(2);
+
+        assert_eq!(receiver.receive(0), None);
+
+        sender.send(0, 0.5);
+        assert_eq!(receiver.receive(0), Some(0.5));
+        assert_eq!(receiver.receive(0), None);
+        assert_eq!(receiver.receive(1), None);
+
+        // Sending the same value again is still a change
+        sender.send(0, 0.5);
+        assert_eq!(receiver.receive(0), Some(0.5));
+
+        // Slots outside of the channel are ignored
+        sender.send(2, 1.0);
+        assert_eq!(receiver.receive(2), None);
+    }
+
+    #[test]
+    fn receive_keeps_latest_value() {
+        let (sender, receiver) = value_channel(1);
+
+        sender.send(0, 0.1);
+        sender.send(0, 0.2);
+        sender.send(0, 0.3);
+
+        assert_eq!(receiver.receive(0), Some(0.3));
+        assert_eq!(receiver.receive(0), None);
+    }
+
+    #[test]
+    fn events_fan_out_to_bound_widgets() {
+        let (sender, mut receiver) = value_channel(2);
+        let (left, right, gain) = (Entity::new(1, 0), Entity::new(2, 0), Entity::new(3, 0));
+
+        receiver
+            .bind(0, left, |level| Event::new(Level(level)))
+            .bind(0, right, |level| Event::new(Level(level * 2.0)))
+            .bind(1, gain, |level| Event::new(Level(level)));
+
+        assert!(receiver.events().is_empty());
+
+        sender.send(0, 0.25);
+        let events = receiver.events();
+        let received = events
+            .iter()
+            .map(|event| {
+                assert_eq!(event.propagation, Propagation::Direct);
+                let level = event.message.as_any().downcast_ref::<Level>().unwrap();
+                (event.target, level.clone())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(received, vec![(left, Level(0.25)), (right, Level(0.5))]);
+
+        // The values were received, so nothing is sent again until they change
+        assert!(receiver.events().is_empty());
+    }
+}
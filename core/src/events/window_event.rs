@@ -2,12 +2,71 @@ use crate::state::mouse::MouseButton;
 
 use keyboard_types::{Code, Key};
 
-// Desccibes the cursor icon to be used
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// The icon of the mouse cursor
+///
+/// Set with the `cursor` style property, for example `cursor: pointer`, or directly with
+/// `State::set_cursor`. The names in stylesheets follow CSS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CursorIcon {
+    // The default arrow, `default` or `auto` in stylesheets
     Arrow,
+    // A pointing hand used for links and buttons, `pointer` in stylesheets
+    Hand,
+    Text,
+    Crosshair,
+    Move,
+    Grab,
+    Grabbing,
+    NotAllowed,
+    Wait,
+    Help,
     NResize,
     EResize,
+    SResize,
+    WResize,
+    NeResize,
+    NwResize,
+    SeResize,
+    SwResize,
+    // Bidirectional resize cursors
+    EwResize,
+    NsResize,
+    NeswResize,
+    NwseResize,
+    ColResize,
+    RowResize,
+}
+
+impl Default for CursorIcon {
+    fn default() -> Self {
+        CursorIcon::Arrow
+    }
+}
+
+/// A change to the window, carried out by the backend
+///
+/// Send as an event to the root entity, or queue directly with `State::window_command`. The
+/// backends apply the queued commands after processing events. Sizes and positions are in
+/// physical pixels, and a size limit of `None` removes the limit.
+///
+/// The baseview backend ignores window commands, as its window is owned by the plugin host.
+///
+/// ```ignore
+/// state.insert_event(
+///     Event::new(WindowCommand::SetTitle("Untitled - Edited".to_string())).target(Entity::root()),
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum WindowCommand {
+    SetTitle(String),
+    SetInnerSize(u32, u32),
+    SetMinInnerSize(Option<(u32, u32)>),
+    SetMaxInnerSize(Option<(u32, u32)>),
+    SetPosition(i32, i32),
+    SetFullscreen(bool),
+    // Closes the window without sending `WindowEvent::WindowCloseRequested`, such as after the
+    // user has confirmed discarding unsaved changes
+    Close,
 }
 
 // Used by the GeometryChanged event to signal that either posx, posy, width, or height of the entity have changed
@@ -23,6 +82,9 @@ pub struct GeometryChanged {
 // Or events that can be used to set properties of the window
 #[derive(Debug, Clone, PartialEq)]
 pub enum WindowEvent {
+    // Emitted to every entity when the window is asked to close, see `State::request_window_close`.
+    // Consuming the event keeps the window open.
+    WindowCloseRequested,
     // Emitted when a window is closed
     WindowClose,
     // Emitted when a window is opened
@@ -41,13 +103,27 @@ pub enum WindowEvent {
     MouseOver,
     // Emitted when the mouse cursor leaves the bounding box of an entity
     MouseOut,
+    // Emitted when an entity receives keyboard focus
+    FocusIn,
+    // Emitted when an entity loses keyboard focus
+    FocusOut,
     // Emitted when a character is typed
     CharInput(char),
+    // Emitted while an input method is composing text, with the composition text and the byte
+    // range of the cursor within it. An empty text ends the composition without committing it.
+    // The windowed backends do not emit this or ImeCommit yet, as winit 0.24 does not report
+    // input method events. Committed text still arrives as CharInput.
+    ImePreedit {
+        text: String,
+        cursor_range: Option<(usize, usize)>,
+    },
+    // Emitted when an input method commits the composed text
+    ImeCommit(String),
     // Emitted when a keyboard key is pressed
     KeyDown(Code, Option<Key>),
     // Emitted when a keyboard key is released
     KeyUp(Code, Option<Key>),
-    // Sets the mouse cursor
+    // Sets the mouse cursor until the hovered entity changes, see `State::set_cursor`
     SetCursor(CursorIcon),
     // Emitted when mouse events have been captured
     MouseCaptureEvent,
@@ -61,4 +137,6 @@ pub enum WindowEvent {
     Restyle,
     // Requests a relayout
     Relayout,
+    // Sent directly to a widget which has been removed, before its event handler is dropped
+    WidgetRemoved,
 }
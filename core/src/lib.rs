@@ -10,4 +10,7 @@ pub use crate::widgets::*;
 pub mod systems;
 pub use crate::systems::*;
 
+pub mod renderer;
+pub use crate::renderer::*;
+
 pub use keyboard_types::{Code, Key};
@@ -0,0 +1,141 @@
+use crate::style::Color;
+
+use super::{Paint, Path, Transform2D};
+
+// Identifies a font which has been added to a canvas
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FontId(pub usize);
+
+// Identifies an image which has been created by a canvas
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ImageId(pub usize);
+
+// The position of a single glyph of a run of text
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct GlyphPosition {
+    pub byte_index: usize,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+// The measured bounds and glyph positions of a run of text
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TextMetrics {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub glyphs: Vec<GlyphPosition>,
+}
+
+impl TextMetrics {
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+}
+
+// The vertical metrics of a font at a particular size
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct FontMetrics {
+    pub ascender: f32,
+    pub descender: f32,
+    pub height: f32,
+}
+
+impl FontMetrics {
+    pub fn ascender(&self) -> f32 {
+        self.ascender
+    }
+
+    pub fn descender(&self) -> f32 {
+        self.descender
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+}
+
+// A scissor rectangle along with the transform that was active when it was set
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scissor {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub transform: Transform2D,
+}
+
+// The transform and scissor which are saved and restored by a canvas
+//
+// Used by canvas implementations which track their own state rather than relying on a library.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct CanvasState {
+    pub transform: Transform2D,
+    pub scissor: Option<Scissor>,
+}
+
+/// The drawing interface used by widgets
+///
+/// Every widget draws through this trait in `EventHandler::on_draw`, which allows the renderer to be
+/// swapped out. `FemtovgCanvas` is the implementation used by the windowed backends.
+pub trait Canvas {
+    // Sets the size of the drawing surface in physical pixels
+    fn set_size(&mut self, width: u32, height: u32, dpi_factor: f32);
+
+    // Fills a rectangle with a color ignoring transforms and scissor
+    fn clear_rect(&mut self, x: u32, y: u32, width: u32, height: u32, color: Color);
+
+    // Submits any pending draw operations
+    fn flush(&mut self);
+
+    // Pushes the current transform and scissor onto the state stack
+    fn save(&mut self);
+
+    // Pops the transform and scissor from the state stack
+    fn restore(&mut self);
+
+    // Resets the transform and scissor of the current state
+    fn reset(&mut self);
+
+    fn translate(&mut self, x: f32, y: f32);
+
+    fn rotate(&mut self, angle: f32);
+
+    fn scale(&mut self, x: f32, y: f32);
+
+    // Returns the current transform
+    fn transform(&self) -> Transform2D;
+
+    // Sets the scissor rectangle (in the current transform space) outside of which nothing is drawn
+    fn scissor(&mut self, x: f32, y: f32, width: f32, height: f32);
+
+    fn reset_scissor(&mut self);
+
+    fn fill_path(&mut self, path: &Path, paint: &Paint);
+
+    fn stroke_path(&mut self, path: &Path, paint: &Paint);
+
+    // Draws text at the specified position and returns its metrics
+    fn fill_text(&mut self, x: f32, y: f32, text: &str, paint: &Paint) -> Option<TextMetrics>;
+
+    // Measures text without drawing it
+    fn measure_text(&mut self, x: f32, y: f32, text: &str, paint: &Paint) -> Option<TextMetrics>;
+
+    // Returns the metrics of the font and font size of the paint
+    fn measure_font(&mut self, paint: &Paint) -> Option<FontMetrics>;
+
+    // Adds a font from memory which can then be used by setting the font of a paint
+    fn add_font_mem(&mut self, data: &[u8]) -> Option<FontId>;
+
+    // Creates an image from tightly packed RGBA8 pixel data
+    fn create_image(&mut self, width: usize, height: usize, data: &[u8]) -> Option<ImageId>;
+
+    fn delete_image(&mut self, id: ImageId);
+}
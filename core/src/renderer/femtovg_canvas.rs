@@ -0,0 +1,324 @@
+use femtovg::{ImageFlags, Renderer};
+
+use fnv::FnvHashMap;
+
+use crate::style::Color;
+
+use super::{
+    Canvas, FontId, FontMetrics, GlyphPosition, ImageId, LineCap, LineJoin, Paint, PaintKind,
+    Path, PathCommand, Solidity, TextAlign, TextBaseline, TextMetrics, Transform2D,
+};
+
+/// Implementation of `Canvas` which draws using femtovg
+///
+/// The windowed backends create one of these with the femtovg OpenGl renderer.
+pub struct FemtovgCanvas<T: Renderer> {
+    pub canvas: femtovg::Canvas<T>,
+    fonts: Vec<femtovg::FontId>,
+    images: FnvHashMap<ImageId, femtovg::ImageId>,
+    next_image: usize,
+}
+
+impl<T: Renderer> FemtovgCanvas<T> {
+    pub fn new(canvas: femtovg::Canvas<T>) -> Self {
+        FemtovgCanvas {
+            canvas,
+            fonts: Vec::new(),
+            images: FnvHashMap::default(),
+            next_image: 0,
+        }
+    }
+
+    fn convert_path(path: &Path) -> femtovg::Path {
+        let mut femtovg_path = femtovg::Path::new();
+
+        for command in path.commands.iter() {
+            match *command {
+                PathCommand::MoveTo(x, y) => femtovg_path.move_to(x, y),
+                PathCommand::LineTo(x, y) => femtovg_path.line_to(x, y),
+                PathCommand::BezierTo(c1x, c1y, c2x, c2y, x, y) => {
+                    femtovg_path.bezier_to(c1x, c1y, c2x, c2y, x, y)
+                }
+                PathCommand::QuadTo(cx, cy, x, y) => femtovg_path.quad_to(cx, cy, x, y),
+                PathCommand::Arc(cx, cy, r, a0, a1, dir) => {
+                    femtovg_path.arc(cx, cy, r, a0, a1, convert_solidity(dir))
+                }
+                PathCommand::Rect(x, y, w, h) => femtovg_path.rect(x, y, w, h),
+                PathCommand::RoundedRect(x, y, w, h, tl, tr, br, bl) => {
+                    femtovg_path.rounded_rect_varying(x, y, w, h, tl, tr, br, bl)
+                }
+                PathCommand::Ellipse(cx, cy, rx, ry) => femtovg_path.ellipse(cx, cy, rx, ry),
+                PathCommand::Close => femtovg_path.close(),
+                PathCommand::Solidity(solidity) => {
+                    femtovg_path.solidity(convert_solidity(solidity))
+                }
+            }
+        }
+
+        femtovg_path
+    }
+
+    fn convert_paint(&self, paint: &Paint) -> femtovg::Paint {
+        let mut femtovg_paint = match &paint.kind {
+            PaintKind::Color(color) => femtovg::Paint::color((*color).into()),
+
+            PaintKind::LinearGradient {
+                start_x,
+                start_y,
+                end_x,
+                end_y,
+                stops,
+            } => femtovg::Paint::linear_gradient_stops(
+                *start_x,
+                *start_y,
+                *end_x,
+                *end_y,
+                stops
+                    .iter()
+                    .map(|(pos, color)| (*pos, (*color).into()))
+                    .collect::<Vec<(f32, femtovg::Color)>>()
+                    .as_slice(),
+            ),
+
+            PaintKind::BoxGradient {
+                x,
+                y,
+                width,
+                height,
+                radius,
+                feather,
+                inner_color,
+                outer_color,
+            } => femtovg::Paint::box_gradient(
+                *x,
+                *y,
+                *width,
+                *height,
+                *radius,
+                *feather,
+                (*inner_color).into(),
+                (*outer_color).into(),
+            ),
+
+            PaintKind::RadialGradient {
+                cx,
+                cy,
+                in_radius,
+                out_radius,
+                inner_color,
+                outer_color,
+            } => femtovg::Paint::radial_gradient(
+                *cx,
+                *cy,
+                *in_radius,
+                *out_radius,
+                (*inner_color).into(),
+                (*outer_color).into(),
+            ),
+
+            PaintKind::Image {
+                id,
+                cx,
+                cy,
+                width,
+                height,
+                angle,
+                alpha,
+            } => {
+                if let Some(image_id) = self.images.get(id) {
+                    femtovg::Paint::image(*image_id, *cx, *cy, *width, *height, *angle, *alpha)
+                } else {
+                    femtovg::Paint::color(Color::rgba(0, 0, 0, 0).into())
+                }
+            }
+        };
+
+        femtovg_paint.set_line_width(paint.line_width);
+
+        femtovg_paint.set_line_cap(match paint.line_cap {
+            LineCap::Butt => femtovg::LineCap::Butt,
+            LineCap::Round => femtovg::LineCap::Round,
+            LineCap::Square => femtovg::LineCap::Square,
+        });
+
+        femtovg_paint.set_line_join(match paint.line_join {
+            LineJoin::Miter => femtovg::LineJoin::Miter,
+            LineJoin::Round => femtovg::LineJoin::Round,
+            LineJoin::Bevel => femtovg::LineJoin::Bevel,
+        });
+
+        femtovg_paint.set_anti_alias(paint.anti_alias);
+
+        if let Some(font) = paint.font {
+            if let Some(font_id) = self.fonts.get(font.0) {
+                femtovg_paint.set_font(&[*font_id]);
+            }
+        }
+
+        femtovg_paint.set_font_size(paint.font_size);
+
+        femtovg_paint.set_text_align(match paint.text_align {
+            TextAlign::Left => femtovg::Align::Left,
+            TextAlign::Center => femtovg::Align::Center,
+            TextAlign::Right => femtovg::Align::Right,
+        });
+
+        femtovg_paint.set_text_baseline(match paint.text_baseline {
+            TextBaseline::Top => femtovg::Baseline::Top,
+            TextBaseline::Middle => femtovg::Baseline::Middle,
+            TextBaseline::Alphabetic => femtovg::Baseline::Alphabetic,
+            TextBaseline::Bottom => femtovg::Baseline::Bottom,
+        });
+
+        femtovg_paint
+    }
+}
+
+fn convert_solidity(solidity: Solidity) -> femtovg::Solidity {
+    match solidity {
+        Solidity::Solid => femtovg::Solidity::Solid,
+        Solidity::Hole => femtovg::Solidity::Hole,
+    }
+}
+
+fn convert_text_metrics(text_metrics: femtovg::TextMetrics) -> TextMetrics {
+    TextMetrics {
+        x: text_metrics.x,
+        y: text_metrics.y,
+        width: text_metrics.width(),
+        height: text_metrics.height(),
+        glyphs: text_metrics
+            .glyphs
+            .iter()
+            .map(|glyph| GlyphPosition {
+                byte_index: glyph.byte_index,
+                x: glyph.x,
+                y: glyph.y,
+                width: glyph.width,
+                height: glyph.height,
+            })
+            .collect(),
+    }
+}
+
+impl<T: Renderer> Canvas for FemtovgCanvas<T> {
+    fn set_size(&mut self, width: u32, height: u32, dpi_factor: f32) {
+        self.canvas.set_size(width, height, dpi_factor);
+    }
+
+    fn clear_rect(&mut self, x: u32, y: u32, width: u32, height: u32, color: Color) {
+        self.canvas.clear_rect(x, y, width, height, color.into());
+    }
+
+    fn flush(&mut self) {
+        self.canvas.flush();
+    }
+
+    fn save(&mut self) {
+        self.canvas.save();
+    }
+
+    fn restore(&mut self) {
+        self.canvas.restore();
+    }
+
+    fn reset(&mut self) {
+        self.canvas.reset();
+    }
+
+    fn translate(&mut self, x: f32, y: f32) {
+        self.canvas.translate(x, y);
+    }
+
+    fn rotate(&mut self, angle: f32) {
+        self.canvas.rotate(angle);
+    }
+
+    fn scale(&mut self, x: f32, y: f32) {
+        self.canvas.scale(x, y);
+    }
+
+    fn transform(&self) -> Transform2D {
+        Transform2D(self.canvas.transform().0)
+    }
+
+    fn scissor(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.canvas.scissor(x, y, width, height);
+    }
+
+    fn reset_scissor(&mut self) {
+        self.canvas.reset_scissor();
+    }
+
+    fn fill_path(&mut self, path: &Path, paint: &Paint) {
+        let mut femtovg_path = Self::convert_path(path);
+        let femtovg_paint = self.convert_paint(paint);
+        self.canvas.fill_path(&mut femtovg_path, femtovg_paint);
+    }
+
+    fn stroke_path(&mut self, path: &Path, paint: &Paint) {
+        let mut femtovg_path = Self::convert_path(path);
+        let femtovg_paint = self.convert_paint(paint);
+        self.canvas.stroke_path(&mut femtovg_path, femtovg_paint);
+    }
+
+    fn fill_text(&mut self, x: f32, y: f32, text: &str, paint: &Paint) -> Option<TextMetrics> {
+        let femtovg_paint = self.convert_paint(paint);
+        self.canvas
+            .fill_text(x, y, text, femtovg_paint)
+            .ok()
+            .map(convert_text_metrics)
+    }
+
+    fn measure_text(&mut self, x: f32, y: f32, text: &str, paint: &Paint) -> Option<TextMetrics> {
+        let femtovg_paint = self.convert_paint(paint);
+        self.canvas
+            .measure_text(x, y, text, femtovg_paint)
+            .ok()
+            .map(convert_text_metrics)
+    }
+
+    fn measure_font(&mut self, paint: &Paint) -> Option<FontMetrics> {
+        let femtovg_paint = self.convert_paint(paint);
+        self.canvas
+            .measure_font(femtovg_paint)
+            .ok()
+            .map(|font_metrics| FontMetrics {
+                ascender: font_metrics.ascender(),
+                descender: font_metrics.descender(),
+                height: font_metrics.height(),
+            })
+    }
+
+    fn add_font_mem(&mut self, data: &[u8]) -> Option<FontId> {
+        let font_id = self.canvas.add_font_mem(data).ok()?;
+        self.fonts.push(font_id);
+        Some(FontId(self.fonts.len() - 1))
+    }
+
+    fn create_image(&mut self, width: usize, height: usize, data: &[u8]) -> Option<ImageId> {
+        use rgb::FromSlice;
+
+        if data.len() != width * height * 4 {
+            return None;
+        }
+
+        let image = imgref::Img::new(data.as_rgba(), width, height);
+        let femtovg_image = self
+            .canvas
+            .create_image(image, ImageFlags::empty())
+            .ok()?;
+
+        let id = ImageId(self.next_image);
+        self.next_image += 1;
+        self.images.insert(id, femtovg_image);
+
+        Some(id)
+    }
+
+    fn delete_image(&mut self, id: ImageId) {
+        if let Some(femtovg_image) = self.images.remove(&id) {
+            self.canvas.delete_image(femtovg_image);
+        }
+    }
+}
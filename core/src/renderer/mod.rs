@@ -0,0 +1,25 @@
+pub mod canvas;
+pub use canvas::*;
+
+pub mod path;
+pub use path::*;
+
+pub mod paint;
+pub use paint::*;
+
+pub mod transform;
+pub use transform::*;
+
+pub mod femtovg_canvas;
+pub use femtovg_canvas::*;
+
+pub mod recording_canvas;
+pub use recording_canvas::*;
+
+mod rasterizer;
+
+pub mod pixmap;
+pub use pixmap::*;
+
+pub mod software_canvas;
+pub use software_canvas::*;
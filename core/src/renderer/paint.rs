@@ -0,0 +1,259 @@
+use crate::style::Color;
+
+use super::{FontId, ImageId};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineCap {
+    Butt,
+    Round,
+    Square,
+}
+
+impl Default for LineCap {
+    fn default() -> Self {
+        LineCap::Butt
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+impl Default for LineJoin {
+    fn default() -> Self {
+        LineJoin::Miter
+    }
+}
+
+// Horizontal alignment of text relative to the text position
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextAlign {
+    Left,
+    Center,
+    Right,
+}
+
+impl Default for TextAlign {
+    fn default() -> Self {
+        TextAlign::Left
+    }
+}
+
+// Vertical alignment of text relative to the text position
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextBaseline {
+    Top,
+    Middle,
+    Alphabetic,
+    Bottom,
+}
+
+impl Default for TextBaseline {
+    fn default() -> Self {
+        TextBaseline::Alphabetic
+    }
+}
+
+// Describes how a path or text is filled or stroked
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaintKind {
+    Color(Color),
+    // Start point, end point and a list of (position, color) stops
+    LinearGradient {
+        start_x: f32,
+        start_y: f32,
+        end_x: f32,
+        end_y: f32,
+        stops: Vec<(f32, Color)>,
+    },
+    // A feathered rounded rectangle, used for drawing shadows
+    BoxGradient {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        radius: f32,
+        feather: f32,
+        inner_color: Color,
+        outer_color: Color,
+    },
+    RadialGradient {
+        cx: f32,
+        cy: f32,
+        in_radius: f32,
+        out_radius: f32,
+        inner_color: Color,
+        outer_color: Color,
+    },
+    Image {
+        id: ImageId,
+        cx: f32,
+        cy: f32,
+        width: f32,
+        height: f32,
+        angle: f32,
+        alpha: f32,
+    },
+}
+
+/// A renderer independent description of how to fill or stroke a path or text
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paint {
+    pub kind: PaintKind,
+    pub line_width: f32,
+    pub line_cap: LineCap,
+    pub line_join: LineJoin,
+    pub anti_alias: bool,
+    pub font: Option<FontId>,
+    pub font_size: f32,
+    pub text_align: TextAlign,
+    pub text_baseline: TextBaseline,
+}
+
+impl Paint {
+    fn new(kind: PaintKind) -> Self {
+        Paint {
+            kind,
+            line_width: 1.0,
+            line_cap: LineCap::default(),
+            line_join: LineJoin::default(),
+            anti_alias: true,
+            font: None,
+            font_size: 16.0,
+            text_align: TextAlign::default(),
+            text_baseline: TextBaseline::default(),
+        }
+    }
+
+    pub fn color(color: Color) -> Self {
+        Paint::new(PaintKind::Color(color))
+    }
+
+    pub fn linear_gradient(
+        start_x: f32,
+        start_y: f32,
+        end_x: f32,
+        end_y: f32,
+        start_color: Color,
+        end_color: Color,
+    ) -> Self {
+        Paint::linear_gradient_stops(
+            start_x,
+            start_y,
+            end_x,
+            end_y,
+            &[(0.0, start_color), (1.0, end_color)],
+        )
+    }
+
+    pub fn linear_gradient_stops(
+        start_x: f32,
+        start_y: f32,
+        end_x: f32,
+        end_y: f32,
+        stops: &[(f32, Color)],
+    ) -> Self {
+        Paint::new(PaintKind::LinearGradient {
+            start_x,
+            start_y,
+            end_x,
+            end_y,
+            stops: stops.to_vec(),
+        })
+    }
+
+    pub fn box_gradient(
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        radius: f32,
+        feather: f32,
+        inner_color: Color,
+        outer_color: Color,
+    ) -> Self {
+        Paint::new(PaintKind::BoxGradient {
+            x,
+            y,
+            width,
+            height,
+            radius,
+            feather,
+            inner_color,
+            outer_color,
+        })
+    }
+
+    pub fn radial_gradient(
+        cx: f32,
+        cy: f32,
+        in_radius: f32,
+        out_radius: f32,
+        inner_color: Color,
+        outer_color: Color,
+    ) -> Self {
+        Paint::new(PaintKind::RadialGradient {
+            cx,
+            cy,
+            in_radius,
+            out_radius,
+            inner_color,
+            outer_color,
+        })
+    }
+
+    pub fn image(
+        id: ImageId,
+        cx: f32,
+        cy: f32,
+        width: f32,
+        height: f32,
+        angle: f32,
+        alpha: f32,
+    ) -> Self {
+        Paint::new(PaintKind::Image {
+            id,
+            cx,
+            cy,
+            width,
+            height,
+            angle,
+            alpha,
+        })
+    }
+
+    pub fn set_line_width(&mut self, line_width: f32) {
+        self.line_width = line_width;
+    }
+
+    pub fn set_line_cap(&mut self, line_cap: LineCap) {
+        self.line_cap = line_cap;
+    }
+
+    pub fn set_line_join(&mut self, line_join: LineJoin) {
+        self.line_join = line_join;
+    }
+
+    pub fn set_anti_alias(&mut self, anti_alias: bool) {
+        self.anti_alias = anti_alias;
+    }
+
+    pub fn set_font(&mut self, font: FontId) {
+        self.font = Some(font);
+    }
+
+    pub fn set_font_size(&mut self, font_size: f32) {
+        self.font_size = font_size;
+    }
+
+    pub fn set_text_align(&mut self, text_align: TextAlign) {
+        self.text_align = text_align;
+    }
+
+    pub fn set_text_baseline(&mut self, text_baseline: TextBaseline) {
+        self.text_baseline = text_baseline;
+    }
+}
@@ -0,0 +1,121 @@
+// Determines whether a sub-path is filled or cut out of the shape
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Solidity {
+    Solid,
+    Hole,
+}
+
+impl Default for Solidity {
+    fn default() -> Self {
+        Solidity::Solid
+    }
+}
+
+// A single command used to build up a path
+//
+// Shapes are kept as high level commands (rather than being flattened into lines and curves)
+// so that a renderer can draw them natively and so recorded paths remain readable.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathCommand {
+    MoveTo(f32, f32),
+    LineTo(f32, f32),
+    BezierTo(f32, f32, f32, f32, f32, f32),
+    QuadTo(f32, f32, f32, f32),
+    // Arc with center (cx, cy), radius, start angle, end angle and direction
+    Arc(f32, f32, f32, f32, f32, Solidity),
+    // Rect with posx, posy, width and height
+    Rect(f32, f32, f32, f32),
+    // Rounded rect with posx, posy, width, height and the top-left, top-right, bottom-right, bottom-left radii
+    RoundedRect(f32, f32, f32, f32, f32, f32, f32, f32),
+    // Ellipse with center (cx, cy) and radii
+    Ellipse(f32, f32, f32, f32),
+    Close,
+    Solidity(Solidity),
+}
+
+/// A renderer independent path made up of a list of commands
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Path {
+    pub commands: Vec<PathCommand>,
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Path {
+            commands: Vec::new(),
+        }
+    }
+
+    pub fn move_to(&mut self, x: f32, y: f32) {
+        self.commands.push(PathCommand::MoveTo(x, y));
+    }
+
+    pub fn line_to(&mut self, x: f32, y: f32) {
+        self.commands.push(PathCommand::LineTo(x, y));
+    }
+
+    pub fn bezier_to(&mut self, c1x: f32, c1y: f32, c2x: f32, c2y: f32, x: f32, y: f32) {
+        self.commands
+            .push(PathCommand::BezierTo(c1x, c1y, c2x, c2y, x, y));
+    }
+
+    pub fn quad_to(&mut self, cx: f32, cy: f32, x: f32, y: f32) {
+        self.commands.push(PathCommand::QuadTo(cx, cy, x, y));
+    }
+
+    pub fn close(&mut self) {
+        self.commands.push(PathCommand::Close);
+    }
+
+    /// Sets the solidity of the current sub-path
+    pub fn solidity(&mut self, solidity: Solidity) {
+        self.commands.push(PathCommand::Solidity(solidity));
+    }
+
+    pub fn arc(&mut self, cx: f32, cy: f32, r: f32, a0: f32, a1: f32, dir: Solidity) {
+        self.commands.push(PathCommand::Arc(cx, cy, r, a0, a1, dir));
+    }
+
+    pub fn rect(&mut self, x: f32, y: f32, w: f32, h: f32) {
+        self.commands.push(PathCommand::Rect(x, y, w, h));
+    }
+
+    pub fn rounded_rect(&mut self, x: f32, y: f32, w: f32, h: f32, r: f32) {
+        self.rounded_rect_varying(x, y, w, h, r, r, r, r);
+    }
+
+    pub fn rounded_rect_varying(
+        &mut self,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        top_left: f32,
+        top_right: f32,
+        bottom_right: f32,
+        bottom_left: f32,
+    ) {
+        self.commands.push(PathCommand::RoundedRect(
+            x,
+            y,
+            w,
+            h,
+            top_left,
+            top_right,
+            bottom_right,
+            bottom_left,
+        ));
+    }
+
+    pub fn ellipse(&mut self, cx: f32, cy: f32, rx: f32, ry: f32) {
+        self.commands.push(PathCommand::Ellipse(cx, cy, rx, ry));
+    }
+
+    pub fn circle(&mut self, cx: f32, cy: f32, r: f32) {
+        self.ellipse(cx, cy, r, r);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
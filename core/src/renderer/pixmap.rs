@@ -0,0 +1,120 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::style::Color;
+
+/// An RGBA image with 8 bits per channel and non-premultiplied alpha
+///
+/// Produced by the `SoftwareCanvas` and can be written to disk as a PNG or PPM file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pixmap {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+impl Pixmap {
+    /// Creates a transparent pixmap
+    pub fn new(width: u32, height: u32) -> Self {
+        Pixmap {
+            width,
+            height,
+            data: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    /// Creates a pixmap from RGBA data, returning None if the data is the wrong length
+    pub fn from_rgba(width: u32, height: u32, data: Vec<u8>) -> Option<Self> {
+        if data.len() != width as usize * height as usize * 4 {
+            return None;
+        }
+
+        Some(Pixmap {
+            width,
+            height,
+            data,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The pixel data in rows of RGBA bytes from top to bottom
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let index = (y as usize * self.width as usize + x as usize) * 4;
+        Some(Color::rgba(
+            self.data[index],
+            self.data[index + 1],
+            self.data[index + 2],
+            self.data[index + 3],
+        ))
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: Color) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+
+        let index = (y as usize * self.width as usize + x as usize) * 4;
+        self.data[index] = color.r();
+        self.data[index + 1] = color.g();
+        self.data[index + 2] = color.b();
+        self.data[index + 3] = color.a();
+    }
+
+    /// Encodes the pixmap as a binary PPM (P6) image
+    ///
+    /// The PPM format has no alpha channel so the pixels are composited onto black.
+    pub fn encode_ppm<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+
+        let mut rgb = Vec::with_capacity(self.width as usize * self.height as usize * 3);
+        for pixel in self.data.chunks_exact(4) {
+            let alpha = pixel[3] as u32;
+            rgb.push((pixel[0] as u32 * alpha / 255) as u8);
+            rgb.push((pixel[1] as u32 * alpha / 255) as u8);
+            rgb.push((pixel[2] as u32 * alpha / 255) as u8);
+        }
+
+        writer.write_all(&rgb)
+    }
+
+    /// Encodes the pixmap as an 8-bit RGBA PNG image
+    pub fn encode_png<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::RGBA);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut png_writer = encoder
+            .write_header()
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        png_writer
+            .write_image_data(&self.data)
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))
+    }
+
+    pub fn save_ppm<P: AsRef<std::path::Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.encode_ppm(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn save_png<P: AsRef<std::path::Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.encode_png(&mut writer)?;
+        writer.flush()
+    }
+}
@@ -0,0 +1,505 @@
+// Converts paths into anti-aliased coverage masks for the software canvas
+
+use std::f32::consts::PI;
+
+use super::{LineCap, Path, PathCommand, Solidity, Transform2D};
+
+// A flattened sub-path in device coordinates
+#[derive(Debug, Clone)]
+pub(crate) struct Contour {
+    pub points: Vec<(f32, f32)>,
+    pub closed: bool,
+    pub solidity: Solidity,
+}
+
+impl Contour {
+    fn new() -> Self {
+        Contour {
+            points: Vec::new(),
+            closed: false,
+            solidity: Solidity::Solid,
+        }
+    }
+
+    fn push(&mut self, x: f32, y: f32) {
+        // Skip duplicate points which would produce zero length segments
+        if let Some(&(px, py)) = self.points.last() {
+            if (px - x).abs() < 1e-4 && (py - y).abs() < 1e-4 {
+                return;
+            }
+        }
+
+        self.points.push((x, y));
+    }
+}
+
+// Returns twice the signed area of a polygon
+fn signed_area(points: &[(f32, f32)]) -> f32 {
+    let mut area = 0.0;
+    for i in 0..points.len() {
+        let (x0, y0) = points[i];
+        let (x1, y1) = points[(i + 1) % points.len()];
+        area += x0 * y1 - x1 * y0;
+    }
+
+    area
+}
+
+// The number of line segments used to approximate a curve with the given length in pixels
+fn num_segments(length: f32) -> usize {
+    ((length / 3.0).ceil() as usize).max(2).min(128)
+}
+
+fn distance(x0: f32, y0: f32, x1: f32, y1: f32) -> f32 {
+    ((x1 - x0) * (x1 - x0) + (y1 - y0) * (y1 - y0)).sqrt()
+}
+
+// Appends an elliptical arc, centered on (cx, cy), from angle a0 to angle a1
+fn push_arc(contour: &mut Contour, cx: f32, cy: f32, rx: f32, ry: f32, a0: f32, a1: f32, scale: f32) {
+    let n = num_segments((a1 - a0).abs() * rx.max(ry) * scale);
+    for i in 0..=n {
+        let a = a0 + (a1 - a0) * i as f32 / n as f32;
+        contour.push(cx + a.cos() * rx, cy + a.sin() * ry);
+    }
+}
+
+/// Flattens a path into polygons in device coordinates
+///
+/// Curves are subdivided based on their size after transformation by `transform`.
+pub(crate) fn flatten(path: &Path, transform: &Transform2D) -> Vec<Contour> {
+    let scale = transform.average_scale();
+
+    let mut contours: Vec<Contour> = Vec::new();
+    let mut current = Contour::new();
+    let mut last = (0.0, 0.0);
+
+    fn finish(contours: &mut Vec<Contour>, current: &mut Contour) {
+        if current.points.len() > 1 {
+            contours.push(std::mem::replace(current, Contour::new()));
+        } else {
+            *current = Contour::new();
+        }
+    }
+
+    for command in path.commands.iter() {
+        match *command {
+            PathCommand::MoveTo(x, y) => {
+                finish(&mut contours, &mut current);
+                current.push(x, y);
+                last = (x, y);
+            }
+
+            PathCommand::LineTo(x, y) => {
+                if current.points.is_empty() {
+                    current.push(last.0, last.1);
+                }
+                current.push(x, y);
+                last = (x, y);
+            }
+
+            PathCommand::BezierTo(c1x, c1y, c2x, c2y, x, y) => {
+                if current.points.is_empty() {
+                    current.push(last.0, last.1);
+                }
+                let (x0, y0) = last;
+                let length = distance(x0, y0, c1x, c1y)
+                    + distance(c1x, c1y, c2x, c2y)
+                    + distance(c2x, c2y, x, y);
+                let n = num_segments(length * scale);
+                for i in 1..=n {
+                    let t = i as f32 / n as f32;
+                    let mt = 1.0 - t;
+                    let a = mt * mt * mt;
+                    let b = 3.0 * mt * mt * t;
+                    let c = 3.0 * mt * t * t;
+                    let d = t * t * t;
+                    current.push(
+                        a * x0 + b * c1x + c * c2x + d * x,
+                        a * y0 + b * c1y + c * c2y + d * y,
+                    );
+                }
+                last = (x, y);
+            }
+
+            PathCommand::QuadTo(cx, cy, x, y) => {
+                if current.points.is_empty() {
+                    current.push(last.0, last.1);
+                }
+                let (x0, y0) = last;
+                let length = distance(x0, y0, cx, cy) + distance(cx, cy, x, y);
+                let n = num_segments(length * scale);
+                for i in 1..=n {
+                    let t = i as f32 / n as f32;
+                    let mt = 1.0 - t;
+                    current.push(
+                        mt * mt * x0 + 2.0 * mt * t * cx + t * t * x,
+                        mt * mt * y0 + 2.0 * mt * t * cy + t * t * y,
+                    );
+                }
+                last = (x, y);
+            }
+
+            PathCommand::Arc(cx, cy, r, a0, a1, dir) => {
+                // Same angle conventions as nanovg, where a hole is drawn clockwise
+                let mut da = a1 - a0;
+                match dir {
+                    Solidity::Hole => {
+                        if da.abs() >= PI * 2.0 {
+                            da = PI * 2.0;
+                        } else {
+                            while da < 0.0 {
+                                da += PI * 2.0;
+                            }
+                        }
+                    }
+
+                    Solidity::Solid => {
+                        if da.abs() >= PI * 2.0 {
+                            da = -PI * 2.0;
+                        } else {
+                            while da > 0.0 {
+                                da -= PI * 2.0;
+                            }
+                        }
+                    }
+                }
+
+                push_arc(&mut current, cx, cy, r, r, a0, a0 + da, scale);
+                if let Some(&point) = current.points.last() {
+                    last = point;
+                }
+            }
+
+            PathCommand::Rect(x, y, w, h) => {
+                finish(&mut contours, &mut current);
+                current.push(x, y);
+                current.push(x, y + h);
+                current.push(x + w, y + h);
+                current.push(x + w, y);
+                current.closed = true;
+                finish(&mut contours, &mut current);
+                last = (x, y);
+            }
+
+            PathCommand::RoundedRect(x, y, w, h, tl, tr, br, bl) => {
+                finish(&mut contours, &mut current);
+
+                let half_width = w.abs() * 0.5;
+                let half_height = h.abs() * 0.5;
+                let sign_x = w.signum();
+                let sign_y = h.signum();
+
+                let radii = |r: f32| (r.min(half_width) * sign_x, r.min(half_height) * sign_y);
+                let (rx_tl, ry_tl) = radii(tl);
+                let (rx_tr, ry_tr) = radii(tr);
+                let (rx_br, ry_br) = radii(br);
+                let (rx_bl, ry_bl) = radii(bl);
+
+                // A corner with no radius collapses to a single point
+                let corner = |contour: &mut Contour, cx: f32, cy: f32, rx: f32, ry: f32, a0: f32| {
+                    push_arc(contour, cx, cy, rx.abs(), ry.abs(), a0, a0 + PI * 0.5, scale);
+                };
+
+                // Clockwise on screen starting from the top left corner
+                corner(&mut current, x + rx_tl, y + ry_tl, rx_tl, ry_tl, PI);
+                corner(&mut current, x + w - rx_tr, y + ry_tr, rx_tr, ry_tr, PI * 1.5);
+                corner(&mut current, x + w - rx_br, y + h - ry_br, rx_br, ry_br, 0.0);
+                corner(&mut current, x + rx_bl, y + h - ry_bl, rx_bl, ry_bl, PI * 0.5);
+
+                current.closed = true;
+                finish(&mut contours, &mut current);
+                last = (x, y);
+            }
+
+            PathCommand::Ellipse(cx, cy, rx, ry) => {
+                finish(&mut contours, &mut current);
+                push_arc(&mut current, cx, cy, rx, ry, 0.0, PI * 2.0, scale);
+                current.points.pop();
+                current.closed = true;
+                finish(&mut contours, &mut current);
+                last = (cx + rx, cy);
+            }
+
+            PathCommand::Close => {
+                current.closed = true;
+                if let Some(&point) = current.points.first() {
+                    last = point;
+                }
+                finish(&mut contours, &mut current);
+            }
+
+            PathCommand::Solidity(solidity) => {
+                // Applies to the sub-path in progress or, if there isn't one, the last sub-path
+                if !current.points.is_empty() {
+                    current.solidity = solidity;
+                } else if let Some(contour) = contours.last_mut() {
+                    contour.solidity = solidity;
+                }
+            }
+        }
+    }
+
+    finish(&mut contours, &mut current);
+
+    for contour in contours.iter_mut() {
+        for point in contour.points.iter_mut() {
+            *point = transform.transform_point(point.0, point.1);
+        }
+    }
+
+    contours
+}
+
+// Orients a polygon so that its signed area is positive (or negative if `positive` is false)
+fn orient(points: &mut Vec<(f32, f32)>, positive: bool) {
+    if (signed_area(points) > 0.0) != positive {
+        points.reverse();
+    }
+}
+
+/// Prepares flattened contours for filling with the non-zero rule
+///
+/// Solid contours are wound in one direction and holes in the other, so that holes subtract
+/// from the filled area regardless of the order in which the points were specified.
+pub(crate) fn fill_polygons(mut contours: Vec<Contour>) -> Vec<Vec<(f32, f32)>> {
+    contours
+        .drain(..)
+        .filter(|contour| contour.points.len() > 2)
+        .map(|contour| {
+            let mut points = contour.points;
+            orient(&mut points, contour.solidity == Solidity::Solid);
+            points
+        })
+        .collect()
+}
+
+// A polygon approximating a circle, used for round joins and caps
+fn disc(cx: f32, cy: f32, r: f32) -> Vec<(f32, f32)> {
+    let n = num_segments(PI * 2.0 * r).max(8);
+    (0..n)
+        .map(|i| {
+            let a = PI * 2.0 * i as f32 / n as f32;
+            (cx + a.cos() * r, cy + a.sin() * r)
+        })
+        .collect()
+}
+
+/// Converts flattened contours into polygons covering the outline of a stroke
+///
+/// Each segment becomes a quad and the joins are rounded. All of the polygons are wound in the
+/// same direction so that overlapping parts are not cancelled out by the non-zero rule.
+pub(crate) fn stroke_polygons(
+    contours: &[Contour],
+    width: f32,
+    cap: LineCap,
+) -> Vec<Vec<(f32, f32)>> {
+    let half_width = width * 0.5;
+    let mut polygons = Vec::new();
+
+    for contour in contours.iter() {
+        let points = &contour.points;
+        let num_points = points.len();
+        let num_segments = if contour.closed {
+            num_points
+        } else {
+            num_points - 1
+        };
+
+        for i in 0..num_segments {
+            let (x0, y0) = points[i];
+            let (x1, y1) = points[(i + 1) % num_points];
+
+            let length = distance(x0, y0, x1, y1);
+            if length < 1e-6 {
+                continue;
+            }
+
+            let dx = (x1 - x0) / length;
+            let dy = (y1 - y0) / length;
+            let nx = -dy * half_width;
+            let ny = dx * half_width;
+
+            // Square caps extend the first and last segments of an open path
+            let (mut sx0, mut sy0, mut sx1, mut sy1) = (x0, y0, x1, y1);
+            if !contour.closed && cap == LineCap::Square {
+                if i == 0 {
+                    sx0 -= dx * half_width;
+                    sy0 -= dy * half_width;
+                }
+                if i == num_segments - 1 {
+                    sx1 += dx * half_width;
+                    sy1 += dy * half_width;
+                }
+            }
+
+            let mut quad = vec![
+                (sx0 + nx, sy0 + ny),
+                (sx1 + nx, sy1 + ny),
+                (sx1 - nx, sy1 - ny),
+                (sx0 - nx, sy0 - ny),
+            ];
+            orient(&mut quad, true);
+            polygons.push(quad);
+        }
+
+        // Joins
+        let (first_join, last_join) = if contour.closed {
+            (0, num_points)
+        } else {
+            (1, num_points - 1)
+        };
+
+        for &(x, y) in points[first_join..last_join].iter() {
+            let mut join = disc(x, y, half_width);
+            orient(&mut join, true);
+            polygons.push(join);
+        }
+
+        if !contour.closed && cap == LineCap::Round {
+            for &(x, y) in [points[0], points[num_points - 1]].iter() {
+                let mut join = disc(x, y, half_width);
+                orient(&mut join, true);
+                polygons.push(join);
+            }
+        }
+    }
+
+    polygons
+}
+
+/// Computes the anti-aliased coverage of polygons using the non-zero fill rule
+///
+/// The coverage is computed within a rectangular region of the frame by accumulating the signed
+/// area covered by each edge, in the same way as the font-rs rasterizer.
+pub(crate) struct Rasterizer {
+    x: i32,
+    y: i32,
+    width: usize,
+    height: usize,
+    accumulation: Vec<f32>,
+}
+
+impl Rasterizer {
+    pub fn new(x: i32, y: i32, width: usize, height: usize) -> Self {
+        Rasterizer {
+            x,
+            y,
+            width,
+            height,
+            accumulation: vec![0.0; width * height + 2],
+        }
+    }
+
+    pub fn add_polygon(&mut self, points: &[(f32, f32)]) {
+        let num_points = points.len();
+        for i in 0..num_points {
+            let (x0, y0) = points[i];
+            let (x1, y1) = points[(i + 1) % num_points];
+            self.add_line(
+                x0 - self.x as f32,
+                y0 - self.y as f32,
+                x1 - self.x as f32,
+                y1 - self.y as f32,
+            );
+        }
+    }
+
+    fn add_line(&mut self, x0: f32, y0: f32, x1: f32, y1: f32) {
+        if (y0 - y1).abs() <= f32::EPSILON {
+            return;
+        }
+
+        // Clamping horizontally preserves the winding of the pixels within the region.
+        // Coverage to the right of the region spills onto the start of the next row, which
+        // the accumulation cancels out.
+        let width = self.width as f32;
+        let (x0, x1) = (x0.max(0.0).min(width), x1.max(0.0).min(width));
+
+        let (dir, (x0, y0), (x1, y1)) = if y0 < y1 {
+            (1.0, (x0, y0), (x1, y1))
+        } else {
+            (-1.0, (x1, y1), (x0, y0))
+        };
+
+        if y1 <= 0.0 || y0 >= self.height as f32 {
+            return;
+        }
+
+        let dxdy = (x1 - x0) / (y1 - y0);
+        let mut x = x0;
+        if y0 < 0.0 {
+            x -= y0 * dxdy;
+        }
+
+        let y_start = y0.max(0.0) as usize;
+        let y_end = (y1.ceil() as usize).min(self.height);
+
+        for y in y_start..y_end {
+            let line_start = y * self.width;
+            let dy = ((y + 1) as f32).min(y1) - (y as f32).max(y0);
+            let x_next = x + dxdy * dy;
+            let d = dy * dir;
+
+            let (left, right) = if x < x_next { (x, x_next) } else { (x_next, x) };
+            let left_floor = left.floor();
+            let left_index = left_floor as usize;
+            let right_ceil = right.ceil();
+            let right_index = right_ceil as usize;
+
+            if right_index <= left_index + 1 {
+                // The edge is within a single pixel column
+                let mid = 0.5 * (x + x_next) - left_floor;
+                self.accumulation[line_start + left_index] += d - d * mid;
+                self.accumulation[line_start + left_index + 1] += d * mid;
+            } else {
+                let s = (right - left).recip();
+                let left_fract = left - left_floor;
+                let a0 = 0.5 * s * (1.0 - left_fract) * (1.0 - left_fract);
+                let right_fract = right - right_ceil + 1.0;
+                let am = 0.5 * s * right_fract * right_fract;
+
+                self.accumulation[line_start + left_index] += d * a0;
+
+                if right_index == left_index + 2 {
+                    self.accumulation[line_start + left_index + 1] += d * (1.0 - a0 - am);
+                } else {
+                    let a1 = s * (1.5 - left_fract);
+                    self.accumulation[line_start + left_index + 1] += d * (a1 - a0);
+                    for index in left_index + 2..right_index - 1 {
+                        self.accumulation[line_start + index] += d * s;
+                    }
+                    let a2 = a1 + (right_index - left_index - 3) as f32 * s;
+                    self.accumulation[line_start + right_index - 1] += d * (1.0 - a2 - am);
+                }
+
+                self.accumulation[line_start + right_index] += d * am;
+            }
+
+            x = x_next;
+        }
+    }
+
+    /// Calls `f` with the position and coverage of every pixel which is at least partly covered
+    pub fn for_each_pixel<F: FnMut(i32, i32, f32)>(&self, mut f: F) {
+        let mut accumulated = 0.0f32;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                accumulated += self.accumulation[y * self.width + x];
+                let coverage = accumulated.abs().min(1.0);
+                if coverage > 1.0 / 512.0 {
+                    f(self.x + x as i32, self.y + y as i32, coverage);
+                }
+            }
+        }
+    }
+}
+
+/// The bounding box of a set of polygons as (min_x, min_y, max_x, max_y)
+pub(crate) fn bounds(polygons: &[Vec<(f32, f32)>]) -> Option<(f32, f32, f32, f32)> {
+    let mut points = polygons.iter().flat_map(|polygon| polygon.iter());
+    let &(x, y) = points.next()?;
+
+    Some(points.fold((x, y, x, y), |(min_x, min_y, max_x, max_y), &(x, y)| {
+        (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+    }))
+}
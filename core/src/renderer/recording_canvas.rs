@@ -0,0 +1,631 @@
+use std::fmt;
+
+use crate::style::Color;
+
+use super::{
+    Canvas, CanvasState, FontId, FontMetrics, ImageId, Paint, PaintKind, Path, PathCommand,
+    Scissor, Solidity, TextMetrics, Transform2D,
+};
+
+// A single draw operation captured by a RecordingCanvas
+//
+// Each operation stores the transform which was active when it was issued so that the position
+// of the drawn shape on screen can be determined without replaying the whole list.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCommand {
+    // The size of the frame in physical pixels
+    SetSize {
+        width: u32,
+        height: u32,
+        dpi_factor: f32,
+    },
+    Clear {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        color: Color,
+    },
+    // Emitted whenever the active scissor changes (None means no scissor)
+    Scissor(Option<Scissor>),
+    FillPath {
+        path: Path,
+        paint: Paint,
+        transform: Transform2D,
+    },
+    StrokePath {
+        path: Path,
+        paint: Paint,
+        transform: Transform2D,
+    },
+    FillText {
+        x: f32,
+        y: f32,
+        text: String,
+        paint: Paint,
+        transform: Transform2D,
+    },
+}
+
+/// A list of the draw operations emitted while drawing a frame
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DisplayList {
+    pub commands: Vec<DrawCommand>,
+}
+
+impl DisplayList {
+    pub fn new() -> Self {
+        DisplayList {
+            commands: Vec::new(),
+        }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<DrawCommand> {
+        self.commands.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Returns the fill operations which use a solid color
+    pub fn fill_colors(&self) -> Vec<Color> {
+        self.commands
+            .iter()
+            .filter_map(|command| match command {
+                DrawCommand::FillPath { paint, .. } => match paint.kind {
+                    PaintKind::Color(color) => Some(color),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the text runs drawn in the frame
+    pub fn texts(&self) -> Vec<&str> {
+        self.commands
+            .iter()
+            .filter_map(|command| match command {
+                DrawCommand::FillText { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Serializes the display list to a line based text format suitable for golden files
+    pub fn serialize(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for DisplayList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for command in self.commands.iter() {
+            writeln!(f, "{}", command)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Numbers are rounded to two decimal places to keep golden files stable
+struct Num(f32);
+
+impl fmt::Display for Num {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rounded = (self.0 * 100.0).round() / 100.0;
+        // Avoid printing negative zero
+        if rounded == 0.0 {
+            write!(f, "0")
+        } else {
+            write!(f, "{}", rounded)
+        }
+    }
+}
+
+struct Hex(Color);
+
+impl fmt::Display for Hex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let color = self.0;
+        write!(
+            f,
+            "#{:02x}{:02x}{:02x}{:02x}",
+            color.r(),
+            color.g(),
+            color.b(),
+            color.a()
+        )
+    }
+}
+
+fn solidity_name(solidity: Solidity) -> &'static str {
+    match solidity {
+        Solidity::Solid => "solid",
+        Solidity::Hole => "hole",
+    }
+}
+
+impl fmt::Display for Transform2D {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a, b, c, d, e, g] = self.0;
+        write!(
+            f,
+            "[{} {} {} {} {} {}]",
+            Num(a),
+            Num(b),
+            Num(c),
+            Num(d),
+            Num(e),
+            Num(g)
+        )
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for command in self.commands.iter() {
+            if !first {
+                write!(f, " ")?;
+            }
+            first = false;
+
+            match *command {
+                PathCommand::MoveTo(x, y) => write!(f, "M {} {}", Num(x), Num(y))?,
+                PathCommand::LineTo(x, y) => write!(f, "L {} {}", Num(x), Num(y))?,
+                PathCommand::BezierTo(c1x, c1y, c2x, c2y, x, y) => write!(
+                    f,
+                    "C {} {} {} {} {} {}",
+                    Num(c1x),
+                    Num(c1y),
+                    Num(c2x),
+                    Num(c2y),
+                    Num(x),
+                    Num(y)
+                )?,
+                PathCommand::QuadTo(cx, cy, x, y) => {
+                    write!(f, "Q {} {} {} {}", Num(cx), Num(cy), Num(x), Num(y))?
+                }
+                PathCommand::Arc(cx, cy, r, a0, a1, dir) => write!(
+                    f,
+                    "arc {} {} {} {} {} {}",
+                    Num(cx),
+                    Num(cy),
+                    Num(r),
+                    Num(a0),
+                    Num(a1),
+                    solidity_name(dir)
+                )?,
+                PathCommand::Rect(x, y, w, h) => {
+                    write!(f, "rect {} {} {} {}", Num(x), Num(y), Num(w), Num(h))?
+                }
+                PathCommand::RoundedRect(x, y, w, h, tl, tr, br, bl) => write!(
+                    f,
+                    "rounded_rect {} {} {} {} {} {} {} {}",
+                    Num(x),
+                    Num(y),
+                    Num(w),
+                    Num(h),
+                    Num(tl),
+                    Num(tr),
+                    Num(br),
+                    Num(bl)
+                )?,
+                PathCommand::Ellipse(cx, cy, rx, ry) => {
+                    write!(f, "ellipse {} {} {} {}", Num(cx), Num(cy), Num(rx), Num(ry))?
+                }
+                PathCommand::Close => write!(f, "Z")?,
+                PathCommand::Solidity(solidity) => write!(f, "{}", solidity_name(solidity))?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Paint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            PaintKind::Color(color) => write!(f, "color {}", Hex(*color)),
+
+            PaintKind::LinearGradient {
+                start_x,
+                start_y,
+                end_x,
+                end_y,
+                stops,
+            } => {
+                write!(
+                    f,
+                    "linear_gradient {} {} {} {}",
+                    Num(*start_x),
+                    Num(*start_y),
+                    Num(*end_x),
+                    Num(*end_y)
+                )?;
+                for (pos, color) in stops.iter() {
+                    write!(f, " {}:{}", Num(*pos), Hex(*color))?;
+                }
+                Ok(())
+            }
+
+            PaintKind::BoxGradient {
+                x,
+                y,
+                width,
+                height,
+                radius,
+                feather,
+                inner_color,
+                outer_color,
+            } => write!(
+                f,
+                "box_gradient {} {} {} {} {} {} {} {}",
+                Num(*x),
+                Num(*y),
+                Num(*width),
+                Num(*height),
+                Num(*radius),
+                Num(*feather),
+                Hex(*inner_color),
+                Hex(*outer_color)
+            ),
+
+            PaintKind::RadialGradient {
+                cx,
+                cy,
+                in_radius,
+                out_radius,
+                inner_color,
+                outer_color,
+            } => write!(
+                f,
+                "radial_gradient {} {} {} {} {} {}",
+                Num(*cx),
+                Num(*cy),
+                Num(*in_radius),
+                Num(*out_radius),
+                Hex(*inner_color),
+                Hex(*outer_color)
+            ),
+
+            PaintKind::Image {
+                id,
+                cx,
+                cy,
+                width,
+                height,
+                angle,
+                alpha,
+            } => write!(
+                f,
+                "image {} {} {} {} {} {} {}",
+                id.0,
+                Num(*cx),
+                Num(*cy),
+                Num(*width),
+                Num(*height),
+                Num(*angle),
+                Num(*alpha)
+            ),
+        }
+    }
+}
+
+impl fmt::Display for DrawCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DrawCommand::SetSize {
+                width,
+                height,
+                dpi_factor,
+            } => write!(f, "size {} {} {}", width, height, Num(*dpi_factor)),
+
+            DrawCommand::Clear {
+                x,
+                y,
+                width,
+                height,
+                color,
+            } => write!(f, "clear {} {} {} {} {}", x, y, width, height, Hex(*color)),
+
+            DrawCommand::Scissor(Some(scissor)) => write!(
+                f,
+                "scissor {} {} {} {} at {}",
+                Num(scissor.x),
+                Num(scissor.y),
+                Num(scissor.width),
+                Num(scissor.height),
+                scissor.transform
+            ),
+
+            DrawCommand::Scissor(None) => write!(f, "scissor none"),
+
+            DrawCommand::FillPath {
+                path,
+                paint,
+                transform,
+            } => write!(f, "fill {} with {} at {}", path, paint, transform),
+
+            DrawCommand::StrokePath {
+                path,
+                paint,
+                transform,
+            } => write!(
+                f,
+                "stroke {} with {} width {} at {}",
+                path,
+                paint,
+                Num(paint.line_width),
+                transform
+            ),
+
+            DrawCommand::FillText {
+                x,
+                y,
+                text,
+                paint,
+                transform,
+            } => {
+                let font = match paint.font {
+                    Some(font) => font.0.to_string(),
+                    None => "none".to_string(),
+                };
+
+                write!(
+                    f,
+                    "text {:?} {} {} font {} size {} align {:?} {:?} with {} at {}",
+                    text,
+                    Num(*x),
+                    Num(*y),
+                    font,
+                    Num(paint.font_size),
+                    paint.text_align,
+                    paint.text_baseline,
+                    paint,
+                    transform
+                )
+            }
+        }
+    }
+}
+
+/// Implementation of `Canvas` which records draw operations instead of rendering them
+///
+/// Used for testing the drawing of widgets without a GPU. Text is not shaped, so text metrics
+/// are approximated from the font size and contain no glyph positions.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut canvas = RecordingCanvas::new();
+/// event_manager.draw(&mut state, &hierarchy, &mut canvas);
+/// let display_list = canvas.take_display_list();
+/// ```
+pub struct RecordingCanvas {
+    display_list: DisplayList,
+    state: CanvasState,
+    saved_states: Vec<CanvasState>,
+    num_fonts: usize,
+    num_images: usize,
+}
+
+impl RecordingCanvas {
+    pub fn new() -> Self {
+        RecordingCanvas {
+            display_list: DisplayList::new(),
+            state: CanvasState::default(),
+            saved_states: Vec::new(),
+            num_fonts: 0,
+            num_images: 0,
+        }
+    }
+
+    /// Returns the operations recorded so far
+    pub fn display_list(&self) -> &DisplayList {
+        &self.display_list
+    }
+
+    /// Returns the operations recorded so far and starts a new display list
+    pub fn take_display_list(&mut self) -> DisplayList {
+        std::mem::replace(&mut self.display_list, DisplayList::new())
+    }
+
+    fn set_scissor(&mut self, scissor: Option<Scissor>) {
+        if self.state.scissor != scissor {
+            self.state.scissor = scissor;
+            self.display_list
+                .commands
+                .push(DrawCommand::Scissor(scissor));
+        }
+    }
+}
+
+impl Default for RecordingCanvas {
+    fn default() -> Self {
+        RecordingCanvas::new()
+    }
+}
+
+impl Canvas for RecordingCanvas {
+    fn set_size(&mut self, width: u32, height: u32, dpi_factor: f32) {
+        self.display_list.commands.push(DrawCommand::SetSize {
+            width,
+            height,
+            dpi_factor,
+        });
+    }
+
+    fn clear_rect(&mut self, x: u32, y: u32, width: u32, height: u32, color: Color) {
+        self.display_list.commands.push(DrawCommand::Clear {
+            x,
+            y,
+            width,
+            height,
+            color,
+        });
+    }
+
+    fn flush(&mut self) {
+        self.state = CanvasState::default();
+        self.saved_states.clear();
+    }
+
+    fn save(&mut self) {
+        self.saved_states.push(self.state);
+    }
+
+    fn restore(&mut self) {
+        if let Some(saved_state) = self.saved_states.pop() {
+            self.state.transform = saved_state.transform;
+            self.set_scissor(saved_state.scissor);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.state.transform = Transform2D::identity();
+        self.set_scissor(None);
+    }
+
+    fn translate(&mut self, x: f32, y: f32) {
+        self.state
+            .transform
+            .premultiply(&Transform2D::new_translation(x, y));
+    }
+
+    fn rotate(&mut self, angle: f32) {
+        self.state
+            .transform
+            .premultiply(&Transform2D::new_rotation(angle));
+    }
+
+    fn scale(&mut self, x: f32, y: f32) {
+        self.state
+            .transform
+            .premultiply(&Transform2D::new_scale(x, y));
+    }
+
+    fn transform(&self) -> Transform2D {
+        self.state.transform
+    }
+
+    fn scissor(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        let transform = self.state.transform;
+        self.set_scissor(Some(Scissor {
+            x,
+            y,
+            width,
+            height,
+            transform,
+        }));
+    }
+
+    fn reset_scissor(&mut self) {
+        self.set_scissor(None);
+    }
+
+    fn fill_path(&mut self, path: &Path, paint: &Paint) {
+        self.display_list.commands.push(DrawCommand::FillPath {
+            path: path.clone(),
+            paint: paint.clone(),
+            transform: self.state.transform,
+        });
+    }
+
+    fn stroke_path(&mut self, path: &Path, paint: &Paint) {
+        self.display_list.commands.push(DrawCommand::StrokePath {
+            path: path.clone(),
+            paint: paint.clone(),
+            transform: self.state.transform,
+        });
+    }
+
+    fn fill_text(&mut self, x: f32, y: f32, text: &str, paint: &Paint) -> Option<TextMetrics> {
+        self.display_list.commands.push(DrawCommand::FillText {
+            x,
+            y,
+            text: text.to_owned(),
+            paint: paint.clone(),
+            transform: self.state.transform,
+        });
+
+        self.measure_text(x, y, text, paint)
+    }
+
+    fn measure_text(&mut self, x: f32, y: f32, _text: &str, paint: &Paint) -> Option<TextMetrics> {
+        Some(TextMetrics {
+            x,
+            y,
+            width: 0.0,
+            height: paint.font_size,
+            glyphs: Vec::new(),
+        })
+    }
+
+    fn measure_font(&mut self, paint: &Paint) -> Option<FontMetrics> {
+        Some(FontMetrics {
+            ascender: paint.font_size * 0.8,
+            descender: -paint.font_size * 0.2,
+            height: paint.font_size,
+        })
+    }
+
+    fn add_font_mem(&mut self, _data: &[u8]) -> Option<FontId> {
+        let id = FontId(self.num_fonts);
+        self.num_fonts += 1;
+        Some(id)
+    }
+
+    fn create_image(&mut self, _width: usize, _height: usize, _data: &[u8]) -> Option<ImageId> {
+        let id = ImageId(self.num_images);
+        self.num_images += 1;
+        Some(id)
+    }
+
+    fn delete_image(&mut self, _id: ImageId) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_display_list() {
+        let mut canvas = RecordingCanvas::new();
+        canvas.set_size(100, 50, 1.0);
+        canvas.clear_rect(0, 0, 100, 50, Color::rgb(255, 255, 255));
+
+        canvas.save();
+        canvas.translate(10.0, 5.0);
+        canvas.scissor(0.0, 0.0, 20.0, 20.0);
+        let mut path = Path::new();
+        path.rect(0.0, 0.0, 20.0, 10.0);
+        canvas.fill_path(&path, &Paint::color(Color::rgb(255, 0, 0)));
+        canvas.restore();
+
+        let mut paint = Paint::color(Color::rgb(0, 0, 0));
+        paint.set_font_size(12.0);
+        canvas.fill_text(1.0, 2.0, "Hi", &paint);
+
+        let display_list = canvas.take_display_list();
+        assert_eq!(
+            display_list.serialize(),
+            "size 100 50 1\n\
+             clear 0 0 100 50 #ffffffff\n\
+             scissor 0 0 20 20 at [1 0 0 1 10 5]\n\
+             fill rect 0 0 20 10 with color #ff0000ff at [1 0 0 1 10 5]\n\
+             scissor none\n\
+             text \"Hi\" 1 2 font none size 12 align Left Alphabetic with color #000000ff \
+             at [1 0 0 1 0 0]\n"
+        );
+        assert_eq!(display_list.fill_colors(), vec![Color::rgb(255, 0, 0)]);
+        assert_eq!(display_list.texts(), vec!["Hi"]);
+        assert!(canvas.display_list().is_empty());
+    }
+}
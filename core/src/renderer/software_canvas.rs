@@ -0,0 +1,677 @@
+use fnv::FnvHashMap;
+
+use crate::style::Color;
+
+use super::rasterizer::{bounds, fill_polygons, flatten, stroke_polygons, Rasterizer};
+use super::{
+    Canvas, CanvasState, FontId, FontMetrics, GlyphPosition, ImageId, Paint, PaintKind, Path,
+    Pixmap, Scissor, TextAlign, TextBaseline, TextMetrics, Transform2D,
+};
+
+// A color with premultiplied alpha and components in the range 0.0 to 1.0
+type Rgba = [f32; 4];
+
+fn premultiplied(color: Color) -> Rgba {
+    let a = color.a() as f32 / 255.0;
+    [
+        color.r() as f32 / 255.0 * a,
+        color.g() as f32 / 255.0 * a,
+        color.b() as f32 / 255.0 * a,
+        a,
+    ]
+}
+
+fn mix(a: Rgba, b: Rgba, t: f32) -> Rgba {
+    [
+        a[0] + (b[0] - a[0]) * t,
+        a[1] + (b[1] - a[1]) * t,
+        a[2] + (b[2] - a[2]) * t,
+        a[3] + (b[3] - a[3]) * t,
+    ]
+}
+
+// Source-over compositing of a premultiplied color scaled by the coverage of the pixel
+fn blend(dst: &mut Rgba, src: Rgba, coverage: f32) {
+    let inv = 1.0 - src[3] * coverage;
+    for i in 0..4 {
+        dst[i] = src[i] * coverage + dst[i] * inv;
+    }
+}
+
+// Signed distance from a point to a rounded rectangle centered on the origin
+fn rounded_rect_distance(x: f32, y: f32, half_width: f32, half_height: f32, radius: f32) -> f32 {
+    let dx = x.abs() - (half_width - radius);
+    let dy = y.abs() - (half_height - radius);
+    dx.max(dy).min(0.0) + (dx.max(0.0) * dx.max(0.0) + dy.max(0.0) * dy.max(0.0)).sqrt() - radius
+}
+
+fn gradient_color(stops: &[(f32, Color)], t: f32) -> Rgba {
+    let (first, last) = match (stops.first(), stops.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return [0.0; 4],
+    };
+
+    if t <= first.0 {
+        return premultiplied(first.1);
+    }
+
+    if t >= last.0 {
+        return premultiplied(last.1);
+    }
+
+    for pair in stops.windows(2) {
+        let (pos0, color0) = pair[0];
+        let (pos1, color1) = pair[1];
+        if t >= pos0 && t <= pos1 {
+            let range = pos1 - pos0;
+            let s = if range > 0.0 { (t - pos0) / range } else { 0.0 };
+            return mix(premultiplied(color0), premultiplied(color1), s);
+        }
+    }
+
+    premultiplied(last.1)
+}
+
+// An RGBA image which has been added to the canvas
+struct SoftwareImage {
+    width: usize,
+    height: usize,
+    data: Vec<u8>,
+}
+
+// Evaluates a paint at a point in the local coordinates of the draw operation
+fn shade(kind: &PaintKind, x: f32, y: f32, images: &FnvHashMap<ImageId, SoftwareImage>) -> Rgba {
+    match kind {
+        PaintKind::Color(color) => premultiplied(*color),
+
+        PaintKind::LinearGradient {
+            start_x,
+            start_y,
+            end_x,
+            end_y,
+            stops,
+        } => {
+            let dx = end_x - start_x;
+            let dy = end_y - start_y;
+            let length_squared = dx * dx + dy * dy;
+            let t = if length_squared > 0.0 {
+                ((x - start_x) * dx + (y - start_y) * dy) / length_squared
+            } else {
+                0.0
+            };
+
+            gradient_color(stops, t)
+        }
+
+        PaintKind::BoxGradient {
+            x: box_x,
+            y: box_y,
+            width,
+            height,
+            radius,
+            feather,
+            inner_color,
+            outer_color,
+        } => {
+            let feather = feather.max(1.0);
+            let distance = rounded_rect_distance(
+                x - (box_x + width * 0.5),
+                y - (box_y + height * 0.5),
+                width * 0.5,
+                height * 0.5,
+                *radius,
+            );
+            let t = ((distance + feather * 0.5) / feather).max(0.0).min(1.0);
+
+            mix(premultiplied(*inner_color), premultiplied(*outer_color), t)
+        }
+
+        PaintKind::RadialGradient {
+            cx,
+            cy,
+            in_radius,
+            out_radius,
+            inner_color,
+            outer_color,
+        } => {
+            let radius = (in_radius + out_radius) * 0.5;
+            let feather = (out_radius - in_radius).max(1.0);
+            let distance = ((x - cx) * (x - cx) + (y - cy) * (y - cy)).sqrt() - radius;
+            let t = ((distance + feather * 0.5) / feather).max(0.0).min(1.0);
+
+            mix(premultiplied(*inner_color), premultiplied(*outer_color), t)
+        }
+
+        PaintKind::Image {
+            id,
+            cx,
+            cy,
+            width,
+            height,
+            angle,
+            alpha,
+        } => {
+            let image = match images.get(id) {
+                Some(image) => image,
+                None => return [0.0; 4],
+            };
+
+            // The pattern is positioned at (cx, cy) and rotated around that point
+            let (sin, cos) = angle.sin_cos();
+            let dx = x - cx;
+            let dy = y - cy;
+            let u = (dx * cos + dy * sin) / width;
+            let v = (-dx * sin + dy * cos) / height;
+
+            if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+                return [0.0; 4];
+            }
+
+            let px = ((u * image.width as f32) as usize).min(image.width - 1);
+            let py = ((v * image.height as f32) as usize).min(image.height - 1);
+            let index = (py * image.width + px) * 4;
+
+            let color = Color::rgba(
+                image.data[index],
+                image.data[index + 1],
+                image.data[index + 2],
+                image.data[index + 3],
+            );
+            let [r, g, b, a] = premultiplied(color);
+
+            [r * alpha, g * alpha, b * alpha, a * alpha]
+        }
+    }
+}
+
+// Returns true if the center of the pixel is inside the scissor rectangle
+fn in_scissor(scissor: &Option<(Scissor, Transform2D)>, x: i32, y: i32) -> bool {
+    match scissor {
+        Some((scissor, inverse)) => {
+            let (sx, sy) = inverse.transform_point(x as f32 + 0.5, y as f32 + 0.5);
+            sx >= scissor.x
+                && sx <= scissor.x + scissor.width
+                && sy >= scissor.y
+                && sy <= scissor.y + scissor.height
+        }
+
+        None => true,
+    }
+}
+
+// A glyph positioned along a line of text, in local coordinates
+struct PositionedGlyph {
+    byte_index: usize,
+    character: char,
+    x: f32,
+    advance: f32,
+}
+
+// The result of laying out a single line of text
+struct TextLayout {
+    font_index: usize,
+    baseline: f32,
+    glyphs: Vec<PositionedGlyph>,
+    metrics: TextMetrics,
+}
+
+/// Implementation of `Canvas` which renders on the CPU into an RGBA buffer
+///
+/// Paths are rasterized with anti-aliasing using the non-zero fill rule and text is rendered
+/// with fontdue, so a frame can be rendered on machines without a GPU. Strokes always use round
+/// joins and scissor rectangles are not anti-aliased, so the output is close to, but not the
+/// same as, the output of the femtovg renderer.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut canvas = SoftwareCanvas::new(800, 600);
+/// event_manager.draw(&mut state, &hierarchy, &mut canvas);
+/// canvas.pixmap().save_png("screenshot.png")?;
+/// ```
+pub struct SoftwareCanvas {
+    width: u32,
+    height: u32,
+    dpi_factor: f32,
+    pixels: Vec<Rgba>,
+    state: CanvasState,
+    saved_states: Vec<CanvasState>,
+    fonts: Vec<fontdue::Font>,
+    // Rasterized glyphs keyed by font index, character and pixel size
+    glyph_cache: FnvHashMap<(usize, char, u32), (fontdue::Metrics, Vec<u8>)>,
+    images: FnvHashMap<ImageId, SoftwareImage>,
+    next_image: usize,
+}
+
+impl SoftwareCanvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        SoftwareCanvas {
+            width,
+            height,
+            dpi_factor: 1.0,
+            pixels: vec![[0.0; 4]; width as usize * height as usize],
+            state: CanvasState::default(),
+            saved_states: Vec::new(),
+            fonts: Vec::new(),
+            glyph_cache: FnvHashMap::default(),
+            images: FnvHashMap::default(),
+            next_image: 0,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn dpi_factor(&self) -> f32 {
+        self.dpi_factor
+    }
+
+    /// Returns a copy of the rendered frame
+    pub fn pixmap(&self) -> Pixmap {
+        let mut data = Vec::with_capacity(self.pixels.len() * 4);
+        for pixel in self.pixels.iter() {
+            let alpha = pixel[3].max(0.0).min(1.0);
+            for i in 0..3 {
+                let value = if alpha > 0.0 { pixel[i] / alpha } else { 0.0 };
+                data.push((value.max(0.0).min(1.0) * 255.0).round() as u8);
+            }
+            data.push((alpha * 255.0).round() as u8);
+        }
+
+        Pixmap::from_rgba(self.width, self.height, data).unwrap()
+    }
+
+    // The region of the frame which can be drawn to as (x0, y0, x1, y1)
+    fn clip_bounds(&self) -> (i32, i32, i32, i32) {
+        let mut clip = (0, 0, self.width as i32, self.height as i32);
+
+        if let Some(scissor) = self.state.scissor {
+            let corners = [
+                (scissor.x, scissor.y),
+                (scissor.x + scissor.width, scissor.y),
+                (scissor.x + scissor.width, scissor.y + scissor.height),
+                (scissor.x, scissor.y + scissor.height),
+            ];
+            let polygon: Vec<(f32, f32)> = corners
+                .iter()
+                .map(|&(x, y)| scissor.transform.transform_point(x, y))
+                .collect();
+
+            if let Some((min_x, min_y, max_x, max_y)) = bounds(&[polygon]) {
+                clip.0 = clip.0.max(min_x.floor() as i32);
+                clip.1 = clip.1.max(min_y.floor() as i32);
+                clip.2 = clip.2.min(max_x.ceil() as i32);
+                clip.3 = clip.3.min(max_y.ceil() as i32);
+            }
+        }
+
+        clip
+    }
+
+    fn fill_polygons(&mut self, polygons: &[Vec<(f32, f32)>], paint: &Paint, alpha: f32) {
+        let (min_x, min_y, max_x, max_y) = match bounds(polygons) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+
+        let clip = self.clip_bounds();
+        let x0 = (min_x.floor() as i32).max(clip.0);
+        let y0 = (min_y.floor() as i32).max(clip.1);
+        let x1 = (max_x.ceil() as i32 + 1).min(clip.2);
+        let y1 = (max_y.ceil() as i32 + 1).min(clip.3);
+
+        if x1 <= x0 || y1 <= y0 {
+            return;
+        }
+
+        let mut rasterizer = Rasterizer::new(x0, y0, (x1 - x0) as usize, (y1 - y0) as usize);
+        for polygon in polygons.iter() {
+            rasterizer.add_polygon(polygon);
+        }
+
+        let inverse = self.state.transform.inversed();
+        let scissor = self
+            .state
+            .scissor
+            .map(|scissor| (scissor, scissor.transform.inversed()));
+        let width = self.width as usize;
+        let images = &self.images;
+        let pixels = &mut self.pixels;
+
+        rasterizer.for_each_pixel(|x, y, coverage| {
+            if !in_scissor(&scissor, x, y) {
+                return;
+            }
+
+            let (lx, ly) = inverse.transform_point(x as f32 + 0.5, y as f32 + 0.5);
+            let color = shade(&paint.kind, lx, ly, images);
+            blend(
+                &mut pixels[y as usize * width + x as usize],
+                color,
+                coverage * alpha,
+            );
+        });
+    }
+
+    fn layout_text(&self, x: f32, y: f32, text: &str, paint: &Paint) -> Option<TextLayout> {
+        let font_index = paint.font.map(|font| font.0).unwrap_or(0);
+        let font = self.fonts.get(font_index)?;
+        let size = paint.font_size;
+
+        let line_metrics = font.horizontal_line_metrics(size)?;
+        let ascent = line_metrics.ascent;
+        let descent = line_metrics.descent;
+
+        let mut glyphs = Vec::new();
+        let mut pen = 0.0;
+        let mut prev = None;
+        for (byte_index, character) in text.char_indices() {
+            if let Some(prev) = prev {
+                pen += font.horizontal_kern(prev, character, size).unwrap_or(0.0);
+            }
+
+            let advance = font.metrics(character, size).advance_width;
+            glyphs.push(PositionedGlyph {
+                byte_index,
+                character,
+                x: pen,
+                advance,
+            });
+
+            pen += advance;
+            prev = Some(character);
+        }
+
+        let width = pen;
+
+        let start_x = match paint.text_align {
+            TextAlign::Left => x,
+            TextAlign::Center => x - width * 0.5,
+            TextAlign::Right => x - width,
+        };
+
+        let baseline = match paint.text_baseline {
+            TextBaseline::Top => y + ascent,
+            TextBaseline::Middle => y + (ascent + descent) * 0.5,
+            TextBaseline::Alphabetic => y,
+            TextBaseline::Bottom => y + descent,
+        };
+
+        for glyph in glyphs.iter_mut() {
+            glyph.x += start_x;
+        }
+
+        let top = baseline - ascent;
+        let height = ascent - descent;
+
+        let metrics = TextMetrics {
+            x: start_x,
+            y: top,
+            width,
+            height,
+            glyphs: glyphs
+                .iter()
+                .map(|glyph| GlyphPosition {
+                    byte_index: glyph.byte_index,
+                    x: glyph.x,
+                    y: top,
+                    width: glyph.advance,
+                    height,
+                })
+                .collect(),
+        };
+
+        Some(TextLayout {
+            font_index,
+            baseline,
+            glyphs,
+            metrics,
+        })
+    }
+
+    fn draw_text(&mut self, layout: &TextLayout, paint: &Paint) {
+        let transform = self.state.transform;
+        let inverse = transform.inversed();
+        // Glyphs are rasterized at their size on screen
+        let size = paint.font_size * transform.average_scale();
+        let clip = self.clip_bounds();
+        let scissor = self
+            .state
+            .scissor
+            .map(|scissor| (scissor, scissor.transform.inversed()));
+
+        let font = &self.fonts[layout.font_index];
+        let glyph_cache = &mut self.glyph_cache;
+        let images = &self.images;
+        let pixels = &mut self.pixels;
+        let width = self.width as usize;
+
+        for glyph in layout.glyphs.iter() {
+            let (metrics, bitmap) = glyph_cache
+                .entry((layout.font_index, glyph.character, size.to_bits()))
+                .or_insert_with(|| font.rasterize(glyph.character, size));
+
+            let (ox, oy) = transform.transform_point(glyph.x, layout.baseline);
+            let left = ox.round() as i32 + metrics.xmin;
+            let top = oy.round() as i32 - metrics.ymin - metrics.height as i32;
+
+            for row in 0..metrics.height {
+                let y = top + row as i32;
+                if y < clip.1 || y >= clip.3 {
+                    continue;
+                }
+
+                for column in 0..metrics.width {
+                    let x = left + column as i32;
+                    if x < clip.0 || x >= clip.2 {
+                        continue;
+                    }
+
+                    let coverage = bitmap[row * metrics.width + column] as f32 / 255.0;
+                    if coverage <= 0.0 || !in_scissor(&scissor, x, y) {
+                        continue;
+                    }
+
+                    let (lx, ly) = inverse.transform_point(x as f32 + 0.5, y as f32 + 0.5);
+                    let color = shade(&paint.kind, lx, ly, images);
+                    blend(&mut pixels[y as usize * width + x as usize], color, coverage);
+                }
+            }
+        }
+    }
+}
+
+impl Canvas for SoftwareCanvas {
+    fn set_size(&mut self, width: u32, height: u32, dpi_factor: f32) {
+        self.dpi_factor = dpi_factor;
+
+        if width != self.width || height != self.height {
+            self.width = width;
+            self.height = height;
+            self.pixels = vec![[0.0; 4]; width as usize * height as usize];
+        }
+    }
+
+    fn clear_rect(&mut self, x: u32, y: u32, width: u32, height: u32, color: Color) {
+        let color = premultiplied(color);
+        for py in y..(y + height).min(self.height) {
+            for px in x..(x + width).min(self.width) {
+                self.pixels[py as usize * self.width as usize + px as usize] = color;
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        self.state = CanvasState::default();
+        self.saved_states.clear();
+    }
+
+    fn save(&mut self) {
+        self.saved_states.push(self.state);
+    }
+
+    fn restore(&mut self) {
+        if let Some(saved_state) = self.saved_states.pop() {
+            self.state = saved_state;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.state = CanvasState::default();
+    }
+
+    fn translate(&mut self, x: f32, y: f32) {
+        self.state
+            .transform
+            .premultiply(&Transform2D::new_translation(x, y));
+    }
+
+    fn rotate(&mut self, angle: f32) {
+        self.state
+            .transform
+            .premultiply(&Transform2D::new_rotation(angle));
+    }
+
+    fn scale(&mut self, x: f32, y: f32) {
+        self.state
+            .transform
+            .premultiply(&Transform2D::new_scale(x, y));
+    }
+
+    fn transform(&self) -> Transform2D {
+        self.state.transform
+    }
+
+    fn scissor(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.state.scissor = Some(Scissor {
+            x,
+            y,
+            width: width.max(0.0),
+            height: height.max(0.0),
+            transform: self.state.transform,
+        });
+    }
+
+    fn reset_scissor(&mut self) {
+        self.state.scissor = None;
+    }
+
+    fn fill_path(&mut self, path: &Path, paint: &Paint) {
+        let polygons = fill_polygons(flatten(path, &self.state.transform));
+        self.fill_polygons(&polygons, paint, 1.0);
+    }
+
+    fn stroke_path(&mut self, path: &Path, paint: &Paint) {
+        let contours = flatten(path, &self.state.transform);
+
+        // Strokes thinner than a pixel are drawn one pixel wide with reduced opacity
+        let mut width = paint.line_width * self.state.transform.average_scale();
+        let mut alpha = 1.0;
+        if width < 1.0 {
+            alpha = width * width;
+            width = 1.0;
+        }
+
+        let polygons = stroke_polygons(&contours, width, paint.line_cap);
+        self.fill_polygons(&polygons, paint, alpha);
+    }
+
+    fn fill_text(&mut self, x: f32, y: f32, text: &str, paint: &Paint) -> Option<TextMetrics> {
+        let layout = self.layout_text(x, y, text, paint)?;
+        self.draw_text(&layout, paint);
+        Some(layout.metrics)
+    }
+
+    fn measure_text(&mut self, x: f32, y: f32, text: &str, paint: &Paint) -> Option<TextMetrics> {
+        self.layout_text(x, y, text, paint)
+            .map(|layout| layout.metrics)
+    }
+
+    fn measure_font(&mut self, paint: &Paint) -> Option<FontMetrics> {
+        let font_index = paint.font.map(|font| font.0).unwrap_or(0);
+        let line_metrics = self
+            .fonts
+            .get(font_index)?
+            .horizontal_line_metrics(paint.font_size)?;
+
+        Some(FontMetrics {
+            ascender: line_metrics.ascent,
+            descender: line_metrics.descent,
+            height: line_metrics.new_line_size,
+        })
+    }
+
+    fn add_font_mem(&mut self, data: &[u8]) -> Option<FontId> {
+        let font = fontdue::Font::from_bytes(data, fontdue::FontSettings::default()).ok()?;
+        self.fonts.push(font);
+        Some(FontId(self.fonts.len() - 1))
+    }
+
+    fn create_image(&mut self, width: usize, height: usize, data: &[u8]) -> Option<ImageId> {
+        if width == 0 || height == 0 || data.len() != width * height * 4 {
+            return None;
+        }
+
+        let id = ImageId(self.next_image);
+        self.next_image += 1;
+        self.images.insert(
+            id,
+            SoftwareImage {
+                width,
+                height,
+                data: data.to_vec(),
+            },
+        );
+
+        Some(id)
+    }
+
+    fn delete_image(&mut self, id: ImageId) {
+        self.images.remove(&id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: Color = Color::rgb(255, 255, 255);
+    const RED: Color = Color::rgb(255, 0, 0);
+    const BLUE: Color = Color::rgb(0, 0, 255);
+
+    #[test]
+    fn renders_transformed_and_clipped_paths() {
+        let mut canvas = SoftwareCanvas::new(20, 10);
+        canvas.clear_rect(0, 0, 20, 10, WHITE);
+
+        canvas.save();
+        canvas.translate(4.0, 2.0);
+        let mut path = Path::new();
+        path.rect(0.0, 0.0, 4.0, 4.0);
+        canvas.fill_path(&path, &Paint::color(RED));
+        canvas.restore();
+
+        // Only the scissored part of a path which covers the whole frame is drawn
+        canvas.scissor(12.0, 0.0, 4.0, 10.0);
+        let mut path = Path::new();
+        path.rect(0.0, 0.0, 20.0, 10.0);
+        canvas.fill_path(&path, &Paint::color(BLUE));
+
+        let pixmap = canvas.pixmap();
+        assert_eq!((pixmap.width(), pixmap.height()), (20, 10));
+        assert_eq!(pixmap.pixel(0, 0), Some(WHITE));
+        assert_eq!(pixmap.pixel(4, 2), Some(RED));
+        assert_eq!(pixmap.pixel(7, 5), Some(RED));
+        assert_eq!(pixmap.pixel(8, 6), Some(WHITE));
+        assert_eq!(pixmap.pixel(11, 5), Some(WHITE));
+        assert_eq!(pixmap.pixel(12, 5), Some(BLUE));
+        assert_eq!(pixmap.pixel(15, 9), Some(BLUE));
+        assert_eq!(pixmap.pixel(16, 5), Some(WHITE));
+        assert_eq!(pixmap.pixel(20, 0), None);
+    }
+}
@@ -0,0 +1,78 @@
+/// A 2D affine transform stored as [a, b, c, d, e, f]
+///
+/// A point (x, y) is transformed to (a * x + c * y + e, b * x + d * y + f).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform2D(pub [f32; 6]);
+
+impl Default for Transform2D {
+    fn default() -> Self {
+        Transform2D::identity()
+    }
+}
+
+impl Transform2D {
+    pub fn identity() -> Self {
+        Transform2D([1.0, 0.0, 0.0, 1.0, 0.0, 0.0])
+    }
+
+    pub fn new_translation(x: f32, y: f32) -> Self {
+        Transform2D([1.0, 0.0, 0.0, 1.0, x, y])
+    }
+
+    pub fn new_rotation(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Transform2D([cos, sin, -sin, cos, 0.0, 0.0])
+    }
+
+    pub fn new_scale(x: f32, y: f32) -> Self {
+        Transform2D([x, 0.0, 0.0, y, 0.0, 0.0])
+    }
+
+    /// Applies `other` before this transform (self = self * other)
+    pub fn premultiply(&mut self, other: &Transform2D) {
+        let [a0, b0, c0, d0, e0, f0] = self.0;
+        let [a1, b1, c1, d1, e1, f1] = other.0;
+
+        self.0 = [
+            a0 * a1 + c0 * b1,
+            b0 * a1 + d0 * b1,
+            a0 * c1 + c0 * d1,
+            b0 * c1 + d0 * d1,
+            a0 * e1 + c0 * f1 + e0,
+            b0 * e1 + d0 * f1 + f0,
+        ];
+    }
+
+    pub fn inversed(&self) -> Self {
+        let [a, b, c, d, e, f] = self.0;
+        let det = a * d - b * c;
+
+        if det.abs() < 1e-6 {
+            return Transform2D::identity();
+        }
+
+        let inv_det = 1.0 / det;
+
+        Transform2D([
+            d * inv_det,
+            -b * inv_det,
+            -c * inv_det,
+            a * inv_det,
+            (c * f - d * e) * inv_det,
+            (b * e - a * f) * inv_det,
+        ])
+    }
+
+    pub fn transform_point(&self, x: f32, y: f32) -> (f32, f32) {
+        let [a, b, c, d, e, f] = self.0;
+        (a * x + c * y + e, b * x + d * y + f)
+    }
+
+    /// Returns the average scale factor of the transform
+    pub fn average_scale(&self) -> f32 {
+        let [a, b, c, d, _, _] = self.0;
+        let sx = (a * a + b * b).sqrt();
+        let sy = (c * c + d * d).sqrt();
+        (sx + sy) * 0.5
+    }
+}
@@ -1,6 +0,0 @@
-
-
-
-pub trait Canvas {
-    
-}
\ No newline at end of file
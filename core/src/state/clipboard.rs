@@ -0,0 +1,187 @@
+use crate::state::State;
+
+/// The MIME type of plain text
+pub const TEXT_PLAIN: &str = "text/plain";
+
+/// The contents of a clipboard, which can hold the same data in several formats
+///
+/// Each format is identified by a MIME type. Widgets can put their own formats on the clipboard
+/// alongside plain text, so that pasting into the same kind of widget keeps more information
+/// while pasting elsewhere still gets the text.
+///
+/// ```ignore
+/// let contents = ClipboardContents::from_text("0.5, 0.25")
+///     .with_data("application/x-tuix-vector", vec![0, 0, 0, 63, 0, 0, 128, 62]);
+/// state.clipboard().set_contents(contents);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClipboardContents {
+    formats: Vec<(String, Vec<u8>)>,
+}
+
+impl ClipboardContents {
+    pub fn new() -> Self {
+        ClipboardContents::default()
+    }
+
+    pub fn from_text(text: &str) -> Self {
+        ClipboardContents::new().with_text(text)
+    }
+
+    /// Adds data in a format, replacing any existing data in the same format
+    pub fn with_data(mut self, mime: &str, data: Vec<u8>) -> Self {
+        self.set_data(mime, data);
+
+        self
+    }
+
+    pub fn with_text(self, text: &str) -> Self {
+        self.with_data(TEXT_PLAIN, text.as_bytes().to_vec())
+    }
+
+    pub fn set_data(&mut self, mime: &str, data: Vec<u8>) {
+        match self.formats.iter_mut().find(|(format, _)| format == mime) {
+            Some((_, existing)) => *existing = data,
+            None => self.formats.push((mime.to_string(), data)),
+        }
+    }
+
+    /// Returns the data in a format
+    pub fn get(&self, mime: &str) -> Option<&[u8]> {
+        self.formats
+            .iter()
+            .find(|(format, _)| format == mime)
+            .map(|(_, data)| data.as_slice())
+    }
+
+    /// Returns the plain text, if there is any and it is valid UTF-8
+    pub fn text(&self) -> Option<&str> {
+        self.get(TEXT_PLAIN)
+            .and_then(|data| std::str::from_utf8(data).ok())
+    }
+
+    /// Iterates the MIME types of the formats
+    pub fn formats(&self) -> impl Iterator<Item = &str> {
+        self.formats.iter().map(|(format, _)| format.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.formats.is_empty()
+    }
+}
+
+/// Access to a clipboard, set on the state with `State::set_clipboard`
+///
+/// Each backend sets the clipboard of its platform. The default is a `MemoryClipboard`, which is
+/// used when running headless.
+pub trait Clipboard: Send {
+    fn get_contents(&mut self) -> ClipboardContents;
+
+    fn set_contents(&mut self, contents: ClipboardContents);
+
+    fn get_text(&mut self) -> Option<String> {
+        self.get_contents().text().map(|text| text.to_string())
+    }
+
+    fn set_text(&mut self, text: &str) {
+        self.set_contents(ClipboardContents::from_text(text));
+    }
+}
+
+/// A clipboard which is only shared within the application
+#[derive(Debug, Clone, Default)]
+pub struct MemoryClipboard {
+    contents: ClipboardContents,
+}
+
+impl MemoryClipboard {
+    pub fn new() -> Self {
+        MemoryClipboard::default()
+    }
+}
+
+impl Clipboard for MemoryClipboard {
+    fn get_contents(&mut self) -> ClipboardContents {
+        self.contents.clone()
+    }
+
+    fn set_contents(&mut self, contents: ClipboardContents) {
+        self.contents = contents;
+    }
+}
+
+/// The clipboard of the operating system
+///
+/// Only plain text is shared with other applications. The other formats of the last contents
+/// set are kept and returned for as long as the text on the system clipboard is unchanged, so
+/// they still work within the application.
+#[cfg(feature = "clipboard")]
+pub struct SystemClipboard {
+    context: Option<copypasta::ClipboardContext>,
+    // The last contents set, used to return the formats the system clipboard does not hold
+    last: ClipboardContents,
+}
+
+#[cfg(feature = "clipboard")]
+impl SystemClipboard {
+    pub fn new() -> Self {
+        SystemClipboard {
+            context: copypasta::ClipboardContext::new().ok(),
+            last: ClipboardContents::new(),
+        }
+    }
+}
+
+#[cfg(feature = "clipboard")]
+impl Clipboard for SystemClipboard {
+    fn get_contents(&mut self) -> ClipboardContents {
+        use copypasta::ClipboardProvider;
+
+        let text = match self.context.as_mut() {
+            Some(context) => context.get_contents().ok(),
+            // Fall back to the formats kept in memory if there is no system clipboard
+            None => return self.last.clone(),
+        };
+
+        match text {
+            Some(text) if self.last.text() == Some(text.as_str()) => self.last.clone(),
+            Some(text) => ClipboardContents::from_text(&text),
+            None => ClipboardContents::new(),
+        }
+    }
+
+    fn set_contents(&mut self, contents: ClipboardContents) {
+        use copypasta::ClipboardProvider;
+
+        if let (Some(context), Some(text)) = (self.context.as_mut(), contents.text()) {
+            context.set_contents(text.to_string()).ok();
+        }
+
+        self.last = contents;
+    }
+}
+
+impl State {
+    /// Replaces the clipboard, which is used by widgets to copy and paste
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// state.set_clipboard(SystemClipboard::new());
+    /// ```
+    pub fn set_clipboard<C: Clipboard + 'static>(&mut self, clipboard: C) {
+        self.clipboard = Box::new(clipboard);
+    }
+
+    /// Returns the clipboard
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// state.clipboard().set_text("Hello");
+    /// assert_eq!(state.clipboard().get_text().as_deref(), Some("Hello"));
+    /// ```
+    pub fn clipboard(&mut self) -> &mut dyn Clipboard {
+        &mut *self.clipboard
+    }
+}
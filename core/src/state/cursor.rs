@@ -0,0 +1,105 @@
+use crate::entity::Entity;
+use crate::state::hierarchy::IntoParentIterator;
+use crate::state::State;
+use crate::window_event::CursorIcon;
+
+impl State {
+    /// Returns the icon the mouse cursor should show
+    ///
+    /// The backends check this after processing events and update the cursor of the window when
+    /// it changes.
+    pub fn cursor(&self) -> CursorIcon {
+        self.cursor
+    }
+
+    /// Sets the icon of the mouse cursor until the hovered entity changes
+    ///
+    /// Most widgets should set the `cursor` style property instead, which is applied whenever an
+    /// entity becomes hovered. This is for cursors which depend on more than the hovered entity,
+    /// such as a resize cursor near the edge of a panel or a grabbing cursor during a drag.
+    /// While an entity has captured the mouse the cursor is kept until the mouse is released, so
+    /// a cursor set when capturing the mouse lasts for the whole drag. Sending
+    /// `WindowEvent::SetCursor` does the same.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// if near_edge {
+    ///     state.set_cursor(CursorIcon::EwResize);
+    /// } else {
+    ///     state.reset_cursor();
+    /// }
+    /// ```
+    pub fn set_cursor(&mut self, cursor: CursorIcon) {
+        self.cursor = cursor;
+    }
+
+    /// Restores the cursor of the hovered entity after a call to `set_cursor`
+    ///
+    /// This is also called when the hovered entity changes while the mouse is not captured, and
+    /// when the mouse is released.
+    pub fn reset_cursor(&mut self) {
+        self.cursor = self.style_cursor(self.hovered);
+    }
+
+    // The cursor is inherited from the nearest ancestor with the cursor property, as in CSS
+    fn style_cursor(&self, entity: Entity) -> CursorIcon {
+        entity
+            .parent_iter(&self.hierarchy)
+            .find_map(|ancestor| self.style.cursor.get(ancestor).cloned())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::apply_hover;
+
+    // Places an entity at a position with a size so that it can be hovered
+    fn place(state: &mut State, entity: Entity, x: f32, width: f32) {
+        state.data.set_posx(entity, x);
+        state.data.set_posy(entity, 0.0);
+        state.data.set_width(entity, width);
+        state.data.set_height(entity, 100.0);
+        state.data.set_opacity(entity, 1.0);
+    }
+
+    fn hover(state: &mut State, x: f32) {
+        state.mouse.cursorx = x;
+        state.mouse.cursory = 50.0;
+        apply_hover(state);
+    }
+
+    #[test]
+    fn captured_entity_keeps_cursor_until_released() {
+        let mut state = State::new();
+        state.hierarchy.add(Entity::root(), None);
+        place(&mut state, Entity::root(), 0.0, 200.0);
+
+        let handle = state.add(Entity::root());
+        let button = state.add(Entity::root());
+        place(&mut state, handle, 0.0, 100.0);
+        place(&mut state, button, 100.0, 100.0);
+        state.style.cursor.insert(handle, CursorIcon::Grab);
+        state.style.cursor.insert(button, CursorIcon::Hand);
+
+        hover(&mut state, 50.0);
+        assert_eq!(state.cursor(), CursorIcon::Grab);
+
+        state.capture(handle);
+        state.set_cursor(CursorIcon::Grabbing);
+
+        // Moving over another entity while captured does not reset the cursor
+        hover(&mut state, 150.0);
+        assert_eq!(state.hovered, button);
+        assert_eq!(state.cursor(), CursorIcon::Grabbing);
+
+        // Releasing restores the cursor of the hovered entity
+        state.release(handle);
+        assert_eq!(state.cursor(), CursorIcon::Hand);
+
+        hover(&mut state, 50.0);
+        assert_eq!(state.cursor(), CursorIcon::Grab);
+    }
+}
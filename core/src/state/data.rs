@@ -50,6 +50,8 @@ pub struct Data {
     pub(crate) child_pos: Vec<f32>,
     pub(crate) child_grow_sum: Vec<f32>,
     pub(crate) child_shrink_sum: Vec<f32>,
+    // The generation of the entity which owns each index
+    generations: Vec<u32>,
 }
 
 impl Data {
@@ -67,6 +69,7 @@ impl Data {
             opacity: Vec::new(),
             z_order: Vec::new(),
             clip_widget: Vec::new(),
+            generations: Vec::new(),
         }
     }
 
@@ -85,77 +88,107 @@ impl Data {
             self.child_shrink_sum.resize(key + 1, 0.0);
             self.opacity.resize(key + 1, 0.0);
             self.z_order.resize(key + 1, 0);
-            self.clip_widget.resize(key + 1, Entity::new(0));
+            self.clip_widget.resize(key + 1, Entity::root());
+            self.generations.resize(key + 1, 0);
         }
 
-        // Are these needed?
-        if let Some(stored) = self.size.get_mut(key) {
-            *stored = Default::default();
-        }
+        self.generations[key] = entity.generation();
+
+        // Reset the data in case the index is being reused from a removed entity
+        self.reset(key);
+    }
 
-        if let Some(stored) = self.position.get_mut(key) {
-            *stored = Default::default();
+    // Resets the data of a removed entity. The index keeps its slot in the data so that it can
+    // be reused by a later entity.
+    pub fn remove(&mut self, entity: Entity) {
+        let key = self.key(entity);
+        if key >= self.generations.len() {
+            return;
         }
 
-        if let Some(stored) = self.visibility.get_mut(key) {
-            *stored = Default::default();
+        self.reset(key);
+        // Stale copies of the removed entity no longer match the generation
+        self.generations[key] = entity.generation().wrapping_add(1);
+    }
+
+    fn reset(&mut self, key: usize) {
+        self.position[key] = Default::default();
+        self.size[key] = Default::default();
+        self.visibility[key] = Default::default();
+        self.hoverability[key] = true;
+        self.child_sum[key] = 0.0;
+        self.child_max[key] = 0.0;
+        self.child_pos[key] = 0.0;
+        self.child_grow_sum[key] = 0.0;
+        self.child_shrink_sum[key] = 0.0;
+        self.opacity[key] = 0.0;
+        self.z_order[key] = 0;
+        self.clip_widget[key] = Entity::root();
+    }
+
+    // Returns the index of the data for the entity.
+    // A stale entity, whose index has been reused by a newer entity, returns an index past the
+    // end of the data so that getters return a default value and setters do nothing.
+    fn key(&self, entity: Entity) -> usize {
+        let index = entity.index_unchecked();
+        match self.generations.get(index) {
+            Some(generation) if *generation == entity.generation() => index,
+            _ => std::usize::MAX,
         }
     }
 
-    pub fn remove(&mut self, _entity: Entity) {}
-
-    // For getters and setters it's safe to use unwrap because every entity must have a position and size.
-    // Event if the position and size are 0.0, or the entity is invisible.
+    // Every live entity has a position and size, even if the position and size are 0.0 or the
+    // entity is invisible. Getters return a default value for stale entities.
 
     pub fn get_clip_widget(&self, entity: Entity) -> Entity {
         self.clip_widget
-            .get(entity.index_unchecked())
+            .get(self.key(entity))
             .cloned()
-            .unwrap()
+            .unwrap_or(Entity::root())
     }
 
     pub fn get_z_order(&self, entity: Entity) -> i32 {
-        self.z_order.get(entity.index_unchecked()).cloned().unwrap()
+        self.z_order.get(self.key(entity)).cloned().unwrap_or_default()
     }
 
     pub fn get_child_sum(&self, entity: Entity) -> f32 {
         self.child_sum
-            .get(entity.index_unchecked())
+            .get(self.key(entity))
             .cloned()
-            .unwrap()
+            .unwrap_or_default()
     }
 
     pub fn get_child_max(&self, entity: Entity) -> f32 {
         self.child_max
-            .get(entity.index_unchecked())
+            .get(self.key(entity))
             .cloned()
-            .unwrap()
+            .unwrap_or_default()
     }
 
     pub fn get_child_pos(&self, entity: Entity) -> f32 {
         self.child_pos
-            .get(entity.index_unchecked())
+            .get(self.key(entity))
             .cloned()
-            .unwrap()
+            .unwrap_or_default()
     }
 
     pub fn get_child_grow_sum(&self, entity: Entity) -> f32 {
         self.child_grow_sum
-            .get(entity.index_unchecked())
+            .get(self.key(entity))
             .cloned()
-            .unwrap()
+            .unwrap_or_default()
     }
 
     pub fn get_child_shrink_sum(&self, entity: Entity) -> f32 {
         self.child_shrink_sum
-            .get(entity.index_unchecked())
+            .get(self.key(entity))
             .cloned()
-            .unwrap()
+            .unwrap_or_default()
     }
 
     pub fn get_posx(&self, entity: Entity) -> f32 {
         self.position
-            .get(entity.index_unchecked())
+            .get(self.key(entity))
             .cloned()
             .unwrap_or_default()
             .x
@@ -163,7 +196,7 @@ impl Data {
 
     pub fn get_posy(&self, entity: Entity) -> f32 {
         self.position
-            .get(entity.index_unchecked())
+            .get(self.key(entity))
             .cloned()
             .unwrap_or_default()
             .y
@@ -171,7 +204,7 @@ impl Data {
 
     pub fn get_width(&self, entity: Entity) -> f32 {
         self.size
-            .get(entity.index_unchecked())
+            .get(self.key(entity))
             .cloned()
             .unwrap_or_default()
             .x
@@ -179,112 +212,126 @@ impl Data {
 
     pub fn get_height(&self, entity: Entity) -> f32 {
         self.size
-            .get(entity.index_unchecked())
+            .get(self.key(entity))
             .cloned()
             .unwrap_or_default()
             .y
     }
 
     pub fn get_opacity(&self, entity: Entity) -> f32 {
-        self.opacity.get(entity.index_unchecked()).cloned().unwrap()
+        self.opacity.get(self.key(entity)).cloned().unwrap_or_default()
     }
 
     // SETTERS
 
     pub fn set_clip_widget(&mut self, entity: Entity, val: Entity) {
-        if let Some(clip_widget) = self.clip_widget.get_mut(entity.index_unchecked()) {
+        let key = self.key(entity);
+        if let Some(clip_widget) = self.clip_widget.get_mut(key) {
             *clip_widget = val;
         }
     }
 
     pub fn set_z_order(&mut self, entity: Entity, val: i32) {
-        if let Some(z_order) = self.z_order.get_mut(entity.index_unchecked()) {
+        let key = self.key(entity);
+        if let Some(z_order) = self.z_order.get_mut(key) {
             *z_order = val;
         }
     }
 
     pub fn set_child_sum(&mut self, entity: Entity, val: f32) {
-        if let Some(child_sum) = self.child_sum.get_mut(entity.index_unchecked()) {
+        let key = self.key(entity);
+        if let Some(child_sum) = self.child_sum.get_mut(key) {
             *child_sum = val;
         }
     }
 
     pub fn set_child_max(&mut self, entity: Entity, val: f32) {
-        if let Some(child_max) = self.child_max.get_mut(entity.index_unchecked()) {
+        let key = self.key(entity);
+        if let Some(child_max) = self.child_max.get_mut(key) {
             *child_max = val;
         }
     }
 
     pub fn set_child_pos(&mut self, entity: Entity, val: f32) {
-        if let Some(child_pos) = self.child_pos.get_mut(entity.index_unchecked()) {
+        let key = self.key(entity);
+        if let Some(child_pos) = self.child_pos.get_mut(key) {
             *child_pos = val;
         }
     }
 
     pub fn set_child_grow_sum(&mut self, entity: Entity, val: f32) {
-        if let Some(child_grow_sum) = self.child_grow_sum.get_mut(entity.index_unchecked()) {
+        let key = self.key(entity);
+        if let Some(child_grow_sum) = self.child_grow_sum.get_mut(key) {
             *child_grow_sum = val;
         }
     }
 
     pub fn set_child_shrink_sum(&mut self, entity: Entity, val: f32) {
-        if let Some(child_shrink_sum) = self.child_shrink_sum.get_mut(entity.index_unchecked()) {
+        let key = self.key(entity);
+        if let Some(child_shrink_sum) = self.child_shrink_sum.get_mut(key) {
             *child_shrink_sum = val;
         }
     }
 
     pub fn set_posx(&mut self, entity: Entity, val: f32) {
-        if let Some(position) = self.position.get_mut(entity.index_unchecked()) {
+        let key = self.key(entity);
+        if let Some(position) = self.position.get_mut(key) {
             position.x = val;
         }
     }
 
     pub fn set_posy(&mut self, entity: Entity, val: f32) {
-        if let Some(position) = self.position.get_mut(entity.index_unchecked()) {
+        let key = self.key(entity);
+        if let Some(position) = self.position.get_mut(key) {
             position.y = val;
         }
     }
 
     pub fn set_width(&mut self, entity: Entity, val: f32) {
-        if let Some(size) = self.size.get_mut(entity.index_unchecked()) {
+        let key = self.key(entity);
+        if let Some(size) = self.size.get_mut(key) {
             size.x = val;
         }
     }
 
     pub fn set_height(&mut self, entity: Entity, val: f32) {
-        if let Some(size) = self.size.get_mut(entity.index_unchecked()) {
+        let key = self.key(entity);
+        if let Some(size) = self.size.get_mut(key) {
             size.y = val;
         }
     }
 
     pub fn get_visibility(&self, entity: Entity) -> Visibility {
         self.visibility
-            .get(entity.index_unchecked())
+            .get(self.key(entity))
             .cloned()
-            .unwrap()
+            .unwrap_or_default()
     }
 
     pub fn set_visibility(&mut self, entity: Entity, val: Visibility) {
-        if let Some(visibility) = self.visibility.get_mut(entity.index_unchecked()) {
+        let key = self.key(entity);
+        if let Some(visibility) = self.visibility.get_mut(key) {
             *visibility = val;
         }
     }
 
     pub fn get_hoverability(&self, entity: Entity) -> bool {
         self.hoverability
-            .get(entity.index_unchecked())
+            .get(self.key(entity))
             .cloned()
-            .unwrap()
+            .unwrap_or_default()
     }
 
     pub fn set_hoverability(&mut self, entity: Entity, val: bool) {
-        if let Some(hoverability) = self.hoverability.get_mut(entity.index_unchecked()) {
+        let key = self.key(entity);
+        if let Some(hoverability) = self.hoverability.get_mut(key) {
             *hoverability = val;
         }
     }
 
     pub fn set_opacity(&mut self, entity: Entity, val: f32) {
-        if let Some(opacity) = self.opacity.get_mut(entity.index_unchecked()) {
+        let key = self.key(entity);
+        if let Some(opacity) = self.opacity.get_mut(key) {
             *opacity = val;
         }
     }
@@ -0,0 +1,337 @@
+use crate::entity::Entity;
+use crate::events::{Event, Message, Propagation};
+use crate::state::mouse::{MouseButton, MouseButtonState};
+use crate::state::style::{Length, Position};
+use crate::state::State;
+use crate::PropSet;
+
+use std::fmt;
+
+/// The data of a drag, sent with each `DragEvent`
+#[derive(Clone)]
+pub struct DragData {
+    /// The entity which started the drag
+    pub source: Entity,
+    /// The position of the cursor
+    pub x: f32,
+    pub y: f32,
+    payload: Box<dyn Message>,
+}
+
+impl DragData {
+    /// Returns the payload of the drag if it is of the specified type
+    pub fn payload<M: Message>(&self) -> Option<&M> {
+        self.payload.as_any().downcast_ref::<M>()
+    }
+
+    /// Returns true if the payload of the drag is of the specified type
+    pub fn has_payload<M: Message>(&self) -> bool {
+        self.payload.is::<M>()
+    }
+}
+
+impl fmt::Debug for DragData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DragData")
+            .field("source", &self.source)
+            .field("x", &self.x)
+            .field("y", &self.y)
+            .field("payload", &self.payload)
+            .finish()
+    }
+}
+
+impl PartialEq for DragData {
+    fn eq(&self, other: &DragData) -> bool {
+        self.source == other.source
+            && self.x == other.x
+            && self.y == other.y
+            && self.payload.equals_a(&*other.payload)
+    }
+}
+
+/// Events sent during a drag started with `State::start_drag`
+///
+/// The entity under the cursor receives `DragEnter` when the drag moves onto it, `DragOver` as
+/// the cursor moves over it, `DragLeave` when the drag moves off it and `Drop` when the drag is
+/// released over it. These events propagate up the hierarchy, so a container can handle drops
+/// onto its children. The source receives `DragEnd` when the drag finishes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DragEvent {
+    DragEnter(DragData),
+    DragOver(DragData),
+    DragLeave(DragData),
+    Drop(DragData),
+    // Sent directly to the source with the entity the drag was dropped on, or null if cancelled
+    DragEnd(Entity),
+}
+
+// The state of the drag in progress
+pub(crate) struct Drag {
+    source: Entity,
+    payload: Box<dyn Message>,
+    // The mouse button held when the drag started, whose release drops it
+    button: Option<MouseButton>,
+    // The entity currently under the drag
+    over: Entity,
+    preview: Entity,
+    // The offset of the preview from the cursor
+    preview_offset: (f32, f32),
+}
+
+impl State {
+    /// Starts dragging a payload from the source entity
+    ///
+    /// The source captures the mouse until the button held when the drag started is released,
+    /// which sends a `DragEvent::Drop` to the entity under the cursor, or the drag is cancelled
+    /// with the escape key or `cancel_drag`. Any drag already in progress is cancelled first.
+    /// Targets receive the payload with each `DragEvent` and can check its type to decide
+    /// whether to accept it.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // In the event handler of a list item
+    /// if let Some(WindowEvent::MouseDown(MouseButton::Left)) = event.message.downcast() {
+    ///     state.start_drag(entity, ListItem(self.index));
+    /// }
+    ///
+    /// // In the event handler of the list
+    /// if let Some(DragEvent::Drop(data)) = event.message.downcast() {
+    ///     if let Some(ListItem(index)) = data.payload::<ListItem>() {
+    ///         self.move_item(*index, event.target);
+    ///     }
+    /// }
+    /// ```
+    pub fn start_drag<M: Message>(&mut self, source: Entity, payload: M) {
+        self.cancel_drag();
+
+        if !self.is_alive(source) {
+            return;
+        }
+
+        self.capture(source);
+
+        let button = if self.mouse.left.state == MouseButtonState::Pressed {
+            Some(MouseButton::Left)
+        } else if self.mouse.right.state == MouseButtonState::Pressed {
+            Some(MouseButton::Right)
+        } else if self.mouse.middle.state == MouseButtonState::Pressed {
+            Some(MouseButton::Middle)
+        } else {
+            None
+        };
+
+        self.drag = Some(Drag {
+            source,
+            payload: Box::new(payload),
+            button,
+            over: Entity::null(),
+            preview: Entity::null(),
+            preview_offset: (0.0, 0.0),
+        });
+
+        self.update_drag();
+    }
+
+    /// Sets an entity to be drawn under the cursor for the drag in progress
+    ///
+    /// The preview starts over the source and keeps its offset from the cursor, so it appears to
+    /// be picked up from where the drag started. It is made non-hoverable so that it does not
+    /// hide the drop targets and is removed when the drag ends. It should be a child of the root
+    /// so that it is not clipped.
+    pub fn set_drag_preview(&mut self, preview: Entity) {
+        let source = match self.drag.as_ref() {
+            Some(drag) => drag.source,
+            None => return,
+        };
+
+        let offset = (
+            self.data.get_posx(source) - self.mouse.cursorx,
+            self.data.get_posy(source) - self.mouse.cursory,
+        );
+
+        let old_preview = match self.drag.as_mut() {
+            Some(drag) => {
+                drag.preview_offset = offset;
+                std::mem::replace(&mut drag.preview, preview)
+            }
+            None => return,
+        };
+
+        if old_preview != preview {
+            self.remove(old_preview);
+        }
+
+        self.data.set_hoverability(preview, false);
+        preview
+            .set_position(self, Position::Absolute)
+            .set_z_order(self, 100);
+        self.position_drag_preview();
+    }
+
+    /// Returns true if a drag is in progress
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Returns the source of the drag in progress
+    pub fn drag_source(&self) -> Option<Entity> {
+        self.drag.as_ref().map(|drag| drag.source)
+    }
+
+    /// Returns the payload of the drag in progress if it is of the specified type
+    pub fn drag_payload<M: Message>(&self) -> Option<&M> {
+        self.drag
+            .as_ref()
+            .and_then(|drag| drag.payload.as_any().downcast_ref::<M>())
+    }
+
+    /// Cancels the drag in progress without dropping it
+    pub fn cancel_drag(&mut self) {
+        if let Some(drag) = self.drag.take() {
+            if drag.over != Entity::null() {
+                let data = self.drag_data(&drag);
+                self.insert_event(
+                    Event::new(DragEvent::DragLeave(data))
+                        .target(drag.over)
+                        .propagate(Propagation::Up),
+                );
+            }
+
+            self.finish_drag(drag, Entity::null());
+        }
+    }
+
+    // Sends enter, leave and over events as the cursor moves
+    pub(crate) fn update_drag(&mut self) {
+        let (over, data) = match self.drag.as_ref() {
+            Some(drag) => (drag.over, self.drag_data(drag)),
+            None => return,
+        };
+
+        let hovered = self.hovered;
+
+        if hovered != over {
+            if over != Entity::null() {
+                self.insert_event(
+                    Event::new(DragEvent::DragLeave(data.clone()))
+                        .target(over)
+                        .propagate(Propagation::Up),
+                );
+            }
+
+            self.insert_event(
+                Event::new(DragEvent::DragEnter(data.clone()))
+                    .target(hovered)
+                    .propagate(Propagation::Up),
+            );
+
+            if let Some(drag) = self.drag.as_mut() {
+                drag.over = hovered;
+            }
+        }
+
+        self.insert_event(
+            Event::new(DragEvent::DragOver(data))
+                .target(hovered)
+                .propagate(Propagation::Up),
+        );
+
+        self.position_drag_preview();
+    }
+
+    // Drops the drag in progress if the released button is the one which started it, or on the
+    // release of any button if the drag was started without a button held
+    pub(crate) fn release_drag(&mut self, button: MouseButton) {
+        let drops = match self.drag.as_ref() {
+            Some(drag) => drag.button.unwrap_or(button) == button,
+            None => false,
+        };
+
+        if drops {
+            self.end_drag();
+        }
+    }
+
+    // Drops the drag in progress on the entity under the cursor
+    fn end_drag(&mut self) {
+        if let Some(drag) = self.drag.take() {
+            let target = self.hovered;
+            let data = self.drag_data(&drag);
+            self.insert_event(
+                Event::new(DragEvent::Drop(data))
+                    .target(target)
+                    .propagate(Propagation::Up),
+            );
+
+            self.finish_drag(drag, target);
+        }
+    }
+
+    // Clears the references of the drag to removed entities and cancels it if its source is removed
+    pub(crate) fn remove_drag_references(&mut self, removed: &[Entity]) {
+        let source_removed = match self.drag.as_mut() {
+            Some(drag) => {
+                if removed.contains(&drag.over) {
+                    drag.over = Entity::null();
+                }
+
+                if removed.contains(&drag.preview) {
+                    drag.preview = Entity::null();
+                }
+
+                removed.contains(&drag.source)
+            }
+            None => false,
+        };
+
+        if source_removed {
+            if let Some(drag) = self.drag.take() {
+                self.remove(drag.preview);
+            }
+        }
+    }
+
+    fn finish_drag(&mut self, drag: Drag, target: Entity) {
+        self.release(drag.source);
+        self.remove(drag.preview);
+
+        self.insert_event(
+            Event::new(DragEvent::DragEnd(target))
+                .target(drag.source)
+                .propagate(Propagation::Direct),
+        );
+    }
+
+    fn drag_data(&self, drag: &Drag) -> DragData {
+        DragData {
+            source: drag.source,
+            x: self.mouse.cursorx,
+            y: self.mouse.cursory,
+            payload: drag.payload.clone(),
+        }
+    }
+
+    fn position_drag_preview(&mut self) {
+        let (preview, offset) = match self.drag.as_ref() {
+            Some(drag) if drag.preview != Entity::null() => (drag.preview, drag.preview_offset),
+            _ => return,
+        };
+
+        let (parent_posx, parent_posy) = match self.hierarchy.get_parent(preview) {
+            Some(parent) => (self.data.get_posx(parent), self.data.get_posy(parent)),
+            None => (0.0, 0.0),
+        };
+
+        preview
+            .set_left(
+                self,
+                Length::Pixels(self.mouse.cursorx + offset.0 - parent_posx),
+            )
+            .set_top(
+                self,
+                Length::Pixels(self.mouse.cursory + offset.1 - parent_posy),
+            );
+    }
+}
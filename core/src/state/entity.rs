@@ -1,12 +1,19 @@
 use std::cmp::{Eq, PartialEq};
+use std::collections::VecDeque;
 use std::hash::Hash;
 
 // An entity is an id used to reference data in external storages.
 // Rather than having widgets own their data, all state is stored in a single database and
 // is stored and loaded using the entities.
 
+// An entity is made up of an index, used to look up the data, and a generation. When an entity
+// is destroyed its index is recycled for a new entity with the next generation, so that any stale
+// copies of the old entity can be detected.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub struct Entity(u32);
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
 
 impl Default for Entity {
     fn default() -> Self {
@@ -16,49 +23,59 @@ impl Default for Entity {
 
 impl std::fmt::Display for Entity {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{}", self.index_unchecked())
+        if self.generation == 0 {
+            write!(f, "{}", self.index)
+        } else {
+            write!(f, "{}v{}", self.index, self.generation)
+        }
     }
 }
 
 impl Entity {
     pub fn null() -> Entity {
-        Entity(std::u32::MAX)
+        Entity {
+            index: std::u32::MAX,
+            generation: 0,
+        }
     }
 
     pub fn default() -> Entity {
-        Entity(std::u32::MAX)
+        Entity::null()
     }
 
-    pub fn new(index: u32) -> Entity {
-        Entity(index)
+    pub fn new(index: u32, generation: u32) -> Entity {
+        Entity { index, generation }
     }
 
     pub fn is_null(&self) -> bool {
-        if self.0 == std::u32::MAX {
+        if self.index == std::u32::MAX {
             true
         } else {
             false
         }
     }
 
-    // pub fn index(&self) -> usize {
-    //     return self.0 as usize;
-    // }
-
     pub fn index(&self) -> Option<usize> {
-        if self.0 < std::u32::MAX - 1 {
-            Some(self.0 as usize)
+        if self.index < std::u32::MAX - 1 {
+            Some(self.index as usize)
         } else {
             None
         }
     }
 
     pub(crate) fn index_unchecked(&self) -> usize {
-        self.0 as usize
+        self.index as usize
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
     }
 
     pub fn root() -> Entity {
-        Entity(0)
+        Entity {
+            index: 0,
+            generation: 0,
+        }
     }
 }
 
@@ -75,31 +92,152 @@ impl std::ops::Not for Entity {
 //     }
 // }
 
+// Returns true if a generation of an index is older than another generation of the same index.
+// Generations wrap around, so they are compared by their difference rather than their value.
+pub(crate) fn is_older_generation(generation: u32, other: u32) -> bool {
+    (generation.wrapping_sub(other) as i32) < 0
+}
+
+// The number of destroyed entities to keep before their indices are recycled.
+// Delaying reuse means that a stale entity is unlikely to share an index with a live one.
+const MINIMUM_FREE_INDICES: usize = 64;
+
 #[derive(Clone)]
 pub(crate) struct EntityManager {
-
-    count: usize,
+    // The current generation of each index
+    generations: Vec<u32>,
+    // Indices of destroyed entities which can be reused
+    free_indices: VecDeque<u32>,
 }
 
 impl EntityManager {
     pub(crate) fn new() -> EntityManager {
         EntityManager {
-            count: 0,
+            generations: Vec::new(),
+            free_indices: VecDeque::new(),
         }
     }
 
     pub(crate) fn create_entity(&mut self) -> Option<Entity> {
-        let index = self.count as u32;
-        self.count += 1;
-        
-        return Some(Entity::new(index));
-    }
-
-    // Destroy an entity.
-    // pub(crate) fn destroy_entity(&mut self, entity: Entity) {
-    //     if let Some(index) = entity.index() {
-    //         //self.count -= 1;
-    //         self.free_indices.push_back(index as u32);
-    //     }
-    // }
+        if self.free_indices.len() > MINIMUM_FREE_INDICES {
+            let index = self.free_indices.pop_front()?;
+            return Some(Entity::new(index, self.generations[index as usize]));
+        }
+
+        let index = self.generations.len() as u32;
+        // The last two indices are reserved for null entities
+        if index >= std::u32::MAX - 1 {
+            return None;
+        }
+
+        self.generations.push(0);
+
+        Some(Entity::new(index, 0))
+    }
+
+    // Destroys an entity, allowing its index to be reused by a later entity.
+    // Returns false if the entity was not alive.
+    pub(crate) fn destroy_entity(&mut self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+
+        let index = entity.index_unchecked();
+        self.generations[index] = self.generations[index].wrapping_add(1);
+        self.free_indices.push_back(index as u32);
+
+        true
+    }
+
+    // Returns true if the entity has been created and not destroyed
+    pub(crate) fn is_alive(&self, entity: Entity) -> bool {
+        match entity.index() {
+            Some(index) => self.generations.get(index) == Some(&entity.generation()),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::State;
+
+    #[test]
+    fn indices_are_reused_after_the_free_threshold() {
+        let mut entities = EntityManager::new();
+        let created = (0..MINIMUM_FREE_INDICES + 1)
+            .map(|_| entities.create_entity().unwrap())
+            .collect::<Vec<_>>();
+
+        for entity in created.iter().take(MINIMUM_FREE_INDICES) {
+            assert!(entities.destroy_entity(*entity));
+        }
+
+        // Up to the threshold destroyed indices are kept free
+        let fresh = entities.create_entity().unwrap();
+        assert_eq!(fresh, Entity::new(MINIMUM_FREE_INDICES as u32 + 1, 0));
+
+        // Past it the oldest free index is reused with the next generation
+        assert!(entities.destroy_entity(created[MINIMUM_FREE_INDICES]));
+        let reused = entities.create_entity().unwrap();
+        assert_eq!(reused, Entity::new(0, 1));
+
+        assert!(entities.is_alive(reused));
+        assert!(!entities.is_alive(created[0]));
+        assert!(!entities.destroy_entity(created[0]));
+    }
+
+    #[test]
+    fn generations_wrap_around() {
+        let mut entities = EntityManager::new();
+        let entity = entities.create_entity().unwrap();
+        entities.generations[0] = u32::MAX;
+        let entity = Entity::new(entity.index_unchecked() as u32, u32::MAX);
+
+        assert!(entities.destroy_entity(entity));
+        assert!(entities.is_alive(Entity::new(0, 0)));
+        assert!(!entities.is_alive(entity));
+
+        assert!(is_older_generation(u32::MAX, 0));
+        assert!(!is_older_generation(0, u32::MAX));
+        assert!(is_older_generation(1, 2));
+        assert!(!is_older_generation(2, 2));
+    }
+
+    #[test]
+    fn stale_entities_are_not_alive_in_state() {
+        let mut state = State::new();
+        state.hierarchy.add(Entity::root(), None);
+
+        let removed = state.add(Entity::root());
+        state.data.set_posx(removed, 10.0);
+        assert!(state.is_alive(removed));
+
+        state.remove(removed);
+        assert!(!state.is_alive(removed));
+        assert!(!state.hierarchy.contains(removed));
+
+        // Remove enough entities for the index of the first one to be reused
+        for _ in 0..MINIMUM_FREE_INDICES {
+            let entity = state.add(Entity::root());
+            state.remove(entity);
+        }
+
+        let reused = state.add(Entity::root());
+        assert_eq!(reused.index(), removed.index());
+        assert!(state.is_alive(reused));
+        assert!(!state.is_alive(removed));
+
+        // The stale copy neither reads nor writes the data of the new entity
+        state.data.set_posx(reused, 20.0);
+        state.data.set_posx(removed, 30.0);
+        assert_eq!(state.data.get_posx(removed), 0.0);
+        assert_eq!(state.data.get_posx(reused), 20.0);
+
+        // Removing the stale copy does not remove the new entity
+        state.remove(removed);
+        assert!(state.is_alive(reused));
+        assert_eq!(state.hierarchy.get_parent(reused), Some(Entity::root()));
+    }
 }
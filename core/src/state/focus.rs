@@ -0,0 +1,243 @@
+use crate::entity::Entity;
+use crate::events::{Event, Propagation};
+use crate::state::hierarchy::{HierarchyTree, IntoBranchIterator};
+use crate::state::style::Visibility;
+use crate::state::State;
+use crate::window_event::WindowEvent;
+use crate::PropSet;
+
+// A focus scope and the entity which had focus when it was pushed
+#[derive(Clone, Copy)]
+pub(crate) struct FocusScope {
+    entity: Entity,
+    restore: Entity,
+}
+
+impl State {
+    /// Moves the keyboard focus to the entity
+    ///
+    /// The entity losing focus receives `WindowEvent::FocusOut` and the entity gaining focus
+    /// receives `WindowEvent::FocusIn`. While a focus scope is active the focus cannot be moved
+    /// outside of it, except to the root to clear the focus. Returns false if the focus could not
+    /// be moved.
+    pub fn focus(&mut self, entity: Entity) -> bool {
+        if entity == self.focused {
+            return true;
+        }
+
+        if !self.is_alive(entity) {
+            return false;
+        }
+
+        if let Some(scope) = self.focus_scope() {
+            if entity != Entity::root() && !is_in_scope(self, entity, scope) {
+                return false;
+            }
+        }
+
+        let old_focus = self.focused;
+        old_focus.set_focus(self, false);
+        self.focused = entity;
+        entity.set_focus(self, true);
+
+        self.insert_event(
+            Event::new(WindowEvent::FocusOut)
+                .target(old_focus)
+                .propagate(Propagation::Direct),
+        );
+        self.insert_event(
+            Event::new(WindowEvent::FocusIn)
+                .target(entity)
+                .propagate(Propagation::Direct),
+        );
+
+        true
+    }
+
+    /// Returns the entities which the tab key moves the focus between, in the order of the moves
+    ///
+    /// An entity is a tab stop if it has a `tab-index` of zero or more and is visible and not
+    /// disabled. Entities with a positive `tab-index` come first in ascending order, followed by
+    /// those with a `tab-index` of zero in hierarchy order. Only the entities within the active
+    /// focus scope are included.
+    pub fn tab_order(&self) -> Vec<Entity> {
+        let scope = self.focus_scope().unwrap_or(Entity::root());
+
+        let mut tab_stops = scope
+            .branch_iter(&self.hierarchy)
+            .filter_map(|entity| {
+                let tab_index = self.style.tab_index.get(entity).cloned()?;
+
+                if tab_index < 0 || self.data.get_visibility(entity) == Visibility::Invisible {
+                    return None;
+                }
+
+                let disabled = self
+                    .style
+                    .pseudo_classes
+                    .get(entity)
+                    .cloned()
+                    .map_or(false, |mut pseudo_classes| pseudo_classes.get_disabled());
+
+                if disabled {
+                    return None;
+                }
+
+                Some((tab_index, entity))
+            })
+            .collect::<Vec<_>>();
+
+        // The sort is stable so entities with the same tab index keep their hierarchy order
+        tab_stops.sort_by_key(|(tab_index, _)| match *tab_index {
+            0 => i32::MAX,
+            tab_index => tab_index,
+        });
+
+        tab_stops.into_iter().map(|(_, entity)| entity).collect()
+    }
+
+    /// Moves the focus to the next tab stop, wrapping around to the first
+    ///
+    /// A next focus set with `set_next_focus` takes precedence over the tab order.
+    pub fn focus_next(&mut self) -> bool {
+        let next = self
+            .style
+            .focus_order
+            .get(self.focused)
+            .map(|focus_order| focus_order.next)
+            .unwrap_or(Entity::null());
+
+        if next != Entity::null() {
+            return self.focus(next);
+        }
+
+        let tab_order = self.tab_order();
+        let next = match tab_order.iter().position(|entity| *entity == self.focused) {
+            Some(index) => tab_order.get((index + 1) % tab_order.len()),
+            None => tab_order.first(),
+        };
+
+        match next {
+            Some(next) => self.focus(*next),
+            None => false,
+        }
+    }
+
+    /// Moves the focus to the previous tab stop, wrapping around to the last
+    ///
+    /// A previous focus set with `set_prev_focus` takes precedence over the tab order.
+    pub fn focus_prev(&mut self) -> bool {
+        let prev = self
+            .style
+            .focus_order
+            .get(self.focused)
+            .map(|focus_order| focus_order.prev)
+            .unwrap_or(Entity::null());
+
+        if prev != Entity::null() {
+            return self.focus(prev);
+        }
+
+        let tab_order = self.tab_order();
+        let prev = match tab_order.iter().position(|entity| *entity == self.focused) {
+            Some(index) => tab_order.get((index + tab_order.len() - 1) % tab_order.len()),
+            None => tab_order.last(),
+        };
+
+        match prev {
+            Some(prev) => self.focus(*prev),
+            None => false,
+        }
+    }
+
+    /// Traps the focus within an entity and its descendants, such as a dialog or popup
+    ///
+    /// If the focus is outside of the scope it moves to the first tab stop within the scope, or
+    /// to the scope entity if there are none. Scopes can be nested, in which case the most
+    /// recently pushed scope is active. When the scope is popped, or its entity removed, the
+    /// focus is restored to the entity which had it when the scope was pushed.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // When the dialog opens
+    /// state.push_focus_scope(dialog);
+    ///
+    /// // When the dialog closes
+    /// state.pop_focus_scope(dialog);
+    /// ```
+    pub fn push_focus_scope(&mut self, entity: Entity) {
+        if !self.is_alive(entity) {
+            return;
+        }
+
+        let restore = self.focused;
+        self.focus_scopes.retain(|scope| scope.entity != entity);
+        self.focus_scopes.push(FocusScope { entity, restore });
+
+        if !is_in_scope(self, self.focused, entity) {
+            let first = self.tab_order().first().cloned().unwrap_or(entity);
+            self.focus(first);
+        }
+    }
+
+    /// Removes a focus scope, restoring the focus if it was the active scope
+    pub fn pop_focus_scope(&mut self, entity: Entity) {
+        let index = match self
+            .focus_scopes
+            .iter()
+            .position(|scope| scope.entity == entity)
+        {
+            Some(index) => index,
+            None => return,
+        };
+
+        let scope = self.focus_scopes.remove(index);
+
+        // A later scope which would restore the focus into this scope restores it to where this
+        // scope would have instead
+        if let Some(next_scope) = self.focus_scopes.get(index).cloned() {
+            if is_in_scope(self, next_scope.restore, entity) {
+                self.focus_scopes[index].restore = scope.restore;
+            }
+
+            return;
+        }
+
+        let mut restore = scope.restore;
+        if !self.is_alive(restore) {
+            restore = Entity::root();
+        }
+
+        if let Some(active_scope) = self.focus_scope() {
+            if !is_in_scope(self, restore, active_scope) {
+                restore = self.tab_order().first().cloned().unwrap_or(active_scope);
+            }
+        }
+
+        self.focus(restore);
+    }
+
+    /// Returns the entity of the active focus scope
+    pub fn focus_scope(&self) -> Option<Entity> {
+        self.focus_scopes.last().map(|scope| scope.entity)
+    }
+
+    // Pops the focus scopes of removed entities
+    pub(crate) fn remove_focus_references(&mut self, removed: &[Entity]) {
+        let removed_scopes = self
+            .focus_scopes
+            .iter()
+            .filter(|scope| removed.contains(&scope.entity))
+            .map(|scope| scope.entity)
+            .collect::<Vec<_>>();
+
+        for scope in removed_scopes.into_iter().rev() {
+            self.pop_focus_scope(scope);
+        }
+    }
+}
+
+fn is_in_scope(state: &State, entity: Entity, scope: Entity) -> bool {
+    entity == scope || entity.is_descendant_of(&state.hierarchy, scope)
+}
@@ -13,6 +13,8 @@ pub struct Hierarchy {
     pub first_child: Vec<Option<Entity>>,
     pub next_sibling: Vec<Option<Entity>>,
     pub prev_sibling: Vec<Option<Entity>>,
+    // The generation of the entity at each index, or None if the index is not in the hierarchy
+    pub generations: Vec<Option<u32>>,
 }
 
 impl Hierarchy {
@@ -23,9 +25,28 @@ impl Hierarchy {
             first_child: Vec::new(),
             next_sibling: Vec::new(),
             prev_sibling: Vec::new(),
+            generations: Vec::new(),
         }
     }
 
+    // Returns the index of the entity if it is in the hierarchy
+    fn live_index(&self, entity: Entity) -> Option<usize> {
+        let index = entity.index()?;
+        if self.generations.get(index) == Some(&Some(entity.generation())) {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if the entity is in the hierarchy
+    ///
+    /// Returns false for entities which have been removed, including stale entities whose index
+    /// has been reused by a newer entity.
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.live_index(entity).is_some()
+    }
+
     pub fn root(&self) -> Option<Entity> {
         return *self.parent.first().unwrap();
     }
@@ -33,7 +54,7 @@ impl Hierarchy {
     /// Returns the last child of an entity
     pub fn get_last_child(&self, entity: Entity) -> Option<Entity> {
         //check if entity exists
-        if let Some(index) = entity.index() {
+        if let Some(index) = self.live_index(entity) {
             let mut f = self.first_child[index];
             let mut r = None;
             while f != None {
@@ -49,7 +70,7 @@ impl Hierarchy {
 
     /// Returns the nth child of an entity
     pub fn get_child(&self, entity: Entity, n: usize) -> Option<Entity> {
-        if let Some(index) = entity.index() {
+        if let Some(index) = self.live_index(entity) {
             let mut f = self.first_child[index];
             let mut i = 0;
             while f != None {
@@ -68,7 +89,7 @@ impl Hierarchy {
 
     /// Returns the number of children of an entity
     pub fn get_num_children(&self, entity: Entity) -> Option<u32> {
-        if let Some(index) = entity.index() {
+        if let Some(index) = self.live_index(entity) {
             let mut f = self.first_child[index];
             let mut r = 0;
             while f != None {
@@ -84,12 +105,8 @@ impl Hierarchy {
 
     /// Returns the parent of an entity
     pub fn get_parent(&self, entity: Entity) -> Option<Entity> {
-        if let Some(index) = entity.index() {
-            if index >= self.parent.len() {
-                None
-            } else {
-                self.parent[index]
-            }
+        if let Some(index) = self.live_index(entity) {
+            self.parent[index]
         } else {
             None
         }
@@ -97,7 +114,7 @@ impl Hierarchy {
 
     /// Returns the first child of an entity
     pub fn get_first_child(&self, entity: Entity) -> Option<Entity> {
-        if let Some(index) = entity.index() {
+        if let Some(index) = self.live_index(entity) {
             self.first_child[index]
         } else {
             None
@@ -106,7 +123,7 @@ impl Hierarchy {
 
     /// Returns the next sibling of an entity
     pub fn get_next_sibling(&self, entity: Entity) -> Option<Entity> {
-        if let Some(index) = entity.index() {
+        if let Some(index) = self.live_index(entity) {
             self.next_sibling[index]
         } else {
             None
@@ -115,7 +132,7 @@ impl Hierarchy {
 
     /// Returns the previous sibling of an entity
     pub fn get_prev_sibling(&self, entity: Entity) -> Option<Entity> {
-        if let Some(index) = entity.index() {
+        if let Some(index) = self.live_index(entity) {
             self.prev_sibling[index]
         } else {
             None
@@ -148,12 +165,26 @@ impl Hierarchy {
         false
     }
 
-    // Not decided yet how this should work
-    pub fn remove_children(&mut self, _entity: Entity) {}
+    /// Removes all of the descendants of an entity from the hierarchy
+    pub fn remove_children(&mut self, entity: Entity) {
+        if !self.contains(entity) {
+            return;
+        }
+
+        let descendants = entity
+            .branch_iter(self)
+            .skip(1)
+            .collect::<Vec<_>>();
+
+        // Remove the deepest entities first so that each entity is a leaf when it is removed
+        for descendant in descendants.into_iter().rev() {
+            self.remove(descendant);
+        }
+    }
 
     /// Returns true if the entity has children
     pub fn has_children(&self, entity: Entity) -> bool {
-        if let Some(index) = entity.index() {
+        if let Some(index) = self.live_index(entity) {
             self.first_child[index].is_some()
         } else {
             false
@@ -166,7 +197,7 @@ impl Hierarchy {
         //     self.recursive_remove(first_child);
         // }
 
-        if let Some(index) = entity.index() {
+        if let Some(index) = self.live_index(entity) {
             if let Some((index, _)) = self.entities.iter().enumerate().find(|(_, &e)| e == entity) {
                 self.entities.remove(index);
             }
@@ -185,15 +216,17 @@ impl Hierarchy {
                 self.prev_sibling[next_sibling.index_unchecked()] = self.get_prev_sibling(entity);
             }
 
+            self.first_child[index] = None;
             self.next_sibling[index] = None;
             self.prev_sibling[index] = None;
             self.parent[index] = None;
+            self.generations[index] = None;
         }
     }
 
     // Makes the entity the first child of its parent
     pub fn set_first_child(&mut self, entity: Entity) -> Result<(), HierarchyError> {
-        if let Some(index) = entity.index() {
+        if let Some(index) = self.live_index(entity) {
             // Check is sibline exists in the hierarchy
             if index >= self.parent.len() {
                 return Err(HierarchyError {});
@@ -241,12 +274,12 @@ impl Hierarchy {
         entity: Entity,
         sibling: Entity,
     ) -> Result<(), HierarchyError> {
-        if self.next_sibling[entity.index_unchecked()] == Some(sibling) {
+        // Check the entity and sibling exist in the hierarchy
+        if !self.contains(entity) || !self.contains(sibling) {
             return Err(HierarchyError {});
         }
 
-        // Check is sibline exists in the hierarchy
-        if sibling.index_unchecked() >= self.parent.len() {
+        if self.next_sibling[entity.index_unchecked()] == Some(sibling) {
             return Err(HierarchyError {});
         }
 
@@ -304,12 +337,12 @@ impl Hierarchy {
         entity: Entity,
         sibling: Entity,
     ) -> Result<(), HierarchyError> {
-        if self.prev_sibling[entity.index_unchecked()] == Some(sibling) {
+        // Check the entity and sibling exist in the hierarchy
+        if !self.contains(entity) || !self.contains(sibling) {
             return Err(HierarchyError {});
         }
 
-        // Check is sibline exists in the hierarchy
-        if sibling.index_unchecked() >= self.parent.len() {
+        if self.prev_sibling[entity.index_unchecked()] == Some(sibling) {
             return Err(HierarchyError {});
         }
 
@@ -361,6 +394,10 @@ impl Hierarchy {
     }
 
     pub fn set_parent(&mut self, entity: Entity, parent: Entity) {
+        if !self.contains(entity) || !self.contains(parent) {
+            return;
+        }
+
         if let Some(old_parent) = self.get_parent(entity) {
             if self.is_first_child(entity) {
                 self.first_child[old_parent.index_unchecked()] = self.get_next_sibling(entity);
@@ -404,8 +441,10 @@ impl Hierarchy {
                 self.first_child.resize(index + 1, None);
                 self.next_sibling.resize(index + 1, None);
                 self.prev_sibling.resize(index + 1, None);
+                self.generations.resize(index + 1, None);
             }
 
+            self.generations[index] = Some(entity.generation());
             self.parent[index] = parent;
             self.first_child[index] = None;
             self.next_sibling[index] = None;
@@ -444,8 +483,11 @@ impl Hierarchy {
                     self.first_child.resize(index + 1, None);
                     self.next_sibling.resize(index + 1, None);
                     self.prev_sibling.resize(index + 1, None);
+                    self.generations.resize(index + 1, None);
                 }
 
+                self.generations[index] = Some(entity.generation());
+
                 if let Some(next_sib) = self.get_next_sibling(sibling) {
                     self.prev_sibling[next_sib.index_unchecked()] = Some(entity);
                 }
@@ -750,3 +792,53 @@ impl<'a> HierarchyTree<'a> for Entity {
     // }
     //}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A root with two children, where the first child has a child
+    fn tree() -> (Hierarchy, Entity, Entity, Entity) {
+        let mut hierarchy = Hierarchy::new();
+        let (a, b, c) = (Entity::new(1, 0), Entity::new(2, 0), Entity::new(3, 0));
+        hierarchy.add(Entity::root(), None);
+        hierarchy.add(a, Some(Entity::root()));
+        hierarchy.add(b, Some(Entity::root()));
+        hierarchy.add(c, Some(a));
+
+        (hierarchy, a, b, c)
+    }
+
+    #[test]
+    fn removed_entity_is_unlinked() {
+        let (mut hierarchy, a, b, c) = tree();
+
+        hierarchy.remove(c);
+        hierarchy.remove(a);
+
+        assert!(!hierarchy.contains(a));
+        assert_eq!(hierarchy.get_parent(a), None);
+        assert_eq!(hierarchy.get_first_child(Entity::root()), Some(b));
+        assert_eq!(hierarchy.get_prev_sibling(b), None);
+        assert_eq!(hierarchy.get_num_children(Entity::root()), Some(1));
+    }
+
+    #[test]
+    fn stale_entity_does_not_reach_reused_index() {
+        let (mut hierarchy, a, b, c) = tree();
+
+        hierarchy.remove(c);
+        let reused = Entity::new(c.index_unchecked() as u32, 1);
+        hierarchy.add(reused, Some(b));
+
+        assert!(hierarchy.contains(reused));
+        assert!(!hierarchy.contains(c));
+        assert_eq!(hierarchy.get_parent(c), None);
+        assert_eq!(hierarchy.get_parent(reused), Some(b));
+        assert!(!hierarchy.has_children(a));
+
+        // Removing the stale copy leaves the new entity in place
+        hierarchy.remove(c);
+        assert_eq!(hierarchy.get_first_child(b), Some(reused));
+    }
+}
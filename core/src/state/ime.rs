@@ -0,0 +1,52 @@
+use crate::entity::Entity;
+use crate::state::State;
+
+/// The area of a text caret in window coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CaretRect {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+impl CaretRect {
+    pub fn new(x: f32, y: f32, w: f32, h: f32) -> Self {
+        CaretRect { x, y, w, h }
+    }
+}
+
+impl State {
+    /// Reports the area of the text caret of an entity, used to position the candidate window of
+    /// an input method
+    ///
+    /// Text input widgets should call this whenever the caret moves, typically when they are
+    /// drawn. The area is only used while the entity has keyboard focus.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // In on_draw, after measuring the text before the cursor
+    /// state.set_caret_rect(entity, CaretRect::new(caretx, posy, 1.0, font_size));
+    /// ```
+    pub fn set_caret_rect(&mut self, entity: Entity, rect: CaretRect) {
+        self.caret = Some((entity, rect));
+    }
+
+    /// Clears the caret area reported by an entity, such as when it stops editing
+    pub fn clear_caret_rect(&mut self, entity: Entity) {
+        if self.caret.map_or(false, |(caret_entity, _)| caret_entity == entity) {
+            self.caret = None;
+        }
+    }
+
+    /// Returns the area of the text caret of the focused entity
+    ///
+    /// The backends use this after drawing to position the candidate window of the input method.
+    pub fn caret_rect(&self) -> Option<CaretRect> {
+        match self.caret {
+            Some((entity, rect)) if entity == self.focused => Some(rect),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use crate::entity::Entity;
+use crate::events::{Event, Propagation};
+use crate::state::mouse::{MouseButton, MouseButtonState};
+use crate::state::style::Length;
+use crate::state::State;
+use crate::systems::apply_hover;
+use crate::window_event::WindowEvent;
+
+use keyboard_types::Code;
+
+impl State {
+    /// Applies an input from the window to the state
+    ///
+    /// The backends translate the events of the windowing library into a `WindowEvent` and pass
+    /// it here, which updates the mouse, keyboard, hover and focus state and sends the event to
+    /// the hovered, captured or focused entity. The inputs are `MouseMove`, `MouseDown`,
+    /// `MouseUp`, `MouseScroll`, `KeyDown`, `KeyUp`, `CharInput`, `ImePreedit`, `ImeCommit` and
+    /// `WindowResize`, and any other event is ignored.
+    ///
+    /// When recording is active the input is also added to the recording, so that it can be
+    /// replayed later through this same function.
+    pub fn dispatch_input(&mut self, input: WindowEvent) {
+        match input {
+            WindowEvent::MouseMove(cursorx, cursory) => self.mouse_move(cursorx, cursory),
+            WindowEvent::MouseDown(button) => self.mouse_down(button),
+            WindowEvent::MouseUp(button) => self.mouse_up(button),
+            WindowEvent::MouseScroll(x, y) => self.mouse_scroll(x, y),
+            WindowEvent::KeyDown(code, ref key) => self.key_down(code, key.clone()),
+            WindowEvent::KeyUp(code, ref key) => self.key_up(code, key.clone()),
+            WindowEvent::CharInput(character) => self.char_input(character),
+            WindowEvent::ImePreedit { .. } | WindowEvent::ImeCommit(_) => {
+                self.ime_input(input.clone())
+            }
+            WindowEvent::WindowResize(width, height) => self.window_resize(width, height),
+            _ => return,
+        }
+
+        if let Some(recorder) = self.input_recorder.as_mut() {
+            recorder.record(input);
+        }
+    }
+
+    fn mouse_move(&mut self, cursorx: f32, cursory: f32) {
+        self.mouse.cursorx = cursorx;
+        self.mouse.cursory = cursory;
+
+        apply_hover(self);
+
+        if self.captured != Entity::null() {
+            self.insert_event(
+                Event::new(WindowEvent::MouseMove(cursorx, cursory))
+                    .target(self.captured)
+                    .propagate(Propagation::Direct),
+            );
+        } else if self.hovered != Entity::root() {
+            self.insert_event(
+                Event::new(WindowEvent::MouseMove(cursorx, cursory)).target(self.hovered),
+            );
+        }
+
+        self.update_drag();
+    }
+
+    fn mouse_down(&mut self, button: MouseButton) {
+        self.set_mouse_button_state(button, MouseButtonState::Pressed);
+
+        if self.hovered != Entity::null() && self.active != self.hovered {
+            self.active = self.hovered;
+            self.insert_event(Event::new(WindowEvent::Restyle).target(Entity::root()));
+        }
+
+        if self.captured != Entity::null() {
+            self.insert_event(
+                Event::new(WindowEvent::MouseDown(button))
+                    .target(self.captured)
+                    .propagate(Propagation::Direct),
+            );
+        } else {
+            self.insert_event(Event::new(WindowEvent::MouseDown(button)).target(self.hovered));
+        }
+
+        let cursor = (self.mouse.cursorx, self.mouse.cursory);
+        match button {
+            MouseButton::Left => {
+                self.mouse.left.pos_down = cursor;
+                self.mouse.left.pressed = self.hovered;
+            }
+
+            MouseButton::Middle => {
+                self.mouse.middle.pos_down = cursor;
+                self.mouse.left.pressed = self.hovered;
+            }
+
+            MouseButton::Right => {
+                self.mouse.right.pos_down = cursor;
+                self.mouse.left.pressed = self.hovered;
+            }
+
+            _ => {}
+        }
+    }
+
+    fn mouse_up(&mut self, button: MouseButton) {
+        self.set_mouse_button_state(button, MouseButtonState::Released);
+
+        self.active = Entity::null();
+        self.insert_event(Event::new(WindowEvent::Restyle).target(Entity::root()));
+
+        if self.captured != Entity::null() {
+            self.insert_event(
+                Event::new(WindowEvent::MouseUp(button))
+                    .target(self.captured)
+                    .propagate(Propagation::Direct),
+            );
+        } else {
+            self.insert_event(Event::new(WindowEvent::MouseUp(button)).target(self.hovered));
+        }
+
+        // Releasing the button which started the drag drops it
+        self.release_drag(button);
+
+        let cursor = (self.mouse.cursorx, self.mouse.cursory);
+        match button {
+            MouseButton::Left => {
+                self.mouse.left.pos_up = cursor;
+                self.mouse.left.released = self.hovered;
+            }
+
+            MouseButton::Middle => {
+                self.mouse.middle.pos_up = cursor;
+                self.mouse.left.released = self.hovered;
+            }
+
+            MouseButton::Right => {
+                self.mouse.right.pos_up = cursor;
+                self.mouse.left.released = self.hovered;
+            }
+
+            _ => {}
+        }
+    }
+
+    fn mouse_scroll(&mut self, x: f32, y: f32) {
+        if self.captured != Entity::null() {
+            self.insert_event(
+                Event::new(WindowEvent::MouseScroll(x, y))
+                    .target(self.captured)
+                    .propagate(Propagation::Direct),
+            );
+        } else {
+            self.insert_event(Event::new(WindowEvent::MouseScroll(x, y)).target(self.hovered));
+        }
+    }
+
+    fn char_input(&mut self, input: char) {
+        self.insert_event(
+            Event::new(WindowEvent::CharInput(input))
+                .target(self.focused)
+                .propagate(Propagation::Down),
+        );
+    }
+
+    // Composition events of an input method go to the focused entity in the same way as typed
+    // characters
+    fn ime_input(&mut self, input: WindowEvent) {
+        self.insert_event(
+            Event::new(input)
+                .target(self.focused)
+                .propagate(Propagation::Down),
+        );
+    }
+
+    // Modifier keys update the modifiers state, shortcuts are triggered before the key reaches
+    // the focused entity, the tab key moves the keyboard focus and the escape key cancels a drag
+    fn key_down(&mut self, code: Code, key: Option<keyboard_types::Key>) {
+        self.set_modifier(code, true);
+
+        if self.trigger_shortcut(code) {
+            return;
+        }
+
+        if code == Code::Tab {
+            if self.modifiers.shift {
+                self.focus_prev();
+            } else {
+                self.focus_next();
+            }
+        }
+
+        if code == Code::Escape {
+            self.cancel_drag();
+        }
+
+        let target = if self.focused != Entity::null() {
+            self.focused
+        } else {
+            self.hovered
+        };
+
+        self.insert_event(
+            Event::new(WindowEvent::KeyDown(code, key))
+                .target(target)
+                .propagate(Propagation::DownUp),
+        );
+    }
+
+    fn key_up(&mut self, code: Code, key: Option<keyboard_types::Key>) {
+        self.set_modifier(code, false);
+
+        let target = if self.focused != Entity::null() {
+            self.focused
+        } else {
+            self.hovered
+        };
+
+        self.insert_event(
+            Event::new(WindowEvent::KeyUp(code, key))
+                .target(target)
+                .propagate(Propagation::DownUp),
+        );
+    }
+
+    fn window_resize(&mut self, width: f32, height: f32) {
+        self.style
+            .width
+            .insert(Entity::root(), Length::Pixels(width));
+        self.style
+            .height
+            .insert(Entity::root(), Length::Pixels(height));
+
+        self.data.set_width(Entity::root(), width);
+        self.data.set_height(Entity::root(), height);
+
+        self.insert_event(
+            Event::new(WindowEvent::Restyle)
+                .origin(Entity::root())
+                .target(Entity::root()),
+        );
+        self.insert_event(Event::new(WindowEvent::Relayout).target(Entity::root()));
+        self.insert_event(Event::new(WindowEvent::Redraw).target(Entity::root()));
+    }
+
+    fn set_mouse_button_state(&mut self, button: MouseButton, button_state: MouseButtonState) {
+        match button {
+            MouseButton::Left => {
+                self.mouse.left.state = button_state;
+            }
+
+            MouseButton::Right => {
+                self.mouse.right.state = button_state;
+            }
+
+            MouseButton::Middle => {
+                self.mouse.middle.state = button_state;
+            }
+
+            _ => {}
+        }
+    }
+
+    fn set_modifier(&mut self, code: Code, pressed: bool) {
+        match code {
+            Code::ShiftLeft | Code::ShiftRight => self.modifiers.shift = pressed,
+            Code::ControlLeft | Code::ControlRight => self.modifiers.ctrl = pressed,
+            Code::AltLeft | Code::AltRight => self.modifiers.alt = pressed,
+            Code::MetaLeft | Code::MetaRight => self.modifiers.logo = pressed,
+            _ => (),
+        }
+    }
+}
@@ -22,12 +22,43 @@ pub use mouse::*;
 pub mod resource;
 pub use resource::*;
 
-pub use crate::events::{Builder, Event, EventHandler, Propagation};
+pub mod timer;
+pub use timer::*;
+
+pub mod input;
+
+pub mod drag;
+pub use drag::*;
+
+pub mod shortcut;
+pub use shortcut::*;
+
+pub mod focus;
+use focus::FocusScope;
+
+pub mod clipboard;
+pub use clipboard::*;
+
+pub mod ime;
+pub use ime::*;
+
+pub mod cursor;
+
+pub mod window_command;
+
+pub use crate::events::{
+    Builder, Event, EventFilterHandle, EventFilterResult, EventHandler, EventProxy, EventTracer,
+    InputRecorder, InputRecording, ListenerContext, ListenerHandle, Message, Propagation,
+    ValueReceiver,
+};
+use crate::events::{EventFilters, EventPhase, EventProxyReceiver, Listeners};
 pub use crate::window_event::WindowEvent;
+use crate::window_event::{CursorIcon, WindowCommand};
 
-use femtovg::FontId;
+use crate::renderer::FontId;
 
 use std::collections::VecDeque;
+use std::time::{Duration, Instant};
 
 use fnv::FnvHashMap;
 
@@ -57,8 +88,23 @@ pub struct State {
     pub focused: Entity,
 
     pub event_handlers: FnvHashMap<Entity, Box<dyn EventHandler>>,
+    pub(crate) listeners: Listeners,
+    pub(crate) event_filters: EventFilters,
     pub(crate) removed_entities: Vec<Entity>,
     pub event_queue: VecDeque<Event>,
+    event_proxy_receiver: EventProxyReceiver,
+    value_receivers: Vec<ValueReceiver>,
+    input_recorder: Option<InputRecorder>,
+    pub(crate) event_tracer: Option<EventTracer>,
+    pub(crate) drag: Option<Drag>,
+    pub(crate) shortcuts: Shortcuts,
+    pub(crate) focus_scopes: Vec<FocusScope>,
+    pub(crate) clipboard: Box<dyn Clipboard>,
+    pub(crate) caret: Option<(Entity, CaretRect)>,
+    pub(crate) cursor: CursorIcon,
+    pub(crate) window_commands: Vec<WindowCommand>,
+
+    pub timers: Timers,
 
     pub fonts: Fonts, //TODO - Replace with resource manager
 
@@ -92,13 +138,27 @@ impl State {
             data,
             mouse,
             modifiers,
-            hovered: Entity::new(0),
+            hovered: Entity::root(),
             active: Entity::null(),
             captured: Entity::null(),
-            focused: Entity::new(0),
+            focused: Entity::root(),
             event_handlers: FnvHashMap::default(),
+            listeners: Listeners::new(),
+            event_filters: EventFilters::new(),
             event_queue: VecDeque::new(),
+            event_proxy_receiver: EventProxyReceiver::new(),
+            value_receivers: Vec::new(),
+            input_recorder: None,
+            event_tracer: None,
+            drag: None,
+            shortcuts: Shortcuts::new(),
+            focus_scopes: Vec::new(),
+            clipboard: Box::new(MemoryClipboard::new()),
+            caret: None,
+            cursor: CursorIcon::default(),
+            window_commands: Vec::new(),
             removed_entities: Vec::new(),
+            timers: Timers::new(),
             fonts: Fonts {
                 regular: None,
                 bold: None,
@@ -118,6 +178,152 @@ impl State {
         Builder::new(self, entity)
     }
 
+    /// Adds a listener which is called with messages of type `M` received by an entity
+    ///
+    /// Listeners are called after the event handler of the entity, whenever an event with a
+    /// message of type `M` reaches the entity while propagating, and an entity can have any
+    /// number of listeners. They are called even if the event handler of the entity consumed
+    /// the event. Listeners are removed when the entity is removed or with `remove_listener`.
+    ///
+    /// A listener is called once per event. For `Propagation::DownUp` and `Propagation::All`
+    /// events, which reach entities on the way down and again on the way up, listeners are only
+    /// called in the bubble phase. The context passed to the listener gives the phase and can
+    /// stop the propagation of the event.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let handle = state.on::<ButtonEvent, _>(panel, |state, panel, message, context| {
+    ///     if *message == ButtonEvent::Pressed {
+    ///         state.insert_event(Event::new(AppEvent::Save).target(panel));
+    ///         context.stop_propagation();
+    ///     }
+    /// });
+    ///
+    /// // Later
+    /// state.remove_listener(handle);
+    /// ```
+    pub fn on<M, F>(&mut self, entity: Entity, mut listener: F) -> ListenerHandle
+    where
+        M: Message,
+        F: FnMut(&mut State, Entity, &M, &mut ListenerContext),
+        F: 'static + Send,
+    {
+        self.listeners.add(
+            entity,
+            Box::new(move |state, entity, event| {
+                let bubbles = matches!(
+                    event.propagation,
+                    Propagation::DownUp | Propagation::All
+                );
+
+                if event.phase == EventPhase::Capture && bubbles {
+                    return;
+                }
+
+                let Event {
+                    origin,
+                    target,
+                    phase,
+                    consumed,
+                    stopped_immediately,
+                    message,
+                    ..
+                } = event;
+
+                if let Some(message) = message.as_any().downcast_ref::<M>() {
+                    let mut context =
+                        ListenerContext::new(*origin, *target, *phase, consumed, stopped_immediately);
+                    (listener)(state, entity, message, &mut context);
+                }
+            }),
+        )
+    }
+
+    /// Removes a listener added with `on`
+    pub fn remove_listener(&mut self, handle: ListenerHandle) {
+        self.listeners.remove(handle);
+    }
+
+    /// Adds a filter which is called with every event before it is dispatched
+    ///
+    /// A filter can modify the event, for example changing its target to redirect it, and returns
+    /// whether the event should be passed on or swallowed. Filters are called in the order they
+    /// were added and a swallowed event is not passed to the remaining filters. Every event in
+    /// the queue passes through the filters, including events without a target.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // Block mouse input to everything outside of a modal dialog
+    /// let filter = state.add_event_filter(move |state, event| {
+    ///     if let Some(WindowEvent::MouseDown(_)) = event.message.downcast::<WindowEvent>() {
+    ///         let target = event.target;
+    ///         if target != dialog && !target.is_descendant_of(&state.hierarchy, dialog) {
+    ///             return EventFilterResult::Swallow;
+    ///         }
+    ///     }
+    ///
+    ///     EventFilterResult::Pass
+    /// });
+    ///
+    /// // When the dialog closes
+    /// state.remove_event_filter(filter);
+    /// ```
+    pub fn add_event_filter<F>(&mut self, filter: F) -> EventFilterHandle
+    where
+        F: FnMut(&mut State, &mut Event) -> EventFilterResult,
+        F: 'static + Send,
+    {
+        self.event_filters.add(Box::new(filter))
+    }
+
+    /// Removes a filter added with `add_event_filter`
+    pub fn remove_event_filter(&mut self, handle: EventFilterHandle) {
+        self.event_filters.remove(handle);
+    }
+
+    /// Calls the function with the widget of an entity if the widget is of type `W`
+    ///
+    /// Returns the result of the function, or None if the entity has no widget of that type.
+    /// While a widget is handling an event or drawing it cannot be accessed this way, so from
+    /// within its own `on_event` a widget should use `self` instead.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let value = state.with_widget::<Slider, _>(slider, |slider| slider.value());
+    /// ```
+    pub fn with_widget<W, R>(&self, entity: Entity, f: impl FnOnce(&W) -> R) -> Option<R>
+    where
+        W: EventHandler + 'static,
+    {
+        self.event_handlers
+            .get(&entity)
+            .and_then(|event_handler| event_handler.downcast_ref::<W>())
+            .map(f)
+    }
+
+    /// Calls the function with a mutable reference to the widget of an entity if the widget is
+    /// of type `W`
+    ///
+    /// This can be used during event dispatch to update another widget directly. Changes made this
+    /// way do not send any events, so the caller is responsible for requesting a redraw if the
+    /// widget draws the changed state.
+    pub fn with_widget_mut<W, R>(
+        &mut self,
+        entity: Entity,
+        f: impl FnOnce(&mut W) -> R,
+    ) -> Option<R>
+    where
+        W: EventHandler + 'static,
+    {
+        self.event_handlers
+            .get_mut(&entity)
+            .and_then(|event_handler| event_handler.downcast::<W>())
+            .map(f)
+    }
+
     /// Adds a stylesheet to the application
     ///
     /// This function adds the stylesheet path to the application allowing for hot reloading of syles
@@ -252,10 +458,212 @@ impl State {
         self.event_queue.push_back(event);
     }
 
-    // TODO
-    // pub fn id2entity(&self, id: &str) -> Option<Entity> {
-    //     self.style.ids.get_by_left(&id.to_string()).cloned()
-    // }
+    /// Returns a handle which can be used to send events to the application from other threads
+    pub fn event_proxy(&self) -> EventProxy {
+        self.event_proxy_receiver.proxy()
+    }
+
+    /// Sets the function used by event proxies to wake the event loop of the backend
+    ///
+    /// Backends which sleep while waiting for window events must set a waker so that events sent
+    /// from other threads are processed without waiting for the next window event.
+    pub fn set_event_proxy_waker<F>(&mut self, waker: F)
+    where
+        F: Fn() + Send + 'static,
+    {
+        self.event_proxy_receiver.set_waker(Box::new(waker));
+    }
+
+    /// Moves the events sent through event proxies into the event queue
+    ///
+    /// Called by the backends at the start of each iteration of the event loop.
+    pub fn receive_proxy_events(&mut self) {
+        let events = self.event_proxy_receiver.try_iter().collect::<Vec<_>>();
+        for event in events {
+            self.insert_event(event);
+        }
+    }
+
+    /// Adds the receiving end of a value channel, delivering its values to the bound widgets
+    pub fn add_value_receiver(&mut self, receiver: ValueReceiver) {
+        self.value_receivers.push(receiver);
+    }
+
+    /// Sends the values which have changed in the value channels to their bound widgets
+    ///
+    /// Called by the backends once per frame.
+    pub fn receive_values(&mut self) {
+        let events = self
+            .value_receivers
+            .iter()
+            .flat_map(|receiver| receiver.events())
+            .collect::<Vec<_>>();
+
+        for event in events {
+            self.insert_event(event);
+        }
+    }
+
+    /// Starts recording the inputs passed to `dispatch_input`, discarding any current recording
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// state.start_recording();
+    /// // ...
+    /// if let Some(recording) = state.stop_recording() {
+    ///     recording.save("inputs.txt").expect("Failed to save recording");
+    /// }
+    /// ```
+    pub fn start_recording(&mut self) {
+        self.input_recorder = Some(InputRecorder::new());
+    }
+
+    /// Stops recording inputs and returns the recording, or None if there was no recording
+    pub fn stop_recording(&mut self) -> Option<InputRecording> {
+        self.input_recorder.take().map(|recorder| recorder.finish())
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.input_recorder.is_some()
+    }
+
+    /// Starts tracing the dispatch of events, keeping the most recent `capacity` traces
+    ///
+    /// Returns the tracer so that a sink can be added. Any existing traces are discarded.
+    pub fn enable_event_tracing(&mut self, capacity: usize) -> &mut EventTracer {
+        self.event_tracer.insert(EventTracer::new(capacity))
+    }
+
+    /// Stops tracing events and returns the tracer with the recorded traces
+    pub fn disable_event_tracing(&mut self) -> Option<EventTracer> {
+        self.event_tracer.take()
+    }
+
+    /// Returns the event tracer if tracing is enabled
+    pub fn event_tracer(&self) -> Option<&EventTracer> {
+        self.event_tracer.as_ref()
+    }
+
+    pub fn event_tracer_mut(&mut self) -> Option<&mut EventTracer> {
+        self.event_tracer.as_mut()
+    }
+
+    /// Sends an event once after a delay
+    ///
+    /// The delay is measured from the start of the current iteration of the event loop. The
+    /// returned handle can be used to cancel the timer before it fires.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let timer = state.add_timer(
+    ///     Duration::from_secs(60),
+    ///     Event::new(AppEvent::AutoSave).target(entity),
+    /// );
+    /// ```
+    pub fn add_timer(&mut self, delay: Duration, event: Event) -> TimerHandle {
+        self.timers.add(delay, None, event)
+    }
+
+    /// Sends an event repeatedly with a fixed interval, starting one interval from now
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let blink = state.add_interval(
+    ///     Duration::from_millis(500),
+    ///     Event::new(AppEvent::Blink).target(entity),
+    /// );
+    /// ```
+    pub fn add_interval(&mut self, interval: Duration, event: Event) -> TimerHandle {
+        self.timers.add(interval, Some(interval), event)
+    }
+
+    /// Cancels a timer or interval, returning false if the timer had already fired or been cancelled
+    pub fn cancel_timer(&mut self, handle: TimerHandle) -> bool {
+        self.timers.cancel(handle)
+    }
+
+    /// Sends the events of any timers which are due at the given time
+    ///
+    /// Called by the backends at the start of each iteration of the event loop.
+    pub fn update_timers(&mut self, now: Instant) {
+        for event in self.timers.update(now) {
+            self.insert_event(event);
+        }
+    }
+
+    /// Returns the time at which the next timer is due, used by backends to wake the event loop
+    pub fn next_timer(&self) -> Option<Instant> {
+        self.timers.next_deadline()
+    }
+
+    /// Returns the entity with the given id
+    ///
+    /// Ids are set with `set_id` when building a widget and can be used to find widgets which are
+    /// built inside of other widgets.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let save_button = state.entity_by_id("save").unwrap();
+    /// ```
+    pub fn entity_by_id(&self, id: &str) -> Option<Entity> {
+        self.style.ids.get_by_left(id).cloned()
+    }
+
+    /// Returns all entities which match a selector, in hierarchy order
+    ///
+    /// The selector uses the same syntax as stylesheets, including element names, ids, classes,
+    /// pseudo-classes and the child (`>`) and descendant (` `) combinators. A comma separated list
+    /// returns the entities which match any of the selectors. An invalid selector is an error.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let buttons = state.query(".track-row > button")?;
+    /// ```
+    pub fn query(&self, selector: &str) -> Result<Vec<Entity>, SelectorParseError> {
+        self.query_in(Entity::root(), selector)
+    }
+
+    /// Returns the first entity which matches a selector, in hierarchy order
+    pub fn query_one(&self, selector: &str) -> Result<Option<Entity>, SelectorParseError> {
+        Ok(self.query_in(Entity::root(), selector)?.first().cloned())
+    }
+
+    /// Returns all descendants of an entity which match a selector, in hierarchy order
+    ///
+    /// The selector is matched against the whole hierarchy, so combinators can refer to ancestors
+    /// of the entity, but only the descendants of the entity are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let buttons = state.query_in(panel, ".track-row button")?;
+    /// ```
+    pub fn query_in(
+        &self,
+        entity: Entity,
+        selector: &str,
+    ) -> Result<Vec<Entity>, SelectorParseError> {
+        let groups = style::theme::parse_selector_groups(selector)?;
+
+        if !self.is_alive(entity) {
+            return Ok(Vec::new());
+        }
+
+        Ok(entity
+            .branch_iter(&self.hierarchy)
+            .skip(1)
+            .filter(|descendant| {
+                groups.iter().any(|selectors| {
+                    crate::systems::check_selectors(self, &self.hierarchy, *descendant, selectors)
+                })
+            })
+            .collect())
+    }
 
     // This should probably be moved to state.mouse
     pub fn capture(&mut self, id: Entity) {
@@ -289,6 +697,7 @@ impl State {
             );
             self.captured = Entity::null();
             self.active = Entity::null();
+            self.reset_cursor();
         }
     }
 
@@ -323,26 +732,69 @@ impl State {
     //     entity
     // }
 
-    //  TODO
+    /// Returns true if the entity has been added and not yet removed
+    ///
+    /// Entity indices are reused after an entity is removed, so a stale copy of a removed entity
+    /// could otherwise be used to address the data of a newer widget.
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.entity_manager.is_alive(entity)
+    }
+
+    /// Removes an entity and all of its descendants
+    ///
+    /// The style, data and hierarchy storage of the removed entities is freed and their indices
+    /// can be reused by new entities. Each removed widget receives a `WindowEvent::WidgetRemoved`
+    /// event, starting with the deepest descendants, before its event handler is dropped.
     pub fn remove(&mut self, entity: Entity) {
+        // Removing a stale entity would remove the newer entity which reuses its index
+        if !self.is_alive(entity) || entity == Entity::root() {
+            return;
+        }
 
         let delete_list = entity.branch_iter(&self.hierarchy).collect::<Vec<_>>();
 
-        println!("Delete List: {:?}", delete_list);
-
         for entity in delete_list.iter().rev() {
-            self.hierarchy.remove(*entity);
             self.hierarchy.remove(*entity);
             self.data.remove(*entity);
             self.style.remove(*entity);
             self.removed_entities.push(*entity);
+            self.entity_manager.destroy_entity(*entity);
+        }
+
+        // Timers sending events to the removed entities will never fire
+        self.timers.remove_targets(&delete_list);
+
+        // Reset any references to the removed entities
+        if delete_list.contains(&self.focused) {
+            self.focused = Entity::root();
+        }
+
+        if delete_list.contains(&self.hovered) {
+            self.hovered = Entity::root();
+        }
+
+        if delete_list.contains(&self.active) {
+            self.active = Entity::null();
+        }
+
+        if delete_list.contains(&self.captured) {
+            self.captured = Entity::null();
+            self.reset_cursor();
+        }
+
+        self.remove_drag_references(&delete_list);
+        self.shortcuts.remove_entities(&delete_list);
+        self.remove_focus_references(&delete_list);
+
+        if let Some((caret_entity, _)) = self.caret {
+            if delete_list.contains(&caret_entity) {
+                self.caret = None;
+            }
         }
 
-    
         self.insert_event(Event::new(WindowEvent::Restyle).target(Entity::root()));
         self.insert_event(Event::new(WindowEvent::Relayout).target(Entity::root()));
         self.insert_event(Event::new(WindowEvent::Redraw).target(Entity::root()));
-
     }
 
     // Run all pending animations
@@ -424,3 +876,149 @@ impl State {
             || self.style.max_height.has_animations()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventManager;
+
+    use std::sync::{Arc, Mutex};
+
+    // Records the removal notifications received while the event handler is still in place
+    struct Removed(Arc<Mutex<Vec<Entity>>>);
+
+    impl EventHandler for Removed {
+        fn on_event(&mut self, state: &mut State, entity: Entity, event: &mut Event) {
+            if let Some(WindowEvent::WidgetRemoved) = event.message.downcast() {
+                assert!(!state.is_alive(entity));
+                self.0.lock().unwrap().push(entity);
+            }
+        }
+    }
+
+    #[test]
+    fn remove_clears_storages_and_references() {
+        let mut state = State::new();
+        state.hierarchy.add(Entity::root(), None);
+        let removed = Arc::new(Mutex::new(Vec::new()));
+
+        let panel = state.add(Entity::root());
+        let button = state.add(panel);
+        let sibling = state.add(Entity::root());
+        for entity in [panel, button].iter() {
+            state.build(*entity, Removed(removed.clone()));
+        }
+
+        state.style.insert_id(button, "save").unwrap();
+        state.style.insert_element(button, "button");
+        state.style.background_color.insert(button, Color::rgb(255, 0, 0));
+        state.style.background_color.insert(sibling, Color::rgb(0, 255, 0));
+        state.data.set_posx(button, 10.0);
+        state.on::<WindowEvent, _>(button, |_, _, _, _| {});
+
+        state.focused = button;
+        state.hovered = button;
+        state.capture(button);
+
+        state.remove(panel);
+
+        assert!(!state.is_alive(panel));
+        assert!(!state.is_alive(button));
+        assert!(!state.hierarchy.contains(button));
+        assert_eq!(state.style.ids.get_by_left(&"save".to_string()), None);
+        assert!(!state.style.elements.contains(button));
+        assert_eq!(state.style.background_color.get(button), None);
+        assert_eq!(state.data.get_posx(button), 0.0);
+
+        // The storage of the remaining entity is untouched
+        assert_eq!(
+            state.style.background_color.get(sibling),
+            Some(&Color::rgb(0, 255, 0))
+        );
+
+        assert_eq!(state.focused, Entity::root());
+        assert_eq!(state.hovered, Entity::root());
+        assert_eq!(state.captured, Entity::null());
+        assert_eq!(state.active, Entity::null());
+
+        // The removed widgets are notified, deepest first, and then their handlers are dropped
+        EventManager::new().flush_events(&mut state);
+        assert_eq!(*removed.lock().unwrap(), vec![button, panel]);
+        assert!(!state.event_handlers.contains_key(&button));
+        assert!(!state.event_handlers.contains_key(&panel));
+        assert!(state.listeners.take(button).is_none());
+    }
+
+    #[test]
+    fn query_matches_selectors_in_hierarchy_order() {
+        let mut state = State::new();
+        state.hierarchy.add(Entity::root(), None);
+
+        let row = state.add(Entity::root());
+        let save = state.add(row);
+        let label = state.add(save);
+        let cancel = state.add(row);
+        let primary = state.add(Entity::root());
+
+        state.style.insert_element(row, "row").insert_class(row, "row");
+        state.style.insert_element(save, "button");
+        state.style.insert_id(save, "save").unwrap();
+        state.style.insert_element(label, "label");
+        state.style.insert_element(cancel, "button");
+        state.style.insert_element(primary, "button").insert_class(primary, "primary");
+
+        assert_eq!(state.query("button"), Ok(vec![save, cancel, primary]));
+        assert_eq!(state.query(".row > button"), Ok(vec![save, cancel]));
+        assert_eq!(state.query(".row label"), Ok(vec![label]));
+        assert_eq!(state.query(".row > label"), Ok(vec![]));
+        assert_eq!(state.query(".row #save"), Ok(vec![save]));
+        assert_eq!(state.query("label, .primary"), Ok(vec![label, primary]));
+
+        assert_eq!(state.query_one("button"), Ok(Some(save)));
+        assert_eq!(state.query_one("textbox"), Ok(None));
+
+        // Only descendants are returned, but the selector can match ancestors of the entity
+        assert_eq!(state.query_in(row, "button"), Ok(vec![save, cancel]));
+        assert_eq!(state.query_in(save, ".row label"), Ok(vec![label]));
+        assert_eq!(state.query_in(save, "button"), Ok(vec![]));
+    }
+
+    #[test]
+    fn query_reports_invalid_selectors() {
+        let mut state = State::new();
+        state.hierarchy.add(Entity::root(), None);
+        let removed = state.add(Entity::root());
+        state.remove(removed);
+
+        let error = SelectorParseError {
+            selector: ".row >".to_string(),
+        };
+        assert_eq!(state.query(".row >"), Err(error.clone()));
+        assert_eq!(state.query_one(".row >"), Err(error.clone()));
+        assert_eq!(state.query_in(removed, ".row >"), Err(error));
+
+        assert_eq!(state.query_in(removed, "button"), Ok(vec![]));
+    }
+
+    #[test]
+    fn duplicate_ids_go_to_the_last_entity() {
+        let mut state = State::new();
+        state.hierarchy.add(Entity::root(), None);
+        let first = state.add(Entity::root());
+        let second = state.add(Entity::root());
+
+        first.set_id(&mut state, "save");
+        assert_eq!(
+            second.try_set_id(&mut state, "save").err(),
+            Some(DuplicateIdError {
+                id: "save".to_string(),
+                entity: first,
+            })
+        );
+        assert_eq!(state.entity_by_id("save"), Some(first));
+
+        second.set_id(&mut state, "save");
+        assert_eq!(state.entity_by_id("save"), Some(second));
+        assert_eq!(state.style.get_id(first), None);
+    }
+}
@@ -0,0 +1,358 @@
+use crate::entity::Entity;
+use crate::events::{parse_code, Event};
+use crate::state::hierarchy::IntoParentIterator;
+use crate::state::mouse::ModifiersState;
+use crate::state::State;
+
+use keyboard_types::Code;
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A key combined with modifiers, such as Ctrl+S or Ctrl+Shift+P
+///
+/// A shortcut matches a key press when the key and the held modifiers are exactly the same, so
+/// Ctrl+S does not match Ctrl+Shift+S. Shortcuts can be parsed from text with the modifiers and
+/// key separated by `+`:
+///
+/// ```ignore
+/// let save: Shortcut = "Ctrl+S".parse().unwrap();
+/// let palette = Shortcut::new(Code::KeyP).ctrl().shift();
+/// assert_eq!(palette.to_string(), "Ctrl+Shift+P");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shortcut {
+    pub code: Code,
+    pub modifiers: ModifiersState,
+}
+
+impl Shortcut {
+    pub fn new(code: Code) -> Self {
+        Shortcut {
+            code,
+            modifiers: ModifiersState::default(),
+        }
+    }
+
+    pub fn ctrl(mut self) -> Self {
+        self.modifiers.ctrl = true;
+        self
+    }
+
+    pub fn shift(mut self) -> Self {
+        self.modifiers.shift = true;
+        self
+    }
+
+    pub fn alt(mut self) -> Self {
+        self.modifiers.alt = true;
+        self
+    }
+
+    pub fn logo(mut self) -> Self {
+        self.modifiers.logo = true;
+        self
+    }
+
+    /// Returns true if the shortcut matches a key pressed with the modifiers
+    pub fn matches(&self, code: Code, modifiers: ModifiersState) -> bool {
+        self.code == code && self.modifiers == modifiers
+    }
+}
+
+impl fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+
+        if self.modifiers.alt {
+            write!(f, "Alt+")?;
+        }
+
+        if self.modifiers.shift {
+            write!(f, "Shift+")?;
+        }
+
+        if self.modifiers.logo {
+            write!(f, "Meta+")?;
+        }
+
+        // Letters, digits and arrows are shown without the prefix of their code
+        let name = self.code.to_string();
+        let name = name
+            .strip_prefix("Key")
+            .or_else(|| name.strip_prefix("Digit"))
+            .or_else(|| name.strip_prefix("Arrow"))
+            .unwrap_or(&name);
+
+        write!(f, "{}", name)
+    }
+}
+
+/// Returned when a shortcut cannot be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShortcutParseError {
+    pub text: String,
+}
+
+impl fmt::Display for ShortcutParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid shortcut: {}", self.text)
+    }
+}
+
+impl std::error::Error for ShortcutParseError {}
+
+impl FromStr for Shortcut {
+    type Err = ShortcutParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let error = || ShortcutParseError {
+            text: text.to_string(),
+        };
+
+        let mut parts = text.split('+').map(|part| part.trim()).collect::<Vec<_>>();
+        let key = parts.pop().filter(|key| !key.is_empty()).ok_or_else(error)?;
+
+        let mut modifiers = ModifiersState::default();
+        for part in parts {
+            match part.to_lowercase().as_str() {
+                "ctrl" | "control" => modifiers.ctrl = true,
+                "shift" => modifiers.shift = true,
+                "alt" | "option" => modifiers.alt = true,
+                "meta" | "logo" | "super" | "cmd" | "command" => modifiers.logo = true,
+                _ => return Err(error()),
+            }
+        }
+
+        let code = parse_key_name(key).ok_or_else(error)?;
+
+        Ok(Shortcut { code, modifiers })
+    }
+}
+
+// Parses the name of a key as shown by the Display implementation of Shortcut or of Code
+fn parse_key_name(key: &str) -> Option<Code> {
+    let mut chars = key.chars();
+    if let (Some(character), None) = (chars.next(), chars.next()) {
+        let character = character.to_ascii_uppercase();
+        if character.is_ascii_uppercase() {
+            return parse_code(&format!("Key{}", character));
+        }
+
+        if character.is_ascii_digit() {
+            return parse_code(&format!("Digit{}", character));
+        }
+    }
+
+    match key {
+        "Up" | "Down" | "Left" | "Right" => parse_code(&format!("Arrow{}", key)),
+        "Esc" => Some(Code::Escape),
+        "Del" => Some(Code::Delete),
+        _ => parse_code(key),
+    }
+}
+
+/// Where a shortcut is active
+///
+/// When the same shortcut is added with more than one scope the most specific is triggered: a
+/// focus scope containing the focused entity, nearest first, then the window and then the
+/// global scope. Each state is a single window, so global and window shortcuts are active in
+/// the same places, but window shortcuts take precedence and are intended for commands which
+/// only apply to one window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShortcutScope {
+    Global,
+    Window,
+    // Active while the entity or one of its descendants has keyboard focus
+    Focus(Entity),
+}
+
+/// A handle to a shortcut added with `State::add_shortcut`, which can be used to remove it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ShortcutHandle(u64);
+
+/// Returned when a shortcut is added with the same keys and scope as an existing shortcut
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShortcutConflict {
+    pub shortcut: Shortcut,
+    pub scope: ShortcutScope,
+    // The shortcut already using the keys
+    pub existing: ShortcutHandle,
+}
+
+impl fmt::Display for ShortcutConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Shortcut {} is already used in scope {:?}",
+            self.shortcut, self.scope
+        )
+    }
+}
+
+impl std::error::Error for ShortcutConflict {}
+
+#[derive(Clone)]
+struct Binding {
+    handle: ShortcutHandle,
+    shortcut: Shortcut,
+    scope: ShortcutScope,
+    event: Event,
+}
+
+// Stores the shortcuts of a state in the order they were added
+#[derive(Clone)]
+pub(crate) struct Shortcuts {
+    next_handle: u64,
+    bindings: Vec<Binding>,
+}
+
+impl Shortcuts {
+    pub fn new() -> Self {
+        Shortcuts {
+            next_handle: 0,
+            bindings: Vec::new(),
+        }
+    }
+
+    // Removes the shortcuts scoped to removed entities
+    pub fn remove_entities(&mut self, entities: &[Entity]) {
+        self.bindings.retain(|binding| match binding.scope {
+            ShortcutScope::Focus(entity) => !entities.contains(&entity),
+            _ => true,
+        });
+    }
+}
+
+impl State {
+    /// Adds a keyboard shortcut which sends an event when its keys are pressed
+    ///
+    /// Shortcuts are matched when a key is pressed, before the `KeyDown` event is sent to the
+    /// focused entity, and a key press which triggers a shortcut is not sent as a `KeyDown`. The
+    /// event is sent to the root, or to the entity of a focus scope, unless it has a target.
+    /// Adding a shortcut with the same keys and scope as an existing one returns a conflict.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// state.add_shortcut(
+    ///     "Ctrl+S".parse().unwrap(),
+    ///     ShortcutScope::Global,
+    ///     Event::new(AppEvent::Save),
+    /// )?;
+    ///
+    /// // Only while the editor has focus
+    /// state.add_shortcut(
+    ///     Shortcut::new(Code::KeyD).ctrl(),
+    ///     ShortcutScope::Focus(editor),
+    ///     Event::new(EditorEvent::DuplicateLine),
+    /// )?;
+    /// ```
+    pub fn add_shortcut(
+        &mut self,
+        shortcut: Shortcut,
+        scope: ShortcutScope,
+        event: Event,
+    ) -> Result<ShortcutHandle, ShortcutConflict> {
+        if let Some(existing) = self
+            .shortcuts
+            .bindings
+            .iter()
+            .find(|binding| binding.shortcut == shortcut && binding.scope == scope)
+        {
+            return Err(ShortcutConflict {
+                shortcut,
+                scope,
+                existing: existing.handle,
+            });
+        }
+
+        let handle = ShortcutHandle(self.shortcuts.next_handle);
+        self.shortcuts.next_handle += 1;
+
+        self.shortcuts.bindings.push(Binding {
+            handle,
+            shortcut,
+            scope,
+            event,
+        });
+
+        Ok(handle)
+    }
+
+    pub fn remove_shortcut(&mut self, handle: ShortcutHandle) {
+        self.shortcuts
+            .bindings
+            .retain(|binding| binding.handle != handle);
+    }
+
+    /// Returns the keys and scope of a shortcut
+    pub fn shortcut(&self, handle: ShortcutHandle) -> Option<(Shortcut, ShortcutScope)> {
+        self.shortcuts
+            .bindings
+            .iter()
+            .find(|binding| binding.handle == handle)
+            .map(|binding| (binding.shortcut, binding.scope))
+    }
+
+    /// Returns the first shortcut which sends an event with the same message
+    ///
+    /// This is used by widgets such as `MenuItem` to show the accelerator of their event.
+    pub fn find_shortcut(&self, event: &Event) -> Option<Shortcut> {
+        self.shortcuts
+            .bindings
+            .iter()
+            .find(|binding| binding.event.message.equals_a(&*event.message))
+            .map(|binding| binding.shortcut)
+    }
+
+    // Sends the event of the shortcut matching the key and returns true if there is one
+    pub(crate) fn trigger_shortcut(&mut self, code: Code) -> bool {
+        let modifiers = self.modifiers;
+        let focused = self.focused;
+
+        let mut best: Option<(usize, &Binding)> = None;
+        for binding in self.shortcuts.bindings.iter() {
+            if !binding.shortcut.matches(code, modifiers) {
+                continue;
+            }
+
+            // Lower is more specific
+            let rank = match binding.scope {
+                ShortcutScope::Focus(entity) => {
+                    match focused
+                        .parent_iter(&self.hierarchy)
+                        .position(|ancestor| ancestor == entity)
+                    {
+                        Some(depth) => depth,
+                        None => continue,
+                    }
+                }
+                ShortcutScope::Window => usize::MAX - 1,
+                ShortcutScope::Global => usize::MAX,
+            };
+
+            if best.map_or(true, |(best_rank, _)| rank < best_rank) {
+                best = Some((rank, binding));
+            }
+        }
+
+        let (scope, mut event) = match best {
+            Some((_, binding)) => (binding.scope, binding.event.clone()),
+            None => return false,
+        };
+
+        if event.target == Entity::null() {
+            event.target = match scope {
+                ShortcutScope::Focus(entity) => entity,
+                _ => Entity::root(),
+            };
+        }
+
+        self.insert_event(event);
+
+        true
+    }
+}
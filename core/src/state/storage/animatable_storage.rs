@@ -1,4 +1,5 @@
 use crate::state::animation::{AnimationState, Interpolator};
+use crate::state::entity::is_older_generation;
 use crate::state::Entity;
 
 #[derive(Copy, Clone)]
@@ -188,6 +189,8 @@ impl Default for DataIndex {
 pub struct AnimatableStorage<T: Interpolator> {
     // Mapping from entity index to data and animations
     pub entity_indices: Vec<DataIndex>,
+    // The generation of the entity which owns each entity index
+    pub generations: Vec<u32>,
     // Mapping from rule index to data
     pub rule_indices: Vec<DataIndex>,
     // An index to the animation either in definitions or active
@@ -208,6 +211,7 @@ where
     pub fn new() -> Self {
         AnimatableStorage {
             entity_indices: Vec::new(),
+            generations: Vec::new(),
             rule_indices: Vec::new(),
             //animation_indices: Vec::new(),
             data: Vec::new(),
@@ -217,12 +221,46 @@ where
         }
     }
 
+    // Returns true if the entity index is owned by the entity rather than a stale or newer
+    // entity with the same index
+    pub fn contains(&self, entity: Entity) -> bool {
+        match entity.index() {
+            Some(index) => {
+                index < self.entity_indices.len() && self.generations[index] == entity.generation()
+            }
+            None => false,
+        }
+    }
+
+    // Makes sure there is an entity index for the entity. If the index was owned by a previous
+    // entity then the link to its data and animation is discarded. Returns false for a stale
+    // entity, whose index is owned by a newer entity.
+    fn claim(&mut self, index: usize, generation: u32) -> bool {
+        if index >= self.entity_indices.len() {
+            self.entity_indices.resize(index + 1, Default::default());
+            self.generations.resize(index + 1, 0);
+        }
+
+        if is_older_generation(generation, self.generations[index]) {
+            return false;
+        }
+
+        if self.generations[index] != generation {
+            self.entity_indices[index] = Default::default();
+            self.generations[index] = generation;
+        }
+
+        true
+    }
+
     // Insert inline data
     pub fn insert(&mut self, entity: Entity, value: T) {
         if let Some(index) = entity.index() {
-            if index >= self.entity_indices.len() {
+            if index >= self.entity_indices.len() || self.generations[index] != entity.generation() {
                 // Resize entity indices to include new entity
-                self.entity_indices.resize(index + 1, Default::default());
+                if !self.claim(index, entity.generation()) {
+                    return;
+                }
                 // Set the data index to the data position
                 self.entity_indices[index].data_index = Index::new(self.inline_data.len())
                     .inherited(false)
@@ -275,8 +313,8 @@ where
             }
 
             // Link the entity to the animation
-            if index >= self.entity_indices.len() {
-                self.entity_indices.resize(index + 1, Default::default());
+            if !self.claim(index, entity.generation()) {
+                return;
             }
 
             let animation_index = self.entity_indices[index].animation_id;
@@ -366,15 +404,69 @@ where
         self.active_animations
             .retain(|e| e.t0 < 1.0 || e.persistent);
 
+        // Entities which have been destroyed since the animation started are skipped
         for state in inactive.into_iter() {
             for entity in state.entities.iter() {
-                self.entity_indices[entity.index_unchecked()].animation_id = std::usize::MAX;
+                if self.contains(*entity) {
+                    self.entity_indices[entity.index_unchecked()].animation_id = std::usize::MAX;
+                }
             }
         }
 
         for (index, state) in self.active_animations.iter().enumerate() {
             for entity in state.entities.iter() {
-                self.entity_indices[entity.index_unchecked()].animation_id = index;
+                if self.contains(*entity) {
+                    self.entity_indices[entity.index_unchecked()].animation_id = index;
+                }
+            }
+        }
+    }
+
+    // Removes the inline data, rule link and animations of the entity.
+    // The last inline data item is moved into the freed slot to keep the data contiguous.
+    pub fn remove(&mut self, entity: Entity) {
+        if !self.contains(entity) {
+            return;
+        }
+
+        let index = entity.index_unchecked();
+        let data_index = self.entity_indices[index].data_index;
+        self.entity_indices[index] = DataIndex::default();
+        // Writes through copies of the removed entity are ignored from now on
+        self.generations[index] = entity.generation().wrapping_add(1);
+
+        // Remove the entity from any active animations and drop animations with no entities left
+        for state in self.active_animations.iter_mut() {
+            state.entities.retain(|e| *e != entity);
+        }
+
+        let num_animations = self.active_animations.len();
+        self.active_animations
+            .retain(|state| !state.entities.is_empty());
+
+        if self.active_animations.len() != num_animations {
+            for (index, state) in self.active_animations.iter().enumerate() {
+                for entity in state.entities.iter() {
+                    if self.contains(*entity) {
+                        self.entity_indices[entity.index_unchecked()].animation_id = index;
+                    }
+                }
+            }
+        }
+
+        if !data_index.is_inline() || data_index.index() >= self.inline_data.len() {
+            return;
+        }
+
+        let last_index = self.inline_data.len() - 1;
+        self.inline_data.swap_remove(data_index.index());
+
+        // Point the entity which owned the moved data to its new position
+        if data_index.index() != last_index {
+            if let Some(moved) = self.entity_indices.iter_mut().find(|index| {
+                index.data_index.is_inline() && index.data_index.index() == last_index
+            }) {
+                moved.data_index.set_value(data_index.index());
             }
         }
     }
@@ -422,8 +514,8 @@ where
             }
 
             // Check if entity exists, else add the entity
-            if index >= self.entity_indices.len() {
-                self.entity_indices.resize(index + 1, Default::default());
+            if !self.claim(index, entity.generation()) {
+                return LinkType::NoData;
             }
 
             // Check if the entity is already linked to the rule
@@ -508,7 +600,7 @@ where
 
     pub fn unlink(&mut self, entity: Entity) {
         if let Some(index) = entity.index() {
-            if index >= self.entity_indices.len() {
+            if !self.contains(entity) {
                 return;
             }
 
@@ -519,7 +611,7 @@ where
     pub fn link_rule(&mut self, entity: Entity, rule_list: &Vec<usize>) -> bool {
         if let Some(index) = entity.index() {
             // Check if the entity already has an inline style. If so then rules don't affect it.
-            if index < self.entity_indices.len() {
+            if self.contains(entity) {
                 if self.entity_indices[index].data_index.is_inline() {
                     return false;
                 }
@@ -589,7 +681,7 @@ where
 
     // Get the current value (either animation or data rule)
     pub fn get(&self, entity: Entity) -> Option<&T> {
-        if entity.index_unchecked() >= self.entity_indices.len() {
+        if !self.contains(entity) {
             return None;
         }
 
@@ -622,7 +714,7 @@ where
 
     // Returns true if the entity is linked to a currently active animation
     pub fn is_animating(&self, entity: Entity) -> bool {
-        if entity.index_unchecked() >= self.entity_indices.len() {
+        if !self.contains(entity) {
             return false;
         }
 
@@ -708,3 +800,64 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    fn fade() -> AnimationState<f32> {
+        AnimationState::new()
+            .with_duration(Duration::from_secs(1))
+            .with_keyframe((0.0, 0.0))
+            .with_keyframe((1.0, 10.0))
+    }
+
+    storage_tests!(AnimatableStorage::new());
+
+    #[test]
+    fn reused_index_does_not_inherit_animation() {
+        let mut storage = AnimatableStorage::new();
+        let old = Entity::new(1, 0);
+        let new = Entity::new(1, 1);
+
+        let animation = storage.insert_animation(fade());
+        storage.insert(old, 1.0f32);
+        storage.play_animation(old, animation);
+        storage.insert(new, 2.0);
+        assert!(!storage.is_animating(new));
+
+        // Nor can a stale entity start one on it
+        storage.play_animation(old, animation);
+        assert!(!storage.is_animating(new));
+        assert_eq!(storage.get(new), Some(&2.0));
+    }
+
+    #[test]
+    fn remove_animating_entity() {
+        let mut storage = AnimatableStorage::new();
+        let first = Entity::new(0, 0);
+        let second = Entity::new(1, 0);
+
+        let animation = storage.insert_animation(fade());
+        storage.insert(first, 1.0f32);
+        storage.insert(second, 2.0);
+        storage.play_animation(first, animation);
+        storage.play_animation(second, animation);
+        storage.remove(first);
+
+        assert!(!storage.is_animating(first));
+        assert_eq!(storage.active_animations.len(), 1);
+
+        // The animation of the remaining entity has moved and is still found
+        assert!(storage.is_animating(second));
+        assert_eq!(storage.get(second), Some(&0.0));
+
+        // The reused index does not pick up the animation
+        let new = Entity::new(0, 1);
+        storage.insert(new, 3.0);
+        assert!(!storage.is_animating(new));
+        assert_eq!(storage.get(new), Some(&3.0));
+    }
+}
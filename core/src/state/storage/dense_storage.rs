@@ -1,8 +1,11 @@
+use crate::state::entity::is_older_generation;
 use crate::entity::Entity;
 
 #[derive(Clone)]
 pub struct DenseStorage<T> {
     pub indices: Vec<u32>,
+    // The generation of the entity which owns each index
+    pub generations: Vec<u32>,
     pub data: Vec<T>,
 }
 
@@ -13,6 +16,7 @@ where
     pub fn new() -> Self {
         DenseStorage {
             indices: Vec::new(),
+            generations: Vec::new(),
             data: Vec::new(),
         }
     }
@@ -21,9 +25,19 @@ where
         if let Some(index) = entity.index() {
             if index >= self.indices.len() {
                 self.indices.resize(index + 1, std::u32::MAX);
+                self.generations.resize(index + 1, 0);
                 self.indices[index] = self.data.len() as u32;
+                self.generations[index] = entity.generation();
                 self.data.push(value);
             } else {
+                // A stale entity must not take the index back from a newer entity
+                if is_older_generation(entity.generation(), self.generations[index]) {
+                    return;
+                }
+
+                // A recycled index takes over the data of the previous entity
+                self.generations[index] = entity.generation();
+
                 let data_index = self.indices[index] as usize;
                 if data_index >= self.data.len() {
                     self.indices[index] = self.data.len() as u32;
@@ -35,10 +49,51 @@ where
         }
     }
 
-    pub fn remove(&mut self, _entity: Entity) {}
+    // Removes the data for the entity.
+    // The last data item is moved into the freed slot to keep the data contiguous.
+    pub fn remove(&mut self, entity: Entity) {
+        if !self.contains(entity) {
+            return;
+        }
+
+        let index = entity.index_unchecked();
+        let data_index = self.indices[index] as usize;
+        self.indices[index] = std::u32::MAX;
+        // Writes through copies of the removed entity are ignored from now on
+        self.generations[index] = entity.generation().wrapping_add(1);
+
+        if data_index >= self.data.len() {
+            return;
+        }
+
+        let last_index = self.data.len() - 1;
+        self.data.swap_remove(data_index);
+
+        // Point the entity which owned the moved data to its new position
+        if data_index != last_index {
+            if let Some(moved) = self
+                .indices
+                .iter_mut()
+                .find(|data_index| **data_index == last_index as u32)
+            {
+                *moved = data_index as u32;
+            }
+        }
+    }
+
+    // Returns true if the storage has data for the entity.
+    // Returns false for a stale entity whose index has been reused by a newer entity.
+    pub fn contains(&self, entity: Entity) -> bool {
+        match entity.index() {
+            Some(index) => {
+                index < self.indices.len() && self.generations[index] == entity.generation()
+            }
+            None => false,
+        }
+    }
 
     pub fn get(&self, entity: Entity) -> Option<&T> {
-        if entity.index_unchecked() >= self.indices.len() {
+        if !self.contains(entity) {
             return None;
         }
 
@@ -52,7 +107,7 @@ where
     }
 
     pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
-        if entity.index_unchecked() >= self.indices.len() {
+        if !self.contains(entity) {
             return None;
         }
 
@@ -66,7 +121,7 @@ where
     }
 
     pub fn set(&mut self, entity: Entity, value: T) {
-        if entity.index_unchecked() >= self.indices.len() {
+        if !self.contains(entity) {
             self.insert(entity, value);
             return;
         }
@@ -88,3 +143,10 @@ where
             + self.indices.len() * std::mem::size_of::<usize>();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    storage_tests!(DenseStorage::new());
+}
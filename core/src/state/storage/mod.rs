@@ -1,3 +1,75 @@
+// Tests shared by the storages, which map entities to values through the same methods
+#[cfg(test)]
+macro_rules! storage_tests {
+    ($storage:expr) => {
+        #[test]
+        fn reused_index_takes_over_data() {
+            let mut storage = $storage;
+            let old = Entity::new(1, 0);
+            let new = Entity::new(1, 1);
+
+            storage.insert(old, 1.0f32);
+            storage.insert(new, 2.0);
+
+            assert_eq!(storage.get(new), Some(&2.0));
+            assert!(!storage.contains(old));
+            assert_eq!(storage.get(old), None);
+        }
+
+        #[test]
+        fn stale_entity_writes_are_ignored() {
+            let mut storage = $storage;
+            let old = Entity::new(1, 0);
+            let new = Entity::new(1, 1);
+
+            storage.insert(old, 1.0f32);
+            storage.insert(new, 2.0);
+            storage.insert(old, 3.0);
+
+            assert_eq!(storage.get(new), Some(&2.0));
+            assert_eq!(storage.get(old), None);
+        }
+
+        #[test]
+        fn reused_index_after_generation_wraps() {
+            let mut storage = $storage;
+            let old = Entity::new(1, u32::MAX);
+            let new = Entity::new(1, 0);
+
+            storage.insert(old, 1.0f32);
+            storage.insert(new, 2.0);
+            storage.insert(old, 3.0);
+
+            assert_eq!(storage.get(new), Some(&2.0));
+            assert_eq!(storage.get(old), None);
+        }
+
+        #[test]
+        fn remove_middle_of_three() {
+            let mut storage = $storage;
+            let first = Entity::new(0, 0);
+            let middle = Entity::new(1, 0);
+            let last = Entity::new(2, 0);
+
+            storage.insert(first, 1.0f32);
+            storage.insert(middle, 2.0);
+            storage.insert(last, 3.0);
+            storage.remove(middle);
+
+            assert_eq!(storage.get(first), Some(&1.0));
+            assert_eq!(storage.get(middle), None);
+            assert_eq!(storage.get(last), Some(&3.0));
+
+            // The moved data is still found after the freed index is reused
+            storage.insert(middle, 4.0);
+            storage.insert(Entity::new(1, 1), 5.0);
+            assert_eq!(storage.get(Entity::new(1, 1)), Some(&5.0));
+            assert_eq!(storage.get(last), Some(&3.0));
+            assert_eq!(storage.get(middle), None);
+        }
+    };
+}
+
 pub mod animatable_storage;
 pub mod dense_storage;
 pub mod style_storage;
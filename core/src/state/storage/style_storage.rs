@@ -1,3 +1,4 @@
+use crate::state::entity::is_older_generation;
 use crate::state::Entity;
 // Could use last bit of entity_indices index to denote whether the data is from a rule or an inline property
 
@@ -90,6 +91,8 @@ impl Default for Index {
 pub struct StyleStorage<T> {
     // Mapping from entity to data
     pub entity_indices: Vec<Index>,
+    // The generation of the entity which owns each entity index
+    pub generations: Vec<u32>,
     // Mapping from rule to data
     pub rule_indices: Vec<usize>,
     pub data: Vec<T>,
@@ -103,17 +106,52 @@ where
     pub fn new() -> Self {
         StyleStorage {
             entity_indices: Vec::new(),
+            generations: Vec::new(),
             rule_indices: Vec::new(),
             data: Vec::new(),
             inline_data: Vec::new(),
         }
     }
 
+    // Returns true if the entity index is owned by the entity rather than a stale or newer
+    // entity with the same index
+    pub fn contains(&self, entity: Entity) -> bool {
+        match entity.index() {
+            Some(index) => {
+                index < self.entity_indices.len() && self.generations[index] == entity.generation()
+            }
+            None => false,
+        }
+    }
+
+    // Makes sure there is an entity index for the entity. If the index was owned by a previous
+    // entity then the link to its data is discarded. Returns false for a stale entity, whose
+    // index is owned by a newer entity.
+    fn claim(&mut self, index: usize, generation: u32) -> bool {
+        if index >= self.entity_indices.len() {
+            self.entity_indices.resize(index + 1, Default::default());
+            self.generations.resize(index + 1, 0);
+        }
+
+        if is_older_generation(generation, self.generations[index]) {
+            return false;
+        }
+
+        if self.generations[index] != generation {
+            self.entity_indices[index] = Default::default();
+            self.generations[index] = generation;
+        }
+
+        true
+    }
+
     //Insert inline style
     pub fn insert(&mut self, entity: Entity, value: T) {
         if let Some(index) = entity.index() {
-            if index >= self.entity_indices.len() {
-                self.entity_indices.resize(index + 1, Default::default());
+            if index >= self.entity_indices.len() || self.generations[index] != entity.generation() {
+                if !self.claim(index, entity.generation()) {
+                    return;
+                }
                 self.entity_indices[index] = Index::new(self.inline_data.len()).inline(true);
                 //self.entity_indices[entity.index()].animation_index = std::usize::MAX - 1;
                 self.inline_data.push(value);
@@ -134,6 +172,38 @@ where
         }
     }
 
+    // Removes the inline data and any rule link for the entity.
+    // The last inline data item is moved into the freed slot to keep the data contiguous.
+    pub fn remove(&mut self, entity: Entity) {
+        if !self.contains(entity) {
+            return;
+        }
+
+        let index = entity.index_unchecked();
+        let data_index = self.entity_indices[index];
+        self.entity_indices[index] = Index::default();
+        // Writes through copies of the removed entity are ignored from now on
+        self.generations[index] = entity.generation().wrapping_add(1);
+
+        if !data_index.is_inline() || data_index.index() >= self.inline_data.len() {
+            return;
+        }
+
+        let last_index = self.inline_data.len() - 1;
+        self.inline_data.swap_remove(data_index.index());
+
+        // Point the entity which owned the moved data to its new position
+        if data_index.index() != last_index {
+            if let Some(moved) = self
+                .entity_indices
+                .iter_mut()
+                .find(|index| index.is_inline() && index.index() == last_index)
+            {
+                moved.set_value(data_index.index());
+            }
+        }
+    }
+
     // When the style system has determined the matching rule with the highest
     // specificity for an entity. The entity can be "linked" to the rule by pointing the
     // same computed property.
@@ -155,9 +225,9 @@ where
         }
 
         // Check if entity exists, else add the entity
-        if entity.index_unchecked() >= self.entity_indices.len() {
-            self.entity_indices
-                .resize(entity.index_unchecked() + 1, Default::default());
+        match entity.index() {
+            Some(index) if self.claim(index, entity.generation()) => {}
+            _ => return LinkType::NoData,
         }
         // Link the entity to the same data as the rule
 
@@ -172,7 +242,7 @@ where
     }
 
     pub fn unlink(&mut self, entity: Entity) {
-        if entity.index_unchecked() >= self.entity_indices.len() {
+        if !self.contains(entity) {
             return;
         }
 
@@ -182,7 +252,7 @@ where
     // Returns true if
     pub fn link_rule(&mut self, entity: Entity, rule_list: &Vec<usize>) -> bool {
         // Check if the entity already has an inline style. If so then rules don't affect it.
-        if entity.index_unchecked() < self.entity_indices.len() {
+        if self.contains(entity) {
             if self.entity_indices[entity.index_unchecked()].is_inline() {
                 return false;
             }
@@ -228,7 +298,7 @@ where
 
     // Get data linked to entity
     pub fn get(&self, entity: Entity) -> Option<&T> {
-        if entity.index_unchecked() >= self.entity_indices.len() {
+        if !self.contains(entity) {
             return None;
         }
 
@@ -250,7 +320,7 @@ where
     }
 
     pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
-        if entity.index_unchecked() >= self.entity_indices.len() {
+        if !self.contains(entity) {
             return None;
         }
 
@@ -330,3 +400,30 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    storage_tests!(StyleStorage::new());
+
+    #[test]
+    fn rule_links_follow_entity_generations() {
+        let mut storage = StyleStorage::new();
+        let linked = Entity::new(1, 0);
+        let old = Entity::new(2, 0);
+        let new = Entity::new(2, 1);
+
+        storage.insert_rule(0, 5.0f32);
+        storage.insert(new, 2.0);
+        assert!(storage.link_rule(linked, &vec![0]));
+
+        // A stale entity cannot link to a rule and take the index from the new entity
+        assert!(!storage.link_rule(old, &vec![0]));
+        assert_eq!(storage.get(new), Some(&2.0));
+
+        storage.remove(new);
+        assert_eq!(storage.get(new), None);
+        assert_eq!(storage.get(linked), Some(&5.0));
+    }
+}
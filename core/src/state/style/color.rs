@@ -42,6 +42,12 @@ impl Color {
         ((self.data & 0xFF00_0000) >> 24) as u8
     }
 
+    // Returns the color with its alpha value scaled by the specified opacity
+    pub fn with_opacity(self, opacity: f32) -> Color {
+        let a = (self.a() as f32 * opacity).max(0.0).min(255.0) as u8;
+        Color::rgba(self.r(), self.g(), self.b(), a)
+    }
+
     // Interpolate between two colors
     pub fn interpolate(start_color: Color, end_color: Color, scale: f64) -> Color {
         let r = Color::interp(start_color.r(), end_color.r(), scale);
@@ -12,6 +12,7 @@ use crate::state::storage::dense_storage::DenseStorage;
 use crate::state::storage::style_storage::StyleStorage;
 
 use crate::state::animation::AnimationState;
+use crate::CursorIcon;
 
 pub mod themes;
 
@@ -56,7 +57,36 @@ pub use color::Color;
 pub mod transform;
 pub use transform::Scale;
 
-// use bimap::BiMap;
+use bimap::BiMap;
+
+// Returned when setting an id which is already used by another entity
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateIdError {
+    pub id: String,
+    pub entity: Entity,
+}
+
+impl std::fmt::Display for DuplicateIdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The id '{}' is already used by entity {}", self.id, self.entity)
+    }
+}
+
+impl std::error::Error for DuplicateIdError {}
+
+// Returned when a selector passed to a query cannot be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectorParseError {
+    pub selector: String,
+}
+
+impl std::fmt::Display for SelectorParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid selector: '{}'", self.selector)
+    }
+}
+
+impl std::error::Error for SelectorParseError {}
 
 #[derive(Clone)]
 pub struct Style {
@@ -65,8 +95,8 @@ pub struct Style {
     //pub rules: Vec<usize>,
     pub rule_selectors: Vec<Vec<Selector>>,
 
-    //pub ids: DenseStorage<u64>,
-    //pub ids: BiMap<String, Entity>,
+    // Maps unique ids to entities and back
+    pub ids: BiMap<String, Entity>,
 
     pub elements: DenseStorage<u64>,
 
@@ -137,6 +167,8 @@ pub struct Style {
     pub clip_widget: DenseStorage<Entity>,
 
     pub focus_order: DenseStorage<FocusOrder>,
+    pub tab_index: StyleStorage<i32>,
+    pub cursor: StyleStorage<CursorIcon>,
 
     // Flexbox
     pub align_self: StyleStorage<AlignSelf>,
@@ -183,8 +215,7 @@ impl Style {
             //rules: Vec::new(),
             rule_selectors: Vec::new(),
 
-            //ids: DenseStorage::new(),
-            //ids: BiMap::new(),
+            ids: BiMap::new(),
             elements: DenseStorage::new(),
             classes: DenseStorage::new(),
             pseudo_classes: DenseStorage::new(),
@@ -259,6 +290,8 @@ impl Style {
             visibility: StyleStorage::new(),
             clip_widget: DenseStorage::new(),
             focus_order: DenseStorage::new(),
+            tab_index: StyleStorage::new(),
+            cursor: StyleStorage::new(),
 
             // Box Shadow
             shadow_h_offset: AnimatableStorage::new(),
@@ -505,6 +538,14 @@ impl Style {
                         self.z_order.insert_rule(rule_id, value);
                     }
 
+                    Property::TabIndex(value) => {
+                        self.tab_index.insert_rule(rule_id, value);
+                    }
+
+                    Property::Cursor(value) => {
+                        self.cursor.insert_rule(rule_id, value);
+                    }
+
                     Property::BoxShadow(box_shadow) => {
                         self.shadow_h_offset
                             .insert_rule(rule_id, box_shadow.horizontal_offset);
@@ -800,7 +841,87 @@ impl Style {
         self.focus_order.insert(entity, Default::default());
     }
 
-    pub fn remove(&mut self, entity: Entity) {}
+    // Remove all style data of an entity
+    pub fn remove(&mut self, entity: Entity) {
+        self.ids.remove_by_right(&entity);
+
+        // Link the previous and next entities in the focus order to each other
+        if let Some(focus_order) = self.focus_order.get(entity).cloned() {
+            if let Some(prev) = self.focus_order.get_mut(focus_order.prev) {
+                if prev.next == entity {
+                    prev.next = focus_order.next;
+                }
+            }
+
+            if let Some(next) = self.focus_order.get_mut(focus_order.next) {
+                if next.prev == entity {
+                    next.prev = focus_order.prev;
+                }
+            }
+        }
+
+        self.elements.remove(entity);
+        self.classes.remove(entity);
+        self.pseudo_classes.remove(entity);
+        self.z_order.remove(entity);
+        self.tab_index.remove(entity);
+        self.cursor.remove(entity);
+        self.rotate.remove(entity);
+        self.scaley.remove(entity);
+        self.display.remove(entity);
+        self.visibility.remove(entity);
+        self.opacity.remove(entity);
+        self.overflow.remove(entity);
+        self.scroll.remove(entity);
+        self.position.remove(entity);
+        self.left.remove(entity);
+        self.right.remove(entity);
+        self.top.remove(entity);
+        self.bottom.remove(entity);
+        self.width.remove(entity);
+        self.height.remove(entity);
+        self.max_width.remove(entity);
+        self.max_height.remove(entity);
+        self.min_width.remove(entity);
+        self.min_height.remove(entity);
+        self.margin_left.remove(entity);
+        self.margin_right.remove(entity);
+        self.margin_top.remove(entity);
+        self.margin_bottom.remove(entity);
+        self.padding_left.remove(entity);
+        self.padding_right.remove(entity);
+        self.padding_top.remove(entity);
+        self.padding_bottom.remove(entity);
+        self.border_width.remove(entity);
+        self.border_color.remove(entity);
+        self.border_radius_top_left.remove(entity);
+        self.border_radius_top_right.remove(entity);
+        self.border_radius_bottom_left.remove(entity);
+        self.border_radius_bottom_right.remove(entity);
+        self.clip_widget.remove(entity);
+        self.focus_order.remove(entity);
+        self.align_self.remove(entity);
+        self.flex_grow.remove(entity);
+        self.flex_shrink.remove(entity);
+        self.flex_basis.remove(entity);
+        self.flex_direction.remove(entity);
+        self.justify_content.remove(entity);
+        self.align_items.remove(entity);
+        self.align_content.remove(entity);
+        self.background_color.remove(entity);
+        self.background_image.remove(entity);
+        self.background_gradient.remove(entity);
+        self.shadow_h_offset.remove(entity);
+        self.shadow_v_offset.remove(entity);
+        self.shadow_blur.remove(entity);
+        self.shadow_color.remove(entity);
+        self.text.remove(entity);
+        self.tooltip.remove(entity);
+        self.font_color.remove(entity);
+        self.font_size.remove(entity);
+        self.text_align.remove(entity);
+        self.text_justify.remove(entity);
+    }
 
     // pub fn insert_style_rule(&mut self, style_rule: StyleRule) -> &mut Self {
     //     self.style_rules.push(style_rule);
@@ -808,14 +929,35 @@ impl Style {
     //     self
     // }
 
-    pub fn insert_id(&mut self, entity: Entity, id: &str) -> &mut Self {
-        // let mut s = DefaultHasher::new();
-        // id.hash(&mut s);
-        // self.ids.insert(entity, s.finish());
+    /// Sets the id of an entity, replacing any previous id of the entity
+    ///
+    /// Ids must be unique. If the id is already used by a different entity then the id is left
+    /// unchanged and an error is returned.
+    pub fn insert_id(&mut self, entity: Entity, id: &str) -> Result<(), DuplicateIdError> {
+        if let Some(other) = self.ids.get_by_left(id) {
+            if *other != entity {
+                return Err(DuplicateIdError {
+                    id: id.to_string(),
+                    entity: *other,
+                });
+            }
+        }
 
-        //self.ids.insert(id.to_string(), entity);
+        self.ids.insert(id.to_string(), entity);
 
-        self
+        Ok(())
+    }
+
+    /// Sets the id of an entity, replacing any previous id of the entity
+    ///
+    /// If the id is already used by a different entity then it is taken from that entity, which
+    /// is left without an id.
+    pub fn replace_id(&mut self, entity: Entity, id: &str) {
+        self.ids.insert(id.to_string(), entity);
+    }
+
+    pub fn get_id(&self, entity: Entity) -> Option<&String> {
+        self.ids.get_by_right(&entity)
     }
 
     pub fn insert_element(&mut self, entity: Entity, element: &str) -> &mut Self {
@@ -2,7 +2,7 @@ use crate::state::style::*;
 use crate::State;
 use crate::{entity::Entity, BuildHandler, Builder, EventHandler, Propagation};
 
-use crate::{Event, WindowEvent};
+use crate::{CursorIcon, Event, WindowEvent};
 
 use crate::state::hierarchy::*;
 
@@ -33,7 +33,13 @@ pub trait PropSet {
 
     // Style
     fn set_element(self, state: &mut State, value: &str) -> Self;
+    // Sets the id, taking it from any other entity which has it so that the last entity given an
+    // id owns it in every build
     fn set_id(self, state: &mut State, value: &str) -> Self;
+    // Sets the id unless it is already used by another entity, in which case an error is returned
+    fn try_set_id(self, state: &mut State, value: &str) -> Result<Self, DuplicateIdError>
+    where
+        Self: Sized;
     fn set_class(self, state: &mut State, value: &str) -> Self;
 
     // Visibility
@@ -140,6 +146,8 @@ pub trait PropSet {
     fn set_next_focus(self, state: &mut State, value: Entity) -> Self;
     fn set_prev_focus(self, state: &mut State, value: Entity) -> Self;
     fn set_focus_order(self, state: &mut State, next: Entity, prev: Entity) -> Self;
+    fn set_tab_index(self, state: &mut State, value: i32) -> Self;
+    fn set_cursor(self, state: &mut State, value: CursorIcon) -> Self;
 
     fn mutate<F: FnMut(Builder) -> Builder>(self, state: &mut State, builder: F) -> Self;
 
@@ -392,11 +400,21 @@ impl PropSet for Entity {
     }
 
     fn set_id(self, state: &mut State, value: &str) -> Self {
-        state.style.insert_id(self, value);
+        state.style.replace_id(self, value);
+
+        state.insert_event(Event::new(WindowEvent::Restyle).target(Entity::root()));
 
         self
     }
 
+    fn try_set_id(self, state: &mut State, value: &str) -> Result<Self, DuplicateIdError> {
+        state.style.insert_id(self, value)?;
+
+        state.insert_event(Event::new(WindowEvent::Restyle).target(Entity::root()));
+
+        Ok(self)
+    }
+
     fn set_class(self, state: &mut State, value: &str) -> Self {
         state.style.insert_class(self, value);
 
@@ -1135,6 +1153,18 @@ impl PropSet for Entity {
 
         self
     }
+
+    fn set_tab_index(self, state: &mut State, value: i32) -> Self {
+        state.style.tab_index.insert(self, value);
+
+        self
+    }
+
+    fn set_cursor(self, state: &mut State, value: CursorIcon) -> Self {
+        state.style.cursor.insert(self, value);
+
+        self
+    }
 }
 
 pub trait PropGet {
@@ -6,6 +6,7 @@ use crate::style::color::Color;
 use crate::state::style::*;
 
 use crate::state::animation::Transition;
+use crate::CursorIcon;
 
 #[derive(Clone, Debug)]
 pub enum Property {
@@ -87,4 +88,8 @@ pub enum Property {
     Transition(Vec<Transition>),
 
     ZIndex(i32),
+
+    TabIndex(i32),
+
+    Cursor(CursorIcon),
 }
@@ -218,6 +218,15 @@ impl Selector {
         true
     }
 
+    // Returns true if the selector has no parts and so matches every entity
+    pub(crate) fn is_empty(&self) -> bool {
+        self.id.is_none()
+            && self.element.is_none()
+            && self.classes.is_empty()
+            && self.pseudo_classes.0 == 0
+            && !self.asterisk
+    }
+
     pub fn specificity(&self) -> Specificity {
         Specificity([
             if self.id.is_some() { 1 } else { 0 },
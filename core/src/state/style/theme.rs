@@ -16,6 +16,7 @@ use crate::state::style::StyleRule;
 use crate::state::style::*;
 
 use crate::state::style::color::Color;
+use crate::CursorIcon;
 
 #[derive(Clone, Debug)]
 pub enum CustomParseError {
@@ -168,11 +169,24 @@ fn parse_selectors<'i, 't>(
                 //     selec.relation = Relation::Parent;
                 // }
                 //selector.relation = Some(Box::new(SelectorRelation::Parent(old_selector)));
+
+                // Whitespace around the combinator is not a descendant combinator
+                first_token_in_selector = true;
+                whitespace = false;
+                continue;
             }
 
             // Id
             Token::IDHash(ref id_name) => {
-                selector.set_id(&id_name.to_string());
+                if whitespace {
+                    selector.relation = Relation::Ancestor;
+                    selectors.push(selector);
+                    selector = Selector::default();
+                    selector.set_id(&id_name.to_string());
+                } else {
+                    selector.set_id(&id_name.to_string());
+                }
+
                 whitespace = false;
             }
 
@@ -209,6 +223,11 @@ fn parse_selectors<'i, 't>(
             }
 
             Token::WhiteSpace(ref ws) => {
+                // Whitespace before the first token is not a descendant combinator
+                if first_token_in_selector {
+                    continue;
+                }
+
                 whitespace = true;
             }
 
@@ -246,6 +265,7 @@ fn parse_selectors<'i, 't>(
                 selectors.push(selector);
                 selector = Selector::default();
                 first_token_in_selector = true;
+                whitespace = false;
                 continue; // need to continue to avoid `first_token_in_selector` being set to false
             }
 
@@ -275,6 +295,45 @@ fn parse_selectors<'i, 't>(
     Ok(selectors)
 }
 
+// Parses a comma separated list of selectors, such as `.row > button, #save`, into groups of
+// selectors which can be matched against an entity
+pub(crate) fn parse_selector_groups(
+    selector: &str,
+) -> Result<Vec<Vec<Selector>>, SelectorParseError> {
+    let error = || SelectorParseError {
+        selector: selector.to_string(),
+    };
+
+    if selector.trim().is_empty() {
+        return Err(error());
+    }
+
+    let mut input = ParserInput::new(selector);
+    let mut parser = Parser::new(&mut input);
+
+    let selectors = parser
+        .parse_entirely(|input| parse_selectors(input))
+        .map_err(|_| error())?;
+
+    // A stray comma or combinator leaves an empty selector, which would match every entity
+    if selectors.iter().any(|selector| selector.is_empty()) {
+        return Err(error());
+    }
+
+    // Only the last selector of each group has no relation to the selector after it
+    let mut groups = Vec::new();
+    let mut group = Vec::new();
+    for selector in selectors.into_iter() {
+        let end_of_group = matches!(selector.relation, Relation::None);
+        group.push(selector);
+        if end_of_group {
+            groups.push(std::mem::replace(&mut group, Vec::new()));
+        }
+    }
+
+    Ok(groups)
+}
+
 // fn parse_selector<'i,'t>(input: &mut Parser<'i,'t>) -> Result<Selector, ParseError<'i, CustomParseError>> {
 //     let mut selector = Selector::default();
 
@@ -389,6 +448,9 @@ impl<'i> cssparser::DeclarationParser<'i> for DeclarationParser {
             }
 
             "z-index" => Property::ZIndex(parse_z_index(input)?),
+            "tab-index" => Property::TabIndex(parse_z_index(input)?),
+
+            "cursor" => Property::Cursor(parse_cursor(input)?),
 
             _ => {
                 let basic_error = BasicParseError {
@@ -820,6 +882,55 @@ fn parse_display<'i, 't>(
     })
 }
 
+fn parse_cursor<'i, 't>(
+    input: &mut Parser<'i, 't>,
+) -> Result<CursorIcon, ParseError<'i, CustomParseError>> {
+    let location = input.current_source_location();
+
+    Ok(match input.next()? {
+        Token::Ident(name) => match name.as_ref() {
+            "default" | "auto" => CursorIcon::Arrow,
+            "pointer" => CursorIcon::Hand,
+            "text" => CursorIcon::Text,
+            "crosshair" => CursorIcon::Crosshair,
+            "move" => CursorIcon::Move,
+            "grab" => CursorIcon::Grab,
+            "grabbing" => CursorIcon::Grabbing,
+            "not-allowed" => CursorIcon::NotAllowed,
+            "wait" => CursorIcon::Wait,
+            "help" => CursorIcon::Help,
+            "n-resize" => CursorIcon::NResize,
+            "e-resize" => CursorIcon::EResize,
+            "s-resize" => CursorIcon::SResize,
+            "w-resize" => CursorIcon::WResize,
+            "ne-resize" => CursorIcon::NeResize,
+            "nw-resize" => CursorIcon::NwResize,
+            "se-resize" => CursorIcon::SeResize,
+            "sw-resize" => CursorIcon::SwResize,
+            "ew-resize" => CursorIcon::EwResize,
+            "ns-resize" => CursorIcon::NsResize,
+            "nesw-resize" => CursorIcon::NeswResize,
+            "nwse-resize" => CursorIcon::NwseResize,
+            "col-resize" => CursorIcon::ColResize,
+            "row-resize" => CursorIcon::RowResize,
+
+            _ => {
+                return Err(
+                    CustomParseError::InvalidStringName(name.to_owned().to_string()).into(),
+                );
+            }
+        },
+
+        t => {
+            let basic_error = BasicParseError {
+                kind: BasicParseErrorKind::UnexpectedToken(t.to_owned()),
+                location,
+            };
+            return Err(basic_error.into());
+        }
+    })
+}
+
 fn parse_visibility<'i, 't>(
     input: &mut Parser<'i, 't>,
 ) -> Result<Visibility, ParseError<'i, CustomParseError>> {
@@ -1146,3 +1257,78 @@ pub fn parse(s: &str) -> Vec<StyleRule> {
 
     rules.into_iter().filter_map(|rule| rule.ok()).collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Checks the parts of a parsed selector and its relation to the selector after it
+    fn check(selector: &Selector, expected: Selector, relation: &str) {
+        assert_eq!(selector.id, expected.id);
+        assert_eq!(selector.element, expected.element);
+        assert_eq!(selector.classes, expected.classes);
+        assert_eq!(format!("{:?}", selector.relation), relation);
+    }
+
+    fn parse(selector: &str) -> Vec<Vec<Selector>> {
+        parse_selector_groups(selector).unwrap()
+    }
+
+    #[test]
+    fn child_combinator_ignores_whitespace() {
+        for selector in &["a > b", "a>b", "a >b", "a> b"] {
+            let groups = parse(selector);
+            assert_eq!(groups.len(), 1, "{}", selector);
+            assert_eq!(groups[0].len(), 2, "{}", selector);
+            check(&groups[0][0], Selector::from("a"), "Parent");
+            check(&groups[0][1], Selector::from("b"), "None");
+        }
+    }
+
+    #[test]
+    fn leading_and_trailing_whitespace_is_ignored() {
+        let groups = parse("  button  ");
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].len(), 1);
+        check(&groups[0][0], Selector::from("button"), "None");
+    }
+
+    #[test]
+    fn whitespace_is_descendant_combinator() {
+        let groups = parse(".row #save");
+        assert_eq!(groups[0].len(), 2);
+        check(&groups[0][0], Selector::new().class("row"), "Ancestor");
+        check(&groups[0][1], Selector::new().id("save"), "None");
+
+        let groups = parse("list .row button.primary");
+        assert_eq!(groups[0].len(), 3);
+        check(&groups[0][0], Selector::from("list"), "Ancestor");
+        check(&groups[0][1], Selector::new().class("row"), "Ancestor");
+        check(&groups[0][2], Selector::from("button").class("primary"), "None");
+    }
+
+    #[test]
+    fn comma_separates_groups() {
+        let groups = parse(".row > button, #save ,textbox");
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].len(), 2);
+        check(&groups[0][0], Selector::new().class("row"), "Parent");
+        check(&groups[0][1], Selector::from("button"), "None");
+        check(&groups[1][0], Selector::new().id("save"), "None");
+        check(&groups[2][0], Selector::from("textbox"), "None");
+    }
+
+    #[test]
+    fn invalid_selectors_are_errors() {
+        for selector in &["", "  ", "a >", "> a", "a,", ",a", "a,,b", "a {", "a + b", "."] {
+            assert_eq!(
+                parse_selector_groups(selector).err(),
+                Some(SelectorParseError {
+                    selector: selector.to_string()
+                }),
+                "{}",
+                selector
+            );
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use crate::entity::Entity;
+use crate::events::Event;
+
+use std::time::{Duration, Instant};
+
+/// A handle to a timer which can be used to cancel it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TimerHandle(u64);
+
+#[derive(Clone, Debug)]
+struct Timer {
+    handle: TimerHandle,
+    // The time at which the timer fires next
+    deadline: Instant,
+    // The time between repeats for an interval, None for a one-shot timer
+    interval: Option<Duration>,
+    event: Event,
+}
+
+/// Stores the timers of an application
+///
+/// Deadlines are measured from the time of the last call to `update`, which backends make at the
+/// start of each iteration of their event loop, rather than from the system clock. This keeps the
+/// timers added while handling events consistent with each other and allows the headless backend
+/// to drive the timers with a simulated clock.
+#[derive(Clone)]
+pub struct Timers {
+    time: Instant,
+    next_handle: u64,
+    timers: Vec<Timer>,
+}
+
+impl Timers {
+    pub fn new() -> Self {
+        Timers {
+            time: Instant::now(),
+            next_handle: 0,
+            timers: Vec::new(),
+        }
+    }
+
+    // The current time of the timers
+    pub fn time(&self) -> Instant {
+        self.time
+    }
+
+    pub fn add(&mut self, delay: Duration, interval: Option<Duration>, event: Event) -> TimerHandle {
+        let handle = TimerHandle(self.next_handle);
+        self.next_handle += 1;
+
+        // An interval of zero would fire on every update
+        let interval = interval.filter(|interval| *interval > Duration::from_secs(0));
+
+        self.timers.push(Timer {
+            handle,
+            deadline: self.time + delay,
+            interval,
+            event,
+        });
+
+        handle
+    }
+
+    // Returns true if the timer was still pending
+    pub fn cancel(&mut self, handle: TimerHandle) -> bool {
+        let len = self.timers.len();
+        self.timers.retain(|timer| timer.handle != handle);
+        self.timers.len() != len
+    }
+
+    pub fn is_pending(&self, handle: TimerHandle) -> bool {
+        self.timers.iter().any(|timer| timer.handle == handle)
+    }
+
+    // Removes the timers with events targeting any of the entities
+    pub fn remove_targets(&mut self, entities: &[Entity]) {
+        self.timers
+            .retain(|timer| !entities.contains(&timer.event.target));
+    }
+
+    // Returns the earliest deadline of the pending timers
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.timers.iter().map(|timer| timer.deadline).min()
+    }
+
+    // Advances the time of the timers and returns the events of the timers which have fired,
+    // in deadline order. Intervals which have fallen behind fire once and skip the missed repeats.
+    pub fn update(&mut self, now: Instant) -> Vec<Event> {
+        if now > self.time {
+            self.time = now;
+        }
+
+        let time = self.time;
+
+        let mut fired = Vec::new();
+        let mut index = 0;
+        while index < self.timers.len() {
+            let timer = &mut self.timers[index];
+            if timer.deadline > time {
+                index += 1;
+                continue;
+            }
+
+            fired.push((timer.deadline, timer.event.clone()));
+
+            if let Some(interval) = timer.interval {
+                while timer.deadline <= time {
+                    timer.deadline += interval;
+                }
+                index += 1;
+            } else {
+                self.timers.remove(index);
+            }
+        }
+
+        fired.sort_by_key(|(deadline, _)| *deadline);
+        fired.into_iter().map(|(_, event)| event).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Tick(u32);
+
+    fn tick(id: u32) -> Event {
+        Event::new(Tick(id)).target(Entity::root())
+    }
+
+    fn ticks(events: Vec<Event>) -> Vec<u32> {
+        events
+            .iter()
+            .filter_map(|event| event.message.as_any().downcast_ref::<Tick>())
+            .map(|tick| tick.0)
+            .collect()
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn fires_in_deadline_order() {
+        let mut timers = Timers::new();
+        let start = timers.time();
+
+        timers.add(ms(20), None, tick(2));
+        timers.add(ms(10), None, tick(1));
+        timers.add(ms(30), None, tick(3));
+
+        assert!(ticks(timers.update(start + ms(5))).is_empty());
+        assert_eq!(ticks(timers.update(start + ms(25))), vec![1, 2]);
+        assert_eq!(ticks(timers.update(start + ms(30))), vec![3]);
+        assert_eq!(timers.next_deadline(), None);
+    }
+
+    #[test]
+    fn one_shot_timer_fires_once() {
+        let mut timers = Timers::new();
+        let start = timers.time();
+
+        let handle = timers.add(ms(10), None, tick(1));
+        assert!(timers.is_pending(handle));
+
+        assert_eq!(ticks(timers.update(start + ms(10))), vec![1]);
+        assert!(!timers.is_pending(handle));
+        assert!(ticks(timers.update(start + ms(50))).is_empty());
+    }
+
+    #[test]
+    fn interval_skips_missed_repeats() {
+        let mut timers = Timers::new();
+        let start = timers.time();
+
+        timers.add(ms(10), Some(ms(10)), tick(1));
+
+        assert_eq!(ticks(timers.update(start + ms(10))), vec![1]);
+        assert_eq!(timers.next_deadline(), Some(start + ms(20)));
+
+        // Falling behind by several repeats fires once and keeps the original phase
+        assert_eq!(ticks(timers.update(start + ms(45))), vec![1]);
+        assert_eq!(timers.next_deadline(), Some(start + ms(50)));
+    }
+
+    #[test]
+    fn zero_interval_fires_once() {
+        let mut timers = Timers::new();
+        let start = timers.time();
+
+        let handle = timers.add(ms(0), Some(ms(0)), tick(1));
+
+        assert_eq!(ticks(timers.update(start)), vec![1]);
+        assert!(!timers.is_pending(handle));
+        assert!(ticks(timers.update(start + ms(10))).is_empty());
+    }
+
+    #[test]
+    fn time_does_not_go_backwards() {
+        let mut timers = Timers::new();
+        let start = timers.time();
+
+        timers.update(start + ms(20));
+        timers.add(ms(10), None, tick(1));
+
+        // The deadline is measured from the latest time, not the earlier time passed in
+        assert!(ticks(timers.update(start + ms(5))).is_empty());
+        assert_eq!(timers.next_deadline(), Some(start + ms(30)));
+    }
+
+    #[test]
+    fn cancel_stops_timer() {
+        let mut timers = Timers::new();
+        let start = timers.time();
+
+        let handle = timers.add(ms(10), Some(ms(10)), tick(1));
+        assert!(timers.cancel(handle));
+        assert!(!timers.cancel(handle));
+        assert!(ticks(timers.update(start + ms(20))).is_empty());
+    }
+
+    #[test]
+    fn remove_targets_drops_timers_of_removed_entities() {
+        let mut timers = Timers::new();
+        let start = timers.time();
+        let removed = Entity::new(1, 0);
+        let kept = Entity::new(2, 0);
+
+        timers.add(ms(10), None, Event::new(Tick(1)).target(removed));
+        timers.add(ms(10), Some(ms(10)), Event::new(Tick(2)).target(removed));
+        timers.add(ms(10), None, Event::new(Tick(3)).target(kept));
+
+        timers.remove_targets(&[removed]);
+
+        assert_eq!(ticks(timers.update(start + ms(10))), vec![3]);
+        assert_eq!(timers.next_deadline(), None);
+    }
+}
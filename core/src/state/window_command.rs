@@ -0,0 +1,52 @@
+use crate::entity::Entity;
+use crate::events::{Event, Propagation};
+use crate::state::State;
+use crate::window_event::{WindowCommand, WindowEvent};
+
+impl State {
+    /// Queues a change to the window, which is carried out by the backend after events have been
+    /// processed
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// state.window_command(WindowCommand::SetInnerSize(1280, 720));
+    /// state.window_command(WindowCommand::SetMinInnerSize(Some((640, 480))));
+    /// ```
+    pub fn window_command(&mut self, command: WindowCommand) {
+        self.window_commands.push(command);
+    }
+
+    /// Asks the window to close
+    ///
+    /// Every entity receives a `WindowEvent::WindowCloseRequested` event, and any of them can
+    /// consume it to keep the window open, for example to ask about unsaved changes first. If the
+    /// event is not consumed the window is closed. The glutin and winit backends call this when
+    /// the user closes the window. With baseview closing cannot be cancelled, so only
+    /// `WindowEvent::WindowClose` is sent.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // In on_event of a document widget
+    /// if let Some(WindowEvent::WindowCloseRequested) = event.message.downcast() {
+    ///     if self.modified {
+    ///         event.consume();
+    ///         // Open a dialog which sends WindowCommand::Close if changes are discarded
+    ///     }
+    /// }
+    /// ```
+    pub fn request_window_close(&mut self) {
+        self.insert_event(
+            Event::new(WindowEvent::WindowCloseRequested)
+                .target(Entity::root())
+                .propagate(Propagation::All)
+                .unique(),
+        );
+    }
+
+    /// Takes the queued window commands, used by the backends to apply them
+    pub fn take_window_commands(&mut self) -> Vec<WindowCommand> {
+        std::mem::take(&mut self.window_commands)
+    }
+}
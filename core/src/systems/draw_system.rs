@@ -1,9 +1,6 @@
 
 
-use femtovg::{
-    renderer::OpenGl, Align, Baseline, Canvas, FillRule, FontId, ImageFlags, ImageId, LineCap,
-    LineJoin, Paint, Path, Renderer, Solidity,
-};
+use crate::renderer::{Canvas, Paint, Path};
 
 // Returns true if the entity is visible and should be drawn
 pub fn is_visible(state: &mut State, entity: Entity) -> bool {
@@ -11,22 +8,22 @@ pub fn is_visible(state: &mut State, entity: Entity) -> bool {
 }
 
 // Draws a shadow based on the shadow style properties of the entity
-pub fn draw_shadow(state: &mut State, entity: Entity, canvas: Canvas<OpenGl>) {
+pub fn draw_shadow(state: &mut State, entity: Entity, canvas: &mut dyn Canvas) {
 
 }
 
 // Draws the widget with specified background and border
-pub fn draw_widget(state: &mut State, entity: Entity, canvas: Canvas<OpenGl>) {
+pub fn draw_widget(state: &mut State, entity: Entity, canvas: &mut dyn Canvas) {
 
 }
 
 // Draws the text based on style properties
-pub fn draw_text(state: &mut State, entity: Entity, canvas: Canvas<OpenGl>) {
+pub fn draw_text(state: &mut State, entity: Entity, canvas: &mut dyn Canvas) {
 
 }
 
 // Applies a clipping scissor to the widget
-pub fn apply_scissor(state: &mut State, entity: Entity, canvas: Canvas<OpenGl>) {
+pub fn apply_scissor(state: &mut State, entity: Entity, canvas: &mut dyn Canvas) {
 
 }
 
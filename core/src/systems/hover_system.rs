@@ -3,6 +3,73 @@
 
 use crate::{Entity, State, Event, WindowEvent, Length, Visibility};
 
+/// Returns the entity which would be hovered with the cursor at the specified position
+///
+/// This is the topmost visible and hoverable entity under the position, or the root if there
+/// is none. The hover state is not changed.
+pub fn entity_at(state: &State, cursorx: f32, cursory: f32) -> Entity {
+    let mut draw_hierarchy: Vec<Entity> = state.hierarchy.into_iter().collect();
+
+    draw_hierarchy.sort_by_cached_key(|entity| state.data.get_z_order(*entity));
+
+    draw_hierarchy
+        .into_iter()
+        .filter(|entity| is_under_cursor(state, *entity, cursorx, cursory))
+        .last()
+        .unwrap_or(Entity::root())
+}
+
+// Returns true if the entity is visible, hoverable and under the position
+fn is_under_cursor(state: &State, entity: Entity, cursorx: f32, cursory: f32) -> bool {
+    // Skip invisible widgets
+    if state.data.get_visibility(entity) == Visibility::Invisible {
+        return false;
+    }
+
+    // This shouldn't be here but there's a bug if it isn't
+    if state.data.get_opacity(entity) == 0.0 {
+        return false;
+    }
+
+    // Skip non-hoverable widgets
+    if state.data.get_hoverability(entity) != true {
+        return false;
+    }
+
+    let border_width = match state
+        .style
+        .border_width
+        .get(entity)
+        .cloned()
+        .unwrap_or_default()
+    {
+        Length::Pixels(val) => val,
+        //Length::Percentage(val) => parent_width * val,
+        _ => 0.0,
+    };
+
+    let posx = state.data.get_posx(entity) - (border_width / 2.0);
+    let posy = state.data.get_posy(entity) - (border_width / 2.0);
+    let width = state.data.get_width(entity) + (border_width);
+    let height = state.data.get_height(entity) + (border_width);
+
+    let clip_widget = state.data.get_clip_widget(entity);
+
+    let clip_posx = state.data.get_posx(clip_widget);
+    let clip_posy = state.data.get_posy(clip_widget);
+    let clip_width = state.data.get_width(clip_widget);
+    let clip_height = state.data.get_height(clip_widget);
+
+    cursorx >= posx
+        && cursorx >= clip_posx
+        && cursorx < (posx + width)
+        && cursorx < (clip_posx + clip_width)
+        && cursory >= posy
+        && cursory >= clip_posy
+        && cursory < (posy + height)
+        && cursory < (clip_posy + clip_height)
+}
+
 /// Determines the hovered entity based on the mouse cursor position
 pub fn apply_hover(state: &mut State) {
 
@@ -17,54 +84,7 @@ pub fn apply_hover(state: &mut State) {
     let mut hovered_widget = Entity::root();
 
     for entity in draw_hierarchy.into_iter() {
-        // Skip invisible widgets
-        if state.data.get_visibility(entity) == Visibility::Invisible {
-            continue;
-        }
-
-        // This shouldn't be here but there's a bug if it isn't
-        if state.data.get_opacity(entity) == 0.0 {
-            continue;
-        }
-
-        // Skip non-hoverable widgets
-        if state.data.get_hoverability(entity) != true {
-            continue;
-        }
-
-        let border_width = match state
-            .style
-            .border_width
-            .get(entity)
-            .cloned()
-            .unwrap_or_default()
-        {
-            Length::Pixels(val) => val,
-            //Length::Percentage(val) => parent_width * val,
-            _ => 0.0,
-        };
-
-        let posx = state.data.get_posx(entity) - (border_width / 2.0);
-        let posy = state.data.get_posy(entity) - (border_width / 2.0);
-        let width = state.data.get_width(entity) + (border_width);
-        let height = state.data.get_height(entity) + (border_width);
-
-        let clip_widget = state.data.get_clip_widget(entity);
-
-        let clip_posx = state.data.get_posx(clip_widget);
-        let clip_posy = state.data.get_posy(clip_widget);
-        let clip_width = state.data.get_width(clip_widget);
-        let clip_height = state.data.get_height(clip_widget);
-
-        if cursorx >= posx
-            && cursorx >= clip_posx
-            && cursorx < (posx + width)
-            && cursorx < (clip_posx + clip_width)
-            && cursory >= posy
-            && cursory >= clip_posy
-            && cursory < (posy + height)
-            && cursory < (clip_posy + clip_height)
-        {
+        if is_under_cursor(state, entity, cursorx, cursory) {
             hovered_widget = entity;
             if let Some(pseudo_classes) =
                 state.style.pseudo_classes.get_mut(hovered_widget)
@@ -133,6 +153,11 @@ pub fn apply_hover(state: &mut State) {
         state.hovered = hovered_widget;
         state.active = Entity::null();
 
+        // An entity which has captured the mouse keeps its cursor until it releases the mouse
+        if state.captured == Entity::null() {
+            state.reset_cursor();
+        }
+
         state.insert_event(Event::new(WindowEvent::Redraw).target(Entity::root()));
     }
 }
\ No newline at end of file
@@ -64,18 +64,71 @@ pub fn apply_visibility(state: &mut State, hierarchy: &Hierarchy) {
     }
 }
 
+// Returns true if the widget matches a list of selectors, such as the selectors of a style rule.
+// The selectors are checked from right to left, walking up the hierarchy for parent and ancestor
+// relations.
+pub(crate) fn check_selectors(
+    state: &State,
+    hierarchy: &Hierarchy,
+    entity: Entity,
+    selectors: &[Selector],
+) -> bool {
+    let mut relation_entity = entity;
+    // All the selectors need to match
+    'selector_loop: for selector in selectors.iter().rev() {
+        // Get the relation of the selector
+        match selector.relation {
+            Relation::None => {
+                if !check_match(state, entity, selector) {
+                    return false;
+                }
+            }
+
+            Relation::Parent => {
+                // Check if the parent matches the selector
+                if let Some(parent) = relation_entity.parent(hierarchy) {
+                    if !check_match(state, parent, selector) {
+                        return false;
+                    }
+
+                    relation_entity = parent;
+                } else {
+                    return false;
+                }
+            }
+
+            Relation::Ancestor => {
+                // Walk up the hierarchy
+                // If any of the ancestors match, move on to the next selector
+                for ancestor in relation_entity.parent_iter(hierarchy) {
+                    if ancestor == relation_entity {
+                        continue;
+                    }
+
+                    if check_match(state, ancestor, selector) {
+                        relation_entity = ancestor;
+
+                        continue 'selector_loop;
+                    }
+                }
+
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
 // Returns true if the widget matches the selector
-fn check_match(state: &State, entity: Entity, selector: &Selector) -> bool {
+pub(crate) fn check_match(state: &State, entity: Entity, selector: &Selector) -> bool {
     // Construct the entity selector
     let mut entity_selector = Selector::new();
 
     // Get the entity id from state
-    //entity_selector.id = state.style.ids.get(entity).cloned();
-    // let mut s = DefaultHasher::new();
-    // entity_selector.id = state.style.ids.get_by_right(&entity).map(|f| {
-    //     f.hash(&mut s);
-    //     s.finish()
-    // });
+    if let Some(id) = state.style.ids.get_by_right(&entity) {
+        entity_selector.set_id(id);
+    }
 
     // Get the entity element from state
     entity_selector.element = state.style.elements.get(entity).cloned();
@@ -114,58 +167,11 @@ pub fn apply_styles(state: &mut State, hierarchy: &Hierarchy) {
         let mut matched_rules: Vec<usize> = Vec::new();
 
         // Loop through all of the style rules
-        'rule_loop: for (index, selectors) in state.style.rule_selectors.iter().enumerate() {
-            let mut relation_entity = entity;
-            // Loop through selectors (Should be from right to left)
-            // All the selectors need to match for the rule to apply
-            'selector_loop: for rule_selector in selectors.iter().rev() {
-                // Get the relation of the selector
-                match rule_selector.relation {
-                    Relation::None => {
-                        if !check_match(state, entity, rule_selector) {
-                            continue 'rule_loop;
-                        }
-                    }
-
-                    Relation::Parent => {
-                        // Get the parent
-                        // Contrust the selector for the parent
-                        // Check if the parent selector matches the rule_seletor
-                        if let Some(parent) = relation_entity.parent(hierarchy) {
-                            if !check_match(state, parent, rule_selector) {
-                                continue 'rule_loop;
-                            }
-
-                            relation_entity = parent;
-                        } else {
-                            continue 'rule_loop;
-                        }
-                    }
-
-                    Relation::Ancestor => {
-                        // Walk up the hierarchy
-                        // Check if each entity matches the selector
-                        // If any of them match, move on to the next selector
-                        // If none of them do, move on to the next rule
-                        for ancestor in relation_entity.parent_iter(hierarchy) {
-                            if ancestor == relation_entity {
-                                continue;
-                            }
-
-                            if check_match(state, ancestor, rule_selector) {
-                                relation_entity = ancestor;
-
-                                continue 'selector_loop;
-                            }
-                        }
-
-                        continue 'rule_loop;
-                    }
-                }
-            }
-
+        for (index, selectors) in state.style.rule_selectors.iter().enumerate() {
             // If all the selectors match then add the rule to the matched rules list
-            matched_rules.push(index);
+            if check_selectors(state, hierarchy, entity, selectors) {
+                matched_rules.push(index);
+            }
         }
 
         //println!("Entity: {}, Matched Rules: {:?}", entity, &matched_rules);
@@ -192,6 +198,12 @@ pub fn apply_styles(state: &mut State, hierarchy: &Hierarchy) {
             should_redraw = true;
         }
 
+        // Only used for focus navigation
+        state.style.tab_index.link_rule(entity, &matched_rules);
+
+        // Applied when the entity is hovered
+        state.style.cursor.link_rule(entity, &matched_rules);
+
         // Currently doesn't do anything - TODO
         state.style.overflow.link_rule(entity, &matched_rules);
 
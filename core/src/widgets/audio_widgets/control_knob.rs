@@ -2,7 +2,7 @@
 
 use crate::entity::Entity;
 use crate::mouse::*;
-use crate::State;
+use crate::{PropSet, State};
 use crate::{BuildHandler, Event, EventHandler, WindowEvent};
 
 use crate::style::{Display, Visibility};
@@ -10,7 +10,7 @@ use crate::style::{Display, Visibility};
 use crate::widgets::slider::SliderEvent;
 use crate::widgets::Element;
 
-use femtovg::{renderer::OpenGl, Canvas, LineCap, Paint, Path, Solidity};
+use crate::renderer::{Canvas, LineCap, Paint, Path, Solidity};
 
 use std::sync::{Arc, Mutex};
 
@@ -95,6 +95,8 @@ impl BuildHandler for ControlKnob {
                 .class("tick")
         });
 
+        entity.set_tab_index(state, 0);
+
         state.style.insert_element(entity, "knob");
 
         entity
@@ -107,7 +109,9 @@ impl EventHandler for ControlKnob {
             match slider_event {
                 SliderEvent::SetValue(val) => {
                     if event.target == entity {
-                        if event.target == entity {
+                        // Values set from outside, such as by the host automating a parameter,
+                        // are ignored while the user is dragging the knob
+                        if !self.sliding {
                             self.value = ((*val).min(self.max)).max(self.min);
 
                             state.insert_event(
@@ -128,7 +132,7 @@ impl EventHandler for ControlKnob {
                         self.sliding = true;
                         self.mouse_down_posy = state.mouse.left.pos_down.1;
                         state.capture(entity);
-                        state.focused = entity;
+                        state.focus(entity);
                         self.temp = self.value;
                     }
                 }
@@ -215,48 +219,44 @@ impl EventHandler for ControlKnob {
         }
     }
 
-    fn on_draw(&mut self, state: &mut State, entity: Entity, canvas: &mut Canvas<OpenGl>) {
+    fn on_draw(&mut self, state: &mut State, entity: Entity, canvas: &mut dyn Canvas) {
         if state.data.get_visibility(entity) == Visibility::Invisible {
             return;
         }
 
         let opacity = state.data.get_opacity(entity);
 
-        let mut knob_color: femtovg::Color = state
+        let knob_color = state
             .style
             .background_color
             .get(entity)
             .cloned()
             .unwrap_or_default()
-            .into();
-        knob_color.set_alphaf(knob_color.a * opacity);
+            .with_opacity(opacity);
 
-        let mut back_color: femtovg::Color = state
+        let back_color = state
             .style
             .background_color
             .get(self.back)
             .cloned()
             .unwrap_or_default()
-            .into();
-        back_color.set_alphaf(back_color.a * opacity);
+            .with_opacity(opacity);
 
-        let mut slider_color: femtovg::Color = state
+        let slider_color = state
             .style
             .background_color
             .get(self.slider)
             .cloned()
             .unwrap_or_default()
-            .into();
-        slider_color.set_alphaf(slider_color.a * opacity);
+            .with_opacity(opacity);
 
-        let mut tick_color: femtovg::Color = state
+        let tick_color = state
             .style
             .background_color
             .get(self.tick)
             .cloned()
             .unwrap_or_default()
-            .into();
-        tick_color.set_alphaf(tick_color.a * opacity);
+            .with_opacity(opacity);
 
         let posx = state.data.get_posx(entity);
         let posy = state.data.get_posy(entity);
@@ -306,7 +306,7 @@ impl EventHandler for ControlKnob {
         let mut paint = Paint::color(back_color);
         paint.set_line_width(5.0);
         paint.set_line_cap(LineCap::Round);
-        canvas.stroke_path(&mut path, paint);
+        canvas.stroke_path(&path, &paint);
 
         if current != zero_position {
             let mut path = Path::new();
@@ -319,7 +319,7 @@ impl EventHandler for ControlKnob {
             let mut paint = Paint::color(slider_color);
             paint.set_line_width(5.0);
             paint.set_line_cap(LineCap::Round);
-            canvas.stroke_path(&mut path, paint);
+            canvas.stroke_path(&path, &paint);
         }
 
         // Draw outer arc fill
@@ -339,7 +339,7 @@ impl EventHandler for ControlKnob {
         let mut path = Path::new();
         path.circle(cx, cy, r0 + 1.0);
         let paint = Paint::color(knob_color);
-        canvas.fill_path(&mut path, paint);
+        canvas.fill_path(&path, &paint);
 
         // Draw knob tick
         canvas.save();
@@ -349,7 +349,7 @@ impl EventHandler for ControlKnob {
         let mut path = Path::new();
         path.circle(0.0, r0 - 2.5, 2.0);
         let paint = Paint::color(tick_color);
-        canvas.fill_path(&mut path, paint);
+        canvas.fill_path(&path, &paint);
 
         canvas.restore();
         canvas.restore();
@@ -2,7 +2,7 @@ use crate::entity::Entity;
 use crate::State;
 use crate::{BuildHandler, Event, EventHandler};
 
-use femtovg::{renderer::OpenGl, Canvas};
+use crate::renderer::Canvas;
 
 pub struct Grid {}
 
@@ -22,7 +22,7 @@ impl BuildHandler for Grid {
 impl EventHandler for Grid {
     fn on_event(&mut self, _state: &mut State, _entity: Entity, _event: &mut Event) {}
 
-    fn on_draw(&mut self, _state: &mut State, _entity: Entity, _canvas: &mut Canvas<OpenGl>) {
+    fn on_draw(&mut self, _state: &mut State, _entity: Entity, _canvas: &mut dyn Canvas) {
 
         // Draw background
 
@@ -1,6 +1,7 @@
 use crate::state::{Entity, State};
 
-use crate::events::{BuildHandler, Event, EventHandler, Propagation};
+use crate::events::{BuildHandler, Event, EventHandler, EventPhase, Propagation};
+use crate::{Code, WindowEvent};
 
 use crate::widgets::{ControlKnob, Label, SliderEvent, Textbox, TextboxEvent};
 
@@ -65,6 +66,7 @@ pub struct ValueKnob {
     init: f32,
     min: f32,
     max: f32,
+    value: f32,
 
     is_log: bool,
 
@@ -81,6 +83,7 @@ impl ValueKnob {
             init,
             min,
             max,
+            value: init,
 
             is_log: false,
 
@@ -159,6 +162,7 @@ impl EventHandler for ValueKnob {
                 SliderEvent::ValueChanged(val) => {
                     //println!("Slider Value Changed: {} {}", self.label, val);
                     if event.target == self.knob {
+                        self.value = *val;
                         // let val_str = format!("{:3}!", val);
                         let freq_val: FreqValue = (*val).into();
                         //println!("val_str: {} {}", self.label, val_str);
@@ -172,6 +176,7 @@ impl EventHandler for ValueKnob {
 
                 SliderEvent::SetValue(val) => {
                     if event.target == entity {
+                        self.value = (*val).min(self.max).max(self.min);
                         let freq_val: FreqValue = (*val).into();
                         //println!("val_str: {} {}", self.label, val_str);
                         state.insert_event(
@@ -190,6 +195,49 @@ impl EventHandler for ValueKnob {
             }
         }
 
+        // Copy and paste the value while the knob, or the textbox when it is not being edited, has
+        // focus. A pasted value is set as if the knob had been moved to it.
+        if event.phase != EventPhase::Capture {
+            if let Some(WindowEvent::KeyDown(code, _)) = event.message.downcast::<WindowEvent>() {
+                if state.modifiers.ctrl {
+                    match code {
+                        Code::KeyC => {
+                            let text = self.value.to_string();
+                            state.clipboard().set_text(&text);
+                        }
+
+                        Code::KeyV => {
+                            let pasted = state
+                                .clipboard()
+                                .get_text()
+                                .and_then(|text| text.trim().parse::<f32>().ok());
+
+                            if let Some(val) = pasted {
+                                let val = val.min(self.max).max(self.min);
+                                state.insert_event(
+                                    Event::new(SliderEvent::SetValue(val))
+                                        .target(entity)
+                                        .propagate(Propagation::Direct),
+                                );
+
+                                if let Some(on_change) = &self.on_change {
+                                    let mut event = (on_change.lock().unwrap())(val);
+                                    if !event.target {
+                                        event.target = entity;
+                                    }
+
+                                    event.origin = entity;
+                                    state.insert_event(event);
+                                }
+                            }
+                        }
+
+                        _ => {}
+                    }
+                }
+            }
+        }
+
         /*
         if let Some(textbox_event) = event.message.downcast::<TextboxEvent>() {
             match textbox_event {
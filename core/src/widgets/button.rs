@@ -61,7 +61,7 @@ impl BuildHandler for Button {
             entity.set_text(state, text);
         }
 
-        entity.set_element(state, "button")
+        entity.set_tab_index(state, 0).set_element(state, "button")
     }
 }
 
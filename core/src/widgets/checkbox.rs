@@ -98,7 +98,8 @@ impl BuildHandler for Checkbox {
         entity
             .set_font(state, "icons")
             .set_text_justify(state, Justify::Center)
-            .set_text_align(state, Align::Center);
+            .set_text_align(state, Align::Center)
+            .set_tab_index(state, 0);
 
         if self.checked {
             entity.set_checked(state, true);
@@ -51,6 +51,8 @@ impl EventHandler for VBox {}
 pub struct ResizableVBox {
     resizing: bool,
     previous_width: f32,
+    // True while the cursor is over the resize edge and showing the resize cursor
+    edge_hovered: bool,
 }
 
 impl ResizableVBox {
@@ -58,6 +60,7 @@ impl ResizableVBox {
         ResizableVBox {
             resizing: false,
             previous_width: 0.0,
+            edge_hovered: false,
         }
     }
 }
@@ -109,13 +112,15 @@ impl EventHandler for ResizableVBox {
                     }
                 }
 
-                // Occurs when the cursor leaves the entity
+                // Occurs when the cursor leaves the entity, which resets the cursor
                 WindowEvent::MouseOut => {
                     if !self.resizing {
-                        state.insert_event(Event::new(WindowEvent::SetCursor(CursorIcon::Arrow)));
+                        self.edge_hovered = false;
                     }
                 }
 
+                // While resizing the mouse is captured, which keeps the resize cursor. Otherwise the
+                // cursor depends on whether the mouse is near the edge.
                 WindowEvent::MouseMove(x, _) => {
                     if self.resizing {
                         let distx = *x - state.mouse.left.pos_down.0;
@@ -124,13 +129,14 @@ impl EventHandler for ResizableVBox {
                         if *x > state.data.get_posx(entity) + state.data.get_width(entity) - 4.0
                             && *x < state.data.get_posx(entity) + state.data.get_width(entity)
                         {
-                            // state.insert_event(Event::new(WindowEvent::SetCursor(
-                            //     CursorIcon::EResize,
-                            // )));
+                            self.edge_hovered = true;
+                            state.set_cursor(CursorIcon::EwResize);
                         } else {
-                            // state.insert_event(Event::new(WindowEvent::SetCursor(
-                            //     CursorIcon::Arrow,
-                            // )));
+                            if self.edge_hovered {
+                                self.edge_hovered = false;
+                                state.reset_cursor();
+                            }
+
                             state.release(entity);
                         }
                     }
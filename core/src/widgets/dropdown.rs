@@ -2,7 +2,9 @@
 
 use crate::entity::Entity;
 use crate::mouse::*;
-use crate::{AnimationState, BuildHandler, Event, EventHandler, Propagation, WindowEvent};
+use crate::{
+    AnimationState, BuildHandler, Event, EventHandler, EventPhase, Propagation, WindowEvent,
+};
 use crate::{PropSet, State};
 
 use crate::state::style::*;
@@ -277,8 +279,10 @@ impl EventHandler for Dropdown {
             match window_event {
                 WindowEvent::MouseDown(button) => match button {
                     MouseButton::Left => {
-                        if event.target == entity || event.target == self.header {
-            
+                        // Events targeting the header are handled once, on the way up
+                        if (event.target == entity || event.target == self.header)
+                            && event.phase != EventPhase::Capture
+                        {
                             //if state.hovered.is_child_of(&state.hierarchy, self.container) {
                             if state.hovered != entity {
                                 state.insert_event(
@@ -329,6 +333,7 @@ impl EventHandler for Dropdown {
                     MouseButton::Left => {
                         if (event.target == entity || event.target == self.header)
                             && event.origin != entity
+                            && event.phase != EventPhase::Capture
                         {
                             if state.mouse.left.pressed == state.hovered {
                                 if !self.open {
@@ -6,7 +6,7 @@ use crate::{BuildHandler, Event, EventHandler, HierarchyTree, Propagation, Windo
 use crate::{PropSet, State};
 
 use crate::state::style::*;
-use crate::widgets::Button;
+use crate::widgets::{Button, Label};
 
 use crate::state::hierarchy::IntoChildIterator;
 
@@ -361,3 +361,81 @@ impl EventHandler for Menu {
         }
     }
 }
+
+/// An item of a menu which sends an event when pressed and shows the shortcut for the event
+///
+/// The accelerator is looked up from the shortcuts of the state each time the menu opens, so
+/// the item shows the shortcut added with `State::add_shortcut` for an event with the same
+/// message.
+///
+/// # Examples
+///
+/// ```ignore
+/// let save = Event::new(AppEvent::Save);
+/// state.add_shortcut("Ctrl+S".parse().unwrap(), ShortcutScope::Global, save.clone())?;
+///
+/// // Shows "Save" with "Ctrl+S" on the right
+/// MenuItem::new("Save", save).build(state, menu, |builder| builder.class("item"));
+/// ```
+pub struct MenuItem {
+    text: String,
+    event: Event,
+    accelerator: Entity,
+    button: Button,
+}
+
+impl MenuItem {
+    pub fn new(text: &str, event: Event) -> Self {
+        MenuItem {
+            text: text.to_string(),
+            event: event.clone(),
+            accelerator: Entity::null(),
+            button: Button::new().on_press(event),
+        }
+    }
+
+    fn update_accelerator(&self, state: &mut State) {
+        let text = state
+            .find_shortcut(&self.event)
+            .map(|shortcut| shortcut.to_string())
+            .unwrap_or_default();
+
+        self.accelerator.set_text(state, &text);
+    }
+}
+
+impl BuildHandler for MenuItem {
+    type Ret = Entity;
+    fn on_build(&mut self, state: &mut State, entity: Entity) -> Self::Ret {
+        entity.set_flex_direction(state, FlexDirection::Row);
+
+        // The labels are not hoverable so that the menu forwards presses to the item
+        Label::new(&self.text).build(state, entity, |builder| {
+            builder
+                .set_flex_grow(1.0)
+                .set_hoverability(false)
+                .class("text")
+        });
+
+        self.accelerator = Label::new("").build(state, entity, |builder| {
+            builder
+                .set_text_justify(Justify::End)
+                .set_hoverability(false)
+                .class("accelerator")
+        });
+
+        self.update_accelerator(state);
+
+        entity.set_element(state, "menu_item")
+    }
+}
+
+impl EventHandler for MenuItem {
+    fn on_event(&mut self, state: &mut State, entity: Entity, event: &mut Event) {
+        self.button.on_event(state, entity, event);
+
+        if let Some(MenuEvent::Open(_)) = event.message.downcast::<MenuEvent>() {
+            self.update_accelerator(state);
+        }
+    }
+}
@@ -2,7 +2,7 @@
 
 use crate::entity::Entity;
 use crate::events::{BuildHandler, Event, EventHandler};
-use crate::{MouseButton, WindowEvent};
+use crate::{CursorIcon, MouseButton, WindowEvent};
 use crate::{PropSet, State};
 
 use crate::state::style::*;
@@ -64,7 +64,9 @@ impl Scrollbar {
 impl BuildHandler for Scrollbar {
     type Ret = Entity;
     fn on_build(&mut self, state: &mut State, entity: Entity) -> Self::Ret {
-        self.front = Button::new().build(state, entity, |builder| builder.class("front"));
+        self.front = Button::new().build(state, entity, |builder| {
+            builder.class("front").set_cursor(CursorIcon::Grab)
+        });
         match self.direction {
             ScrollDirection::Horizontal => {
                 // entity
@@ -224,6 +226,7 @@ impl EventHandler for Scrollbar {
                             .unwrap_or_default();
                         self.position = scroll.y;
                         state.capture(entity);
+                        state.set_cursor(CursorIcon::Grabbing);
                     }
                     _ => {}
                 },
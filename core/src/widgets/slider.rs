@@ -101,7 +101,7 @@ impl EventHandler for ProgressBar {
                             self.pressed_x = state.mouse.cursorx;
                             //state.captured = entity;
                             state.capture(entity);
-                            state.focused = entity;
+                            state.focus(entity);
 
                             let dx = (self.pressed_x - state.data.get_posx(entity))
                                 / state.data.get_width(entity);
@@ -273,12 +273,19 @@ impl Slider {
         self.div = val;
         self
     }
+
+    /// The current value of the slider
+    pub fn value(&self) -> f32 {
+        self.value
+    }
 }
 
 impl BuildHandler for Slider {
     type Ret = Entity;
     fn on_build(&mut self, state: &mut State, entity: Entity) -> Self::Ret {
-        entity.set_flex_direction(state, FlexDirection::Row);
+        entity
+            .set_flex_direction(state, FlexDirection::Row)
+            .set_tab_index(state, 0);
         // .set_width(state, Length::Pixels(100.0))
         // .set_height(state, Length::Pixels(4.0));
         //.set_align_items(state, AlignItems::Center)
@@ -343,6 +350,7 @@ impl EventHandler for Slider {
                         let nx = (dx - thumb_width / 2.0) / (width - thumb_width);
 
                         let v = self.min + nx * (self.max - self.min);
+                        self.value = v;
 
                         self.active.set_width(state, Length::Percentage(nx));
                         self.thumb
@@ -390,6 +398,7 @@ impl EventHandler for Slider {
                         let nx = (dx - thumb_width / 2.0) / (width - thumb_width);
 
                         let v = self.min + nx * (self.max - self.min);
+                        self.value = v;
 
                         self.active.set_width(state, Length::Percentage(nx));
                         //self.thumb.set_left(state, Length::Pixels(dx - thumb_width/2.0));
@@ -6,6 +6,8 @@ use crate::{CheckboxEvent, Entity, HierarchyTree, MouseButton, Propagation, Radi
 
 use crate::events::{BuildHandler, Event, EventHandler};
 
+use crate::state::DragEvent;
+
 use crate::widgets::Element;
 
 use crate::widgets::*;
@@ -412,28 +414,11 @@ impl EventHandler for MovableTab {
 
                         entity.set_position(state, Position::Absolute);
                         entity.set_z_order(state, 10);
-                        state.capture(entity);
+                        state.start_drag(entity, entity);
                         state.insert_event(Event::new(MovableTabEvent::StartMove(entity)).target(entity));
                     }
                 }
 
-                WindowEvent::MouseUp(button) => {
-                    if *button == MouseButton::Left {
-                        self.moving = false;
-                        entity.set_height(state, self.previous_height);
-                        entity.set_width(state, self.previous_width);
-                        entity.set_position(state, Position::Relative);
-                        state.data.set_hoverability(entity, true);
-                        entity.set_left(state, Length::Auto);
-                        entity.set_top(state, Length::Auto);
-                        entity.set_z_order(state, 0);
-                        state.release(entity);
-                        entity.set_left(state, Length::Auto);
-                        entity.set_top(state, Length::Auto);
-                        state.insert_event(Event::new(MovableTabEvent::StopMove(entity)).target(entity));
-                    }
-                }
-
                 WindowEvent::MouseMove(x,y) => {
                     if self.moving {
 
@@ -443,17 +428,26 @@ impl EventHandler for MovableTab {
 
                         entity.set_left(state, Length::Pixels(self.pos_down_x - parent_posx + (*x - state.mouse.left.pos_down.0)));
                         entity.set_top(state, Length::Pixels(self.pos_down_y - parent_posy + (*y - state.mouse.left.pos_down.1)));
+                    }
+                }
 
-                        state.insert_event(Event::new(WindowEvent::MouseMove(*x,*y)).target(state.hovered));
+                _=> {}
+            }
+        }
 
-                    } else {
-                        //println!("Entity Hovered: {}", entity);
-                        if *x >= state.data.get_posx(entity) + state.data.get_width(entity)/2.0 {
+        if let Some(drag_event) = event.message.downcast::<DragEvent>() {
+            match drag_event {
+                // Another tab of the same tab bar is being dragged over this one
+                DragEvent::DragOver(data) => {
+                    if data.source != entity
+                        && data.has_payload::<Entity>()
+                        && state.hierarchy.get_parent(data.source) == state.hierarchy.get_parent(entity)
+                    {
+                        if data.x >= state.data.get_posx(entity) + state.data.get_width(entity)/2.0 {
                             if self.position_state {
                                 self.position_state = false;
                                 state.insert_event(Event::new(MovableTabEvent::Switch(self.position_state)).target(entity));
                             }
-                            
                         } else {
                             if !self.position_state {
                                 self.position_state = true;
@@ -463,8 +457,22 @@ impl EventHandler for MovableTab {
                     }
                 }
 
+                DragEvent::DragEnd(_) => {
+                    if event.target == entity && self.moving {
+                        self.moving = false;
+                        entity.set_height(state, self.previous_height);
+                        entity.set_width(state, self.previous_width);
+                        entity.set_position(state, Position::Relative);
+                        state.data.set_hoverability(entity, true);
+                        entity.set_left(state, Length::Auto);
+                        entity.set_top(state, Length::Auto);
+                        entity.set_z_order(state, 0);
+                        state.insert_event(Event::new(MovableTabEvent::StopMove(entity)).target(entity));
+                    }
+                }
+
                 _=> {}
             }
         }
     }
-}
\ No newline at end of file
+}
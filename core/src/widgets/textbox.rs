@@ -2,11 +2,14 @@
 
 use crate::entity::Entity;
 use crate::events::*;
-use crate::{BuildHandler, Justify, Length, PropSet, State, Visibility, WindowEvent};
+use crate::{
+    BuildHandler, CaretRect, CursorIcon, Justify, Length, PropSet, State, Visibility, WindowEvent,
+};
 
-use femtovg::{renderer::OpenGl, Align, Baseline, Canvas, Color, Paint, Path};
+use crate::renderer::{Canvas, Paint, Path, TextAlign, TextBaseline};
+use crate::style::Color;
 
-use crate::Key;
+use crate::{Code, Key};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum TextboxEvent {
@@ -21,6 +24,7 @@ pub struct Textbox {
     entity: Entity,
     text: String,
 
+    // The text when editing started, which is restored if editing is cancelled
     buffer: String,
 
     units: String,
@@ -32,6 +36,10 @@ pub struct Textbox {
     hitx: f32,
     dragx: f32,
 
+    // The text being composed by an input method and the byte range of its cursor
+    preedit: String,
+    preedit_cursor: Option<(usize, usize)>,
+
     // Events
     on_change: Option<Box<dyn Fn(&str) -> Event + Send>>,
     on_submit: Option<Box<dyn Fn(&str) -> Event + Send>>,
@@ -59,6 +67,9 @@ impl Textbox {
             hitx: -1.0,
             dragx: -1.0,
 
+            preedit: String::new(),
+            preedit_cursor: None,
+
             on_change: None,
             on_submit: None,
         }
@@ -79,6 +90,56 @@ impl Textbox {
         self
     }
 
+    /// The text shown in the textbox, including any units, which is updated on every edit
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns true while the text is being edited
+    pub fn is_editing(&self) -> bool {
+        self.edit
+    }
+
+    // Returns the start and end of the selection, which is empty if they are the same
+    fn selection(&self) -> (usize, usize) {
+        let start = std::cmp::min(self.select_pos, self.cursor_pos) as usize;
+        let end = std::cmp::max(self.select_pos, self.cursor_pos) as usize;
+        (start, end)
+    }
+
+    // Replaces the selection with the text and moves the cursor to the end of it
+    fn replace_selection(&mut self, state: &mut State, entity: Entity, text: &str) {
+        let (start, end) = self.selection();
+
+        if let Some(txt) = state.style.text.get_mut(entity) {
+            txt.text.replace_range(start..end, text);
+        }
+
+        self.cursor_pos = (start + text.len()) as u32;
+        self.select_pos = self.cursor_pos;
+
+        self.text_changed(state, entity);
+    }
+
+    // Keeps the text of the widget in sync with the edited text and sends the change event
+    fn text_changed(&mut self, state: &mut State, entity: Entity) {
+        if let Some(txt) = state.style.text.get(entity) {
+            self.text = txt.text.clone();
+
+            if let Some(on_change) = &self.on_change {
+                let mut event = (on_change)(&self.text);
+
+                if !event.target {
+                    event.target = entity;
+                }
+
+                event.origin = entity;
+
+                state.insert_event(event);
+            }
+        }
+    }
+
     // pub fn set_enabled(&self, state: &mut WidgetState, val: bool) {
     //     if val {
     //         self.id
@@ -93,7 +154,12 @@ impl Textbox {
 impl BuildHandler for Textbox {
     type Ret = Entity;
     fn on_build(&mut self, state: &mut State, entity: Entity) -> Self::Ret {
-        entity.set_text(state, &(self.text.to_owned() + &self.units));
+        self.text.push_str(&self.units);
+
+        entity
+            .set_text(state, &self.text)
+            .set_tab_index(state, 0)
+            .set_cursor(state, CursorIcon::Text);
 
         self.entity = entity;
 
@@ -109,7 +175,8 @@ impl EventHandler for Textbox {
             match textbox_event {
                 TextboxEvent::SetValue(val) => {
                     if event.target == entity {
-                        entity.set_text(state, &(val.to_owned() + &self.units));
+                        self.text = val.to_owned() + &self.units;
+                        entity.set_text(state, &self.text);
 
                         // state.insert_event(
                         //     Event::new(WindowEvent::Restyle).target(Entity::new(0, 0)),
@@ -150,7 +217,9 @@ impl EventHandler for Textbox {
                             self.cursor_pos = text_data.text.len() as u32;
                             self.select_pos = 0;
                             self.buffer = text_data.text.clone();
-                            state.focused = entity;
+                            self.preedit.clear();
+                            self.preedit_cursor = None;
+                            state.focus(entity);
                             //state.captured = entity;
                             state.capture(entity);
                             //self.edit = true;
@@ -191,7 +260,7 @@ impl EventHandler for Textbox {
                         }
 
                         if state.focused == entity {
-                            state.focused = Entity::root();
+                            state.focus(Entity::root());
                         }
 
                         //state.captured = Entity::null();
@@ -203,14 +272,64 @@ impl EventHandler for Textbox {
                     self.hitx = -1.0;
                 }
 
-                WindowEvent::KeyDown(_, key) => {
+                WindowEvent::KeyDown(code, key) => {
                     //println!("Code: {:?} Key: {:?}", code, key);
+                    // Clipboard shortcuts are consumed so that a containing widget, such as a
+                    // vector edit, does not also handle them
+                    if self.edit && state.modifiers.ctrl {
+                        let (start, end) = self.selection();
+                        let handled = match code {
+                            // Select all
+                            Code::KeyA => {
+                                self.select_pos = 0;
+                                self.cursor_pos = text_data.text.len() as u32;
+                                true
+                            }
+
+                            // Copy and cut
+                            Code::KeyC | Code::KeyX => {
+                                if start != end {
+                                    if let Some(selected) = text_data.text.get(start..end) {
+                                        state.clipboard().set_text(selected);
+                                    }
+
+                                    if *code == Code::KeyX {
+                                        self.replace_selection(state, entity, "");
+                                    }
+                                }
+                                true
+                            }
+
+                            // Paste, without line breaks or other control characters
+                            Code::KeyV => {
+                                if let Some(text) = state.clipboard().get_text() {
+                                    let text = text
+                                        .chars()
+                                        .filter(|c| !c.is_control())
+                                        .collect::<String>();
+                                    self.replace_selection(state, entity, &text);
+                                }
+                                true
+                            }
+
+                            _ => false,
+                        };
+
+                        if handled {
+                            state.insert_event(
+                                Event::new(WindowEvent::Redraw).target(Entity::root()),
+                            );
+                            event.consume();
+                            return;
+                        }
+                    }
+
                     if *key == Some(Key::ArrowLeft) {
                         if self.edit {
                             self.hitx = -1.0;
-                            if self.cursor_pos > 0 {
-                                self.cursor_pos -= 1;
-                            }
+                            self.cursor_pos =
+                                prev_char_boundary(&text_data.text, self.cursor_pos as usize)
+                                    as u32;
                             if !state.modifiers.shift {
                                 self.select_pos = self.cursor_pos;
                             }
@@ -228,9 +347,9 @@ impl EventHandler for Textbox {
                     if *key == Some(Key::ArrowRight) {
                         if self.edit {
                             self.hitx = -1.0;
-                            if self.cursor_pos < text_data.text.len() as u32 {
-                                self.cursor_pos += 1;
-                            }
+                            self.cursor_pos =
+                                next_char_boundary(&text_data.text, self.cursor_pos as usize)
+                                    as u32;
                             if !state.modifiers.shift {
                                 self.select_pos = self.cursor_pos;
                             }
@@ -251,12 +370,13 @@ impl EventHandler for Textbox {
                             //let start = text_data.select_pos as usize;
                             //let end = text_data.cursor_pos as usize;
                             if start == end && self.cursor_pos > 0 {
+                                let prev = prev_char_boundary(&text_data.text, start);
                                 if let Some(txt) = state.style.text.get_mut(entity) {
-                                    txt.text.remove((self.cursor_pos - 1) as usize);
+                                    txt.text.replace_range(prev..start, "");
                                 }
 
-                                self.cursor_pos -= 1;
-                                self.select_pos -= 1;
+                                self.cursor_pos = prev as u32;
+                                self.select_pos = prev as u32;
                             } else {
                                 if let Some(txt) = state.style.text.get_mut(entity) {
                                     txt.text.replace_range(start..end, "");
@@ -269,19 +389,7 @@ impl EventHandler for Textbox {
                             //     Event::new(WindowEvent::Restyle).target(Entity::new(0, 0)),
                             // );
 
-                            if let Some(txt) = state.style.text.get(entity) {
-                                if let Some(on_change) = &self.on_change {
-                                    let mut event = (on_change)(&txt.text);
-
-                                    if !event.target {
-                                        event.target = entity;
-                                    }
-
-                                    event.origin = entity;
-
-                                    state.insert_event(event);
-                                }
-                            }
+                            self.text_changed(state, entity);
 
                             state.insert_event(
                                 Event::new(WindowEvent::Redraw).target(Entity::root()),
@@ -298,7 +406,7 @@ impl EventHandler for Textbox {
 
                             self.edit = false;
                             entity.set_active(state, false);
-                            state.focused = Entity::root();
+                            state.focus(Entity::root());
                             state.captured = Entity::null();
 
                             // state.insert_event(
@@ -312,7 +420,11 @@ impl EventHandler for Textbox {
                     }
                     if *key == Some(Key::Escape) {
                         if self.edit {
-                            self.text = self.buffer.clone();
+                            let buffer = self.buffer.clone();
+                            entity.set_text(state, &buffer);
+                            self.cursor_pos = buffer.len() as u32;
+                            self.select_pos = self.cursor_pos;
+                            self.text_changed(state, entity);
                             self.edit = false;
                             entity.set_active(state, false);
 
@@ -328,7 +440,8 @@ impl EventHandler for Textbox {
                 }
 
                 WindowEvent::CharInput(input) => {
-                    if *input as u8 != 8 && *input as u8 != 13 {
+                    // Control characters, such as those sent with Ctrl+C on some platforms, are not inserted
+                    if !input.is_control() {
                         if self.edit {
                             let start = std::cmp::min(self.select_pos, self.cursor_pos) as usize;
                             let end = std::cmp::max(self.select_pos, self.cursor_pos) as usize;
@@ -340,29 +453,17 @@ impl EventHandler for Textbox {
                                 }
 
                                 //text_data.text.remove((text_data.cursor_pos - 1) as usize);
-                                self.cursor_pos += 1;
-                                self.select_pos += 1;
+                                self.cursor_pos += input.len_utf8() as u32;
+                                self.select_pos += input.len_utf8() as u32;
                             } else {
                                 if let Some(txt) = state.style.text.get_mut(entity) {
                                     txt.text.replace_range(start..end, &input.to_string());
                                 }
-                                self.cursor_pos = (start + 1) as u32;
-                                self.select_pos = (start + 1) as u32;
+                                self.cursor_pos = (start + input.len_utf8()) as u32;
+                                self.select_pos = (start + input.len_utf8()) as u32;
                             }
 
-                            if let Some(txt) = state.style.text.get(entity) {
-                                if let Some(on_change) = &self.on_change {
-                                    let mut event = (on_change)(&txt.text);
-
-                                    if !event.target {
-                                        event.target = entity;
-                                    }
-
-                                    event.origin = entity;
-
-                                    state.insert_event(event);
-                                }
-                            }
+                            self.text_changed(state, entity);
 
                             // state.insert_event(
                             //     Event::new(WindowEvent::Restyle).target(Entity::new(0, 0)),
@@ -375,6 +476,28 @@ impl EventHandler for Textbox {
                     }
                 }
 
+
+                // The composition text is drawn at the selection, which it replaces when committed
+                WindowEvent::ImePreedit { text, cursor_range } => {
+                    if self.edit {
+                        self.preedit = text.clone();
+                        self.preedit_cursor = *cursor_range;
+
+                        state.insert_event(Event::new(WindowEvent::Redraw).target(Entity::root()));
+                    }
+                }
+
+                WindowEvent::ImeCommit(text) => {
+                    if self.edit {
+                        self.preedit.clear();
+                        self.preedit_cursor = None;
+
+                        let text = text.chars().filter(|c| !c.is_control()).collect::<String>();
+                        self.replace_selection(state, entity, &text);
+
+                        state.insert_event(Event::new(WindowEvent::Redraw).target(Entity::root()));
+                    }
+                }
                 _ => {}
             }
         }
@@ -384,7 +507,7 @@ impl EventHandler for Textbox {
         &mut self,
         state: &mut State,
         entity: Entity,
-        canvas: &mut Canvas<OpenGl>,
+        canvas: &mut dyn Canvas,
         //images: &HashMap<String, nanovg::Image>,
     ) {
         // Skip window
@@ -528,14 +651,11 @@ impl EventHandler for Textbox {
 
         let opacity = state.data.get_opacity(entity);
 
-        let mut background_color: femtovg::Color = background_color.into();
-        background_color.set_alphaf(background_color.a * opacity);
+        let background_color = background_color.with_opacity(opacity);
 
-        let mut border_color: femtovg::Color = border_color.into();
-        border_color.set_alphaf(border_color.a * opacity);
+        let border_color = border_color.with_opacity(opacity);
 
-        let mut shadow_color: femtovg::Color = shadow_color.into();
-        shadow_color.set_alphaf(shadow_color.a * opacity);
+        let shadow_color = shadow_color.with_opacity(opacity);
 
         let border_width = match state
             .style
@@ -610,17 +730,19 @@ impl EventHandler for Textbox {
             border_radius_bottom_left,
         );
         let paint = Paint::color(background_color);
-        canvas.fill_path(&mut path, paint);
+        canvas.fill_path(&path, &paint);
 
         // Draw border
         let mut paint = Paint::color(border_color);
         paint.set_line_width(border_width);
         //paint.set_anti_alias(false);
-        canvas.stroke_path(&mut path, paint);
+        canvas.stroke_path(&path, &paint);
         //println!("posx: {}", posx);
 
-        let mut font_color: femtovg::Color = font_color.into();
-        font_color.set_alphaf(font_color.a * opacity);
+        let font_color = font_color.with_opacity(opacity);
+
+        // The caret is reported after drawing, to position the candidate window of an input method
+        let mut caret_rect = None;
 
         if let Some(text) = state.style.text.get_mut(entity) {
             let font_id = match text.font.as_ref() {
@@ -632,7 +754,19 @@ impl EventHandler for Textbox {
             let mut x = posx;
             let mut y = posy;
 
-            let text_string = text.text.to_owned();
+            // While an input method is composing, its text is shown in place of the selection
+            let composing = self.edit && !self.preedit.is_empty();
+            let (start, end) = self.selection();
+            let text_string = if composing {
+                format!(
+                    "{}{}{}",
+                    text.text.get(..start).unwrap_or_default(),
+                    self.preedit,
+                    text.text.get(end..).unwrap_or_default()
+                )
+            } else {
+                text.text.to_owned()
+            };
 
             let text_align = state
                 .style
@@ -650,30 +784,30 @@ impl EventHandler for Textbox {
             let align = match text_justify {
                 Justify::Start => {
                     x += padding_left;
-                    Align::Left
+                    TextAlign::Left
                 }
                 Justify::Center => {
                     x += 0.5 * width;
-                    Align::Center
+                    TextAlign::Center
                 }
                 Justify::End => {
                     x += width - padding_right;
-                    Align::Right
+                    TextAlign::Right
                 }
             };
 
             let baseline = match text_align {
                 crate::Align::Start => {
                     y += padding_top;
-                    Baseline::Top
+                    TextBaseline::Top
                 }
                 crate::Align::Center => {
                     y += 0.5 * height;
-                    Baseline::Middle
+                    TextBaseline::Middle
                 }
                 crate::Align::End => {
                     y += height - padding_bottom;
-                    Baseline::Bottom
+                    TextBaseline::Bottom
                 }
             };
 
@@ -681,15 +815,15 @@ impl EventHandler for Textbox {
 
             let mut paint = Paint::color(font_color);
             paint.set_font_size(font_size);
-            paint.set_font(&[font_id]);
+            paint.set_font(font_id);
             paint.set_text_align(align);
             paint.set_text_baseline(baseline);
 
             let font_metrics = canvas
-                .measure_font(paint)
+                .measure_font(&paint)
                 .expect("Failed to read font metrics");
 
-            if let Ok(res) = canvas.fill_text(x, y, &text_string, paint) {
+            if let Some(res) = canvas.fill_text(x, y, &text_string, &paint) {
                 let text_width = res.width();
                 //let mut glyph_positions = res.glyphs.iter().peekable();
 
@@ -697,7 +831,64 @@ impl EventHandler for Textbox {
 
                 let mut selectx = caretx;
 
-                if self.edit {
+                if composing {
+                    let startx = res.glyphs.first().map_or(x, |glyph| glyph.x);
+                    let endx = startx + text_width;
+                    let glyph_x = |byte_index: usize| {
+                        res.glyphs
+                            .iter()
+                            .find(|glyph| glyph.byte_index >= byte_index)
+                            .map_or(endx, |glyph| glyph.x)
+                    };
+
+                    // Underline the composition text
+                    let preedit_startx = glyph_x(start);
+                    let preedit_endx = glyph_x(start + self.preedit.len());
+                    let mut path = Path::new();
+                    path.rect(
+                        preedit_startx,
+                        y + font_metrics.height() / 2.0 - 1.0,
+                        preedit_endx - preedit_startx,
+                        1.0,
+                    );
+                    canvas.fill_path(&path, &Paint::color(font_color));
+
+                    // Highlight the part being converted, if the input method shows one
+                    if let Some((cursor_start, cursor_end)) = self.preedit_cursor {
+                        let cursor_startx = glyph_x(start + cursor_start);
+                        let cursor_endx = glyph_x(start + cursor_end);
+                        if cursor_endx > cursor_startx {
+                            let mut path = Path::new();
+                            path.rect(
+                                cursor_startx,
+                                y - font_metrics.height() / 2.0,
+                                cursor_endx - cursor_startx,
+                                font_metrics.height(),
+                            );
+                            canvas.fill_path(&path, &Paint::color(Color::rgba(0, 0, 0, 64)));
+                        }
+
+                        caretx = cursor_startx;
+                    } else {
+                        caretx = preedit_endx;
+                    }
+
+                    let mut path = Path::new();
+                    path.rect(
+                        caretx.floor(),
+                        y - font_metrics.height() / 2.0,
+                        1.0,
+                        font_metrics.height(),
+                    );
+                    canvas.fill_path(&path, &Paint::color(Color::rgba(247, 76, 0, 255)));
+
+                    caret_rect = Some(CaretRect::new(
+                        caretx.floor(),
+                        y - font_metrics.height() / 2.0,
+                        1.0,
+                        font_metrics.height(),
+                    ));
+                } else if self.edit {
                     let startx = if let Some(first_glyph) = res.glyphs.first() {
                         first_glyph.x
                     } else {
@@ -725,7 +916,6 @@ impl EventHandler for Textbox {
                             endx
                         };
 
-                        let mut n = 0;
                         let mut px = x + padding_left;
 
                         for glyph in res.glyphs.iter() {
@@ -756,33 +946,28 @@ impl EventHandler for Textbox {
                             if self.hitx >= px && self.hitx < gx {
                                 selectx = left_edge;
 
-                                self.select_pos = n;
+                                self.select_pos = glyph.byte_index as u32;
                             }
 
                             if self.dragx >= px && self.dragx < gx {
                                 caretx = left_edge;
 
-                                self.cursor_pos = n;
+                                self.cursor_pos = glyph.byte_index as u32;
                             }
 
                             px = gx;
-                            n += 1;
                         }
                     } else {
-                        let mut n = 0;
-
                         //let mut start_x = 0.0;
 
                         for glyph in res.glyphs.iter() {
-                            if n == self.cursor_pos {
+                            if glyph.byte_index == self.cursor_pos as usize {
                                 caretx = glyph.x;
                             }
 
-                            if n == self.select_pos {
+                            if glyph.byte_index == self.select_pos as usize {
                                 selectx = glyph.x;
                             }
-
-                            n += 1;
                         }
 
                         if self.cursor_pos as usize == text.text.len() && text.text.len() != 0 {
@@ -804,7 +989,7 @@ impl EventHandler for Textbox {
                             select_width,
                             font_metrics.height(),
                         );
-                        canvas.fill_path(&mut path, Paint::color(Color::rgba(0, 0, 0, 64)));
+                        canvas.fill_path(&path, &Paint::color(Color::rgba(0, 0, 0, 64)));
                     } else if caretx > selectx {
                         let mut path = Path::new();
                         path.rect(
@@ -813,7 +998,7 @@ impl EventHandler for Textbox {
                             select_width,
                             font_metrics.height(),
                         );
-                        canvas.fill_path(&mut path, Paint::color(Color::rgba(0, 0, 0, 64)));
+                        canvas.fill_path(&path, &Paint::color(Color::rgba(0, 0, 0, 64)));
                     }
 
                     let mut path = Path::new();
@@ -823,7 +1008,14 @@ impl EventHandler for Textbox {
                         1.0,
                         font_metrics.height(),
                     );
-                    canvas.fill_path(&mut path, Paint::color(Color::rgba(247, 76, 0, 255)));
+                    canvas.fill_path(&path, &Paint::color(Color::rgba(247, 76, 0, 255)));
+
+                    caret_rect = Some(CaretRect::new(
+                        caretx.floor(),
+                        y - font_metrics.height() / 2.0,
+                        1.0,
+                        font_metrics.height(),
+                    ));
 
                     // let mut path = Path::new();
                     // path.rect(endx, y - 0.25 * height, 1.0, height * 0.5);
@@ -831,5 +1023,24 @@ impl EventHandler for Textbox {
                 }
             }
         }
+
+        match caret_rect {
+            Some(rect) => state.set_caret_rect(entity, rect),
+            None => state.clear_caret_rect(entity),
+        }
     }
 }
+
+// Returns the byte index of the character before the index, or zero
+fn prev_char_boundary(text: &str, index: usize) -> usize {
+    text.get(..index)
+        .and_then(|before| before.char_indices().next_back())
+        .map_or(0, |(boundary, _)| boundary)
+}
+
+// Returns the byte index of the character after the index, or the length of the text
+fn next_char_boundary(text: &str, index: usize) -> usize {
+    text.get(index..)
+        .and_then(|after| after.chars().next())
+        .map_or(text.len(), |character| index + character.len_utf8())
+}
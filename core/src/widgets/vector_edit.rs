@@ -1,8 +1,8 @@
 use crate::entity::Entity;
-use crate::events::{BuildHandler, Event, EventHandler};
+use crate::events::{BuildHandler, Event, EventHandler, EventPhase};
 use crate::state::style::*;
 use crate::WindowEvent;
-use crate::{MouseButton, Propagation, State};
+use crate::{Code, MouseButton, Propagation, State};
 
 use crate::widgets::{Dropdown, DropdownEvent, Textbox, TextboxEvent};
 use crate::AnimationState;
@@ -119,7 +119,8 @@ where
         + std::fmt::Display
         + Copy
         + PartialEq
-        + std::str::FromStr,
+        + std::str::FromStr
+        + Send,
 {
     pub fn new() -> Self {
         VectorEdit {
@@ -166,6 +167,69 @@ where
 
         self
     }
+
+    // Returns the values of the visible dimensions as text, such as "1, 2, 3"
+    fn values_text(&self) -> String {
+        [self.xval, self.yval, self.zval, self.wval]
+            .iter()
+            .take(self.num_of_dims as usize)
+            .map(|val| val.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    // Sets the values from text separated by commas or whitespace, starting from the first
+    // dimension. Nothing is set unless every value can be parsed.
+    fn set_values_text(&mut self, state: &mut State, entity: Entity, text: &str) {
+        let values = match text
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .map(|part| part.parse::<T>().ok())
+            .collect::<Option<Vec<T>>>()
+        {
+            Some(values) if !values.is_empty() => values,
+            _ => return,
+        };
+
+        let textboxes = [self.x, self.y, self.z, self.w];
+        for (index, val) in values.into_iter().take(4).enumerate() {
+            match index {
+                0 => self.xval = val,
+                1 => self.yval = val,
+                2 => self.zval = val,
+                _ => self.wval = val,
+            }
+
+            state.insert_event(
+                Event::new(TextboxEvent::SetValue(val.to_string()))
+                    .target(textboxes[index])
+                    .propagate(Propagation::Direct),
+            );
+        }
+
+        self.send_value(state, entity);
+    }
+
+    // Sends the event with the values of the visible dimensions
+    fn send_value(&self, state: &mut State, entity: Entity) {
+        match self.num_of_dims {
+            1 => state.insert_event(Event::new(VectorEditEvent::Dim1(self.xval)).target(entity)),
+            2 => state.insert_event(
+                Event::new(VectorEditEvent::Dim2(self.xval, self.yval)).target(entity),
+            ),
+            3 => state.insert_event(
+                Event::new(VectorEditEvent::Dim3(self.xval, self.yval, self.zval))
+                    .target(entity),
+            ),
+            4 => state.insert_event(
+                Event::new(VectorEditEvent::Dim4(
+                    self.xval, self.yval, self.zval, self.wval,
+                ))
+                .target(entity),
+            ),
+            _ => {}
+        }
+    }
 }
 
 impl<T> BuildHandler for VectorEdit<T>
@@ -442,26 +506,7 @@ where
                             self.wval = val;
                         }
 
-                        match self.num_of_dims {
-                            1 => state.insert_event(
-                                Event::new(VectorEditEvent::Dim1(self.xval)).target(entity),
-                            ),
-                            2 => state.insert_event(
-                                Event::new(VectorEditEvent::Dim2(self.xval, self.yval))
-                                    .target(entity),
-                            ),
-                            3 => state.insert_event(
-                                Event::new(VectorEditEvent::Dim3(self.xval, self.yval, self.zval))
-                                    .target(entity),
-                            ),
-                            4 => state.insert_event(
-                                Event::new(VectorEditEvent::Dim4(
-                                    self.xval, self.yval, self.zval, self.wval,
-                                ))
-                                .target(entity),
-                            ),
-                            _ => {}
-                        }
+                        self.send_value(state, entity);
 
                         //state.insert_event(Event::new(VectorEditEvent::ValueChanged(self.xval, self.yval, self.zval, self.wval)).target(entity));
                     }
@@ -470,5 +515,28 @@ where
                 _ => {}
             }
         }
+
+        // Copy and paste all of the values while a child without its own clipboard handling,
+        // such as a textbox which is not being edited, has focus
+        if event.phase != EventPhase::Capture {
+            if let Some(WindowEvent::KeyDown(code, _)) = event.message.downcast::<WindowEvent>() {
+                if state.modifiers.ctrl {
+                    match code {
+                        Code::KeyC => {
+                            let text = self.values_text();
+                            state.clipboard().set_text(&text);
+                        }
+
+                        Code::KeyV => {
+                            if let Some(text) = state.clipboard().get_text() {
+                                self.set_values_text(state, entity, &text);
+                            }
+                        }
+
+                        _ => {}
+                    }
+                }
+            }
+        }
     }
 }
@@ -1,4 +1,4 @@
-use crate::{Entity, Event, EventHandler, State, WindowEvent, apply_hover};
+use crate::{Entity, Event, EventHandler, EventPhase, State, WindowEvent, apply_hover};
 
 use crate::systems::{
     apply_clipping, apply_layout, apply_styles, apply_visibility, apply_z_ordering,
@@ -21,7 +21,8 @@ impl EventHandler for WindowWidget {
     fn on_event(&mut self, state: &mut State, _entity: Entity, event: &mut Event) {
         if let Some(window_event) = event.message.downcast::<WindowEvent>() {
             match window_event {
-                WindowEvent::WindowClose => {
+                // The close is broadcast down and back up, the window reports it on the way down
+                WindowEvent::WindowClose if event.phase == EventPhase::Capture => {
                     println!("Window Close Event");
                 }
 
@@ -235,7 +235,7 @@ impl BuildHandler for Calculator {
                 builder.set_text("\u{003D}").class("operator")
             });
 
-        state.focused = self.display;
+        state.focus(self.display);
 
         // Define the focus order for the buttons
         self.display
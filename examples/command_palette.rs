@@ -5,11 +5,6 @@ use tuix::*;
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
 
-use femtovg::{
-    renderer::OpenGl, Align, Baseline, Canvas, FillRule, FontId, ImageFlags, ImageId, LineCap,
-    LineJoin, Paint, Path, Renderer, Solidity,
-};
-
 fn main() {
     let app = Application::new(|win_desc, state, window| {
         state.add_stylesheet("examples/themes/cmd_palette_theme.css");
@@ -109,7 +104,7 @@ impl CommandPalette {
 impl BuildHandler for CommandPalette {
     type Ret = Entity;
     fn on_build(&mut self, state: &mut State, entity: Entity) -> Self::Ret {
-        state.focused = entity;
+        state.focus(entity);
 
         // Textbox for command searching
         self.search_box = Textbox::new("Type a command")
@@ -397,7 +392,7 @@ impl EventHandler for SearchLabel {
         }
     }
 
-    fn on_draw(&mut self, state: &mut State, entity: Entity, canvas: &mut Canvas<OpenGl>) {
+    fn on_draw(&mut self, state: &mut State, entity: Entity, canvas: &mut dyn Canvas) {
         // Skip invisible widgets
         if state.data.get_visibility(entity) == Visibility::Invisible {
             return;
@@ -541,14 +536,11 @@ impl EventHandler for SearchLabel {
 
         let opacity = state.data.get_opacity(entity);
 
-        let mut background_color: femtovg::Color = background_color.into();
-        background_color.set_alphaf(background_color.a * opacity);
+        let background_color = background_color.with_opacity(opacity);
 
-        let mut border_color: femtovg::Color = border_color.into();
-        border_color.set_alphaf(border_color.a * opacity);
+        let border_color = border_color.with_opacity(opacity);
 
-        let mut shadow_color: femtovg::Color = shadow_color.into();
-        shadow_color.set_alphaf(shadow_color.a * opacity);
+        let shadow_color = shadow_color.with_opacity(opacity);
 
         canvas.save();
 
@@ -574,14 +566,14 @@ impl EventHandler for SearchLabel {
             border_radius_bottom_right,
             border_radius_bottom_left,
         );
-        let mut paint = Paint::color(background_color);
-        canvas.fill_path(&mut path, paint);
+        let paint = Paint::color(background_color);
+        canvas.fill_path(&path, &paint);
 
         // Draw border
         let mut paint = Paint::color(border_color);
         paint.set_line_width(border_width);
         //paint.set_anti_alias(false);
-        canvas.stroke_path(&mut path, paint);
+        canvas.stroke_path(&path, &paint);
 
         // Stupid way, draw each glyph seperately
         if let Some(text) = state.style.text.get_mut(entity) {
@@ -612,53 +604,52 @@ impl EventHandler for SearchLabel {
             let align = match text_justify {
                 Justify::Start => {
                     x += padding_left;
-                    Align::Left
+                    TextAlign::Left
                 }
                 Justify::Center => {
                     x += 0.5 * width;
-                    Align::Center
+                    TextAlign::Center
                 }
                 Justify::End => {
                     x += width - padding_right;
-                    Align::Right
+                    TextAlign::Right
                 }
             };
 
             let baseline = match text_align {
                 tuix::Align::Start => {
                     y += padding_top;
-                    Baseline::Top
+                    TextBaseline::Top
                 }
                 tuix::Align::Center => {
                     y += 0.5 * height;
-                    Baseline::Middle
+                    TextBaseline::Middle
                 }
                 tuix::Align::End => {
                     y += height - padding_bottom;
-                    Baseline::Bottom
+                    TextBaseline::Bottom
                 }
             };
 
-            let mut font_color: femtovg::Color = font_color.into();
-            font_color.set_alphaf(font_color.a * opacity);
+            let font_color = font_color.with_opacity(opacity);
 
             let font_size = state.style.font_size.get(entity).cloned().unwrap_or(16.0);
 
             let mut paint = Paint::color(font_color);
             paint.set_font_size(font_size);
-            paint.set_font(&[font_id]);
+            paint.set_font(font_id);
             paint.set_text_align(align);
             paint.set_text_baseline(baseline);
             paint.set_anti_alias(false);
 
-            let mut paint_highlight = Paint::color(femtovg::Color::rgb(0, 148, 252));
+            let mut paint_highlight = Paint::color(Color::rgb(0, 148, 252));
             paint_highlight.set_font_size(font_size);
-            paint_highlight.set_font(&[font_id]);
+            paint_highlight.set_font(font_id);
             paint_highlight.set_text_align(align);
             paint_highlight.set_text_baseline(baseline);
             paint_highlight.set_anti_alias(false);
 
-            let text_metrics = canvas.measure_text(x, y, &text_string, paint).unwrap();
+            let text_metrics = canvas.measure_text(x, y, &text_string, &paint).unwrap();
 
             let mut temp = [0; 4];
 
@@ -670,9 +661,9 @@ impl EventHandler for SearchLabel {
             {
                 let px = glyph.x;
                 if self.indices.contains(&index) {
-                    canvas.fill_text(px, y, c.encode_utf8(&mut temp), paint_highlight);
+                    canvas.fill_text(px, y, c.encode_utf8(&mut temp), &paint_highlight);
                 } else {
-                    canvas.fill_text(px, y, c.encode_utf8(&mut temp), paint);
+                    canvas.fill_text(px, y, c.encode_utf8(&mut temp), &paint);
                 }
             }
 
@@ -2,29 +2,30 @@
 
 use glutin::event_loop::{ControlFlow, EventLoop};
 
+use crate::cursor::cursor_icon;
 use crate::keyboard::{scan_to_code, vk_to_key};
 
 use crate::window::Window;
 
 use tuix_core::{Length};
-use tuix_core::{Entity, State};
+use tuix_core::{Entity, State, SystemClipboard};
 
 use tuix_core::state::mouse::{MouseButton, MouseButtonState};
 
-use tuix_core::events::{Event, EventManager, Propagation};
-
-use tuix_core::state::hierarchy::IntoHierarchyIterator;
+use tuix_core::events::{Event, EventManager, EventProxy, Propagation};
 
 use tuix_core::state::Fonts;
 
-use tuix_core::state::style::prop::*;
+use tuix_core::renderer::Canvas;
 
-use tuix_core::{WindowDescription, WindowEvent, WindowWidget};
+use tuix_core::{CursorIcon, WindowCommand, WindowDescription, WindowEvent, WindowWidget};
 
-use tuix_core::systems::{apply_styles, apply_hover};
+use tuix_core::systems::apply_styles;
 
 use glutin::event::VirtualKeyCode;
 
+use std::time::Instant;
+
 type GEvent<'a, T> = glutin::event::Event<'a, T>;
 
 pub struct Application {
@@ -40,6 +41,7 @@ impl Application {
     ) -> Self {
         let event_loop = EventLoop::new();
         let mut state = State::new();
+        state.set_clipboard(SystemClipboard::new());
 
         let event_manager = EventManager::new();
 
@@ -104,6 +106,12 @@ impl Application {
 
         WindowWidget::new().build_window(&mut state);
 
+        // Wake the event loop when an event is sent from another thread
+        let event_loop_proxy = event_loop.create_proxy();
+        state.set_event_proxy_waker(move || {
+            event_loop_proxy.send_event(()).ok();
+        });
+
         Application {
             window: window,
             event_loop: event_loop,
@@ -112,6 +120,11 @@ impl Application {
         }
     }
 
+    /// Returns a handle which can be used to send events to the application from other threads
+    pub fn event_proxy(&self) -> EventProxy {
+        self.state.event_proxy()
+    }
+
     pub fn run(self) {
 
         let mut state = self.state;
@@ -130,8 +143,18 @@ impl Application {
 
         let mut first_time = true;
 
+        // The last caret area sent to the input method
+        let mut ime_caret = None;
+
+        // The cursor icon shown by the window
+        let mut window_cursor = CursorIcon::default();
+
         self.event_loop.run(move |event, _, control_flow| {
-            *control_flow = ControlFlow::Wait;
+            // Sleep until the next timer is due, or until the next event if there are no timers
+            *control_flow = match state.next_timer() {
+                Some(deadline) => ControlFlow::WaitUntil(deadline),
+                None => ControlFlow::Wait,
+            };
 
             match event {
                 GEvent::LoopDestroyed => return,
@@ -142,6 +165,10 @@ impl Application {
 
                 GEvent::MainEventsCleared => {
 
+                    state.receive_proxy_events();
+                    state.receive_values();
+                    state.update_timers(Instant::now());
+
                     let mut needs_redraw = false;
                     while !state.event_queue.is_empty() {
                         if event_manager.flush_events(&mut state) {
@@ -149,6 +176,26 @@ impl Application {
                         }
                     }
 
+                    for command in state.take_window_commands() {
+                        if command == WindowCommand::Close {
+                            should_quit = true;
+                        } else {
+                            window.apply_command(command);
+                        }
+                    }
+
+                    // Let every widget know the window is closing before exiting
+                    if should_quit {
+                        state.insert_event(
+                            Event::new(WindowEvent::WindowClose)
+                                .target(Entity::root())
+                                .propagate(Propagation::All),
+                        );
+                        while !state.event_queue.is_empty() {
+                            event_manager.flush_events(&mut state);
+                        }
+                    }
+
                     if state.apply_animations() {
                         //println!("Animate");
                         *control_flow = ControlFlow::Poll;
@@ -160,6 +207,8 @@ impl Application {
                         //state.insert_event(Event::new(WindowEvent::Redraw));
                         event_loop_proxy.send_event(()).unwrap();
                         window.handle.window().request_redraw();
+                    } else if let Some(deadline) = state.next_timer() {
+                        *control_flow = ControlFlow::WaitUntil(deadline);
                     } else {
                         //println!("Wait");
                         *control_flow = ControlFlow::Wait;
@@ -175,6 +224,14 @@ impl Application {
                         window.handle.window().request_redraw();
                     }
 
+                    if state.cursor() != window_cursor {
+                        window_cursor = state.cursor();
+                        window
+                            .handle
+                            .window()
+                            .set_cursor_icon(cursor_icon(window_cursor));
+                    }
+
                     //
 
                     // event_manager.flush_events(&mut state);
@@ -201,6 +258,17 @@ impl Application {
                         .handle
                         .swap_buffers()
                         .expect("Failed to swap buffers");
+
+                    // Position the candidate window of the input method below the caret
+                    let caret = state.caret_rect();
+                    if caret != ime_caret {
+                        if let Some(rect) = caret {
+                            window.handle.window().set_ime_position(
+                                glutin::dpi::PhysicalPosition::new(rect.x, rect.y + rect.h),
+                            );
+                        }
+                        ime_caret = caret;
+                    }
                 }
 
                 GEvent::WindowEvent {
@@ -212,8 +280,7 @@ impl Application {
                         // Close Window //
                         //////////////////
                         glutin::event::WindowEvent::CloseRequested => {
-                            state.insert_event(Event::new(WindowEvent::WindowClose));
-                            should_quit = true;
+                            state.request_window_close();
                         }
 
                         //TODO
@@ -249,11 +316,7 @@ impl Application {
                         // Focused Window //
                         ////////////////////
                         glutin::event::WindowEvent::ReceivedCharacter(input) => {
-                            state.insert_event(
-                                Event::new(WindowEvent::CharInput(input))
-                                    .target(state.focused)
-                                    .propagate(Propagation::Down),
-                            );
+                            state.dispatch_input(WindowEvent::CharInput(input));
                         }
 
                         glutin::event::WindowEvent::KeyboardInput {
@@ -285,93 +348,15 @@ impl Application {
 
                                     }
                                 }
-
-                                if virtual_keycode == VirtualKeyCode::Tab
-                                    && s == MouseButtonState::Pressed
-                                {
-                                    let next_focus = state
-                                        .style
-                                        .focus_order
-                                        .get(state.focused)
-                                        .cloned()
-                                        .unwrap_or_default()
-                                        .next;
-                                    let prev_focus = state
-                                        .style
-                                        .focus_order
-                                        .get(state.focused)
-                                        .cloned()
-                                        .unwrap_or_default()
-                                        .prev;
-
-                                    if state.modifiers.shift {
-                                        if prev_focus != Entity::null() {
-                                            state.focused.set_focus(&mut state, false);
-                                            state.focused = prev_focus;
-                                            state.focused.set_focus(&mut state, true);
-                                        } else {
-                                            // TODO impliment reverse iterator for hierarchy
-                                            // state.focused = match state.focused.into_iter(&state.hierarchy).next() {
-                                            //     Some(val) => val,
-                                            //     None => Entity::root(),
-                                            // };
-                                        }
-                                    } else {
-                                        let hierarchy = state.hierarchy.clone();
-                                        if next_focus != Entity::null() {
-                                            state.focused.set_focus(&mut state, false);
-                                            state.focused = next_focus;
-                                            state.focused.set_focus(&mut state, true);
-                                        } else {
-                                            state.focused.set_focus(&mut state, false);
-                                            state.focused =
-                                                match state.focused.into_iter(&hierarchy).next() {
-                                                    Some(val) => val,
-                                                    None => Entity::root(),
-                                                };
-                                            state.focused.set_focus(&mut state, true);
-                                        }
-                                    }
-
-                                    state.insert_event(
-                                        Event::new(WindowEvent::Restyle)
-                                            .target(Entity::root())
-                                            .origin(Entity::root()),
-                                    );
-                                }
                             }
 
                             match s {
                                 MouseButtonState::Pressed => {
-                                    if state.focused != Entity::null() {
-                                        state.insert_event(
-                                            Event::new(WindowEvent::KeyDown(code, key))
-                                                .target(state.focused)
-                                                .propagate(Propagation::DownUp),
-                                        );
-                                    } else {
-                                        state.insert_event(
-                                            Event::new(WindowEvent::KeyDown(code, key))
-                                                .target(state.hovered)
-                                                .propagate(Propagation::DownUp),
-                                        );
-                                    }
+                                    state.dispatch_input(WindowEvent::KeyDown(code, key));
                                 }
 
                                 MouseButtonState::Released => {
-                                    if state.focused != Entity::null() {
-                                        state.insert_event(
-                                            Event::new(WindowEvent::KeyUp(code, key))
-                                                .target(state.focused)
-                                                .propagate(Propagation::DownUp),
-                                        );
-                                    } else {
-                                        state.insert_event(
-                                            Event::new(WindowEvent::KeyUp(code, key))
-                                                .target(state.hovered)
-                                                .propagate(Propagation::DownUp),
-                                        );
-                                    }
+                                    state.dispatch_input(WindowEvent::KeyUp(code, key));
                                 }
                             }
                         }
@@ -379,27 +364,10 @@ impl Application {
                         glutin::event::WindowEvent::Resized(physical_size) => {
                             window.handle.resize(physical_size);
 
-                            state
-                                .style
-                                .width
-                                .insert(Entity::root(), Length::Pixels(physical_size.width as f32));
-                            state
-                                .style
-                                .height
-                                .insert(Entity::root(), Length::Pixels(physical_size.height as f32));
-
-                            state
-                                .data
-                                .set_width(Entity::root(), physical_size.width as f32);
-                            state
-                                .data
-                                .set_height(Entity::root(), physical_size.height as f32);
-
-                            state.insert_event(Event::new(WindowEvent::Restyle).origin(Entity::root()).target(Entity::root()));
-                            state.insert_event(
-                                Event::new(WindowEvent::Relayout).target(Entity::root()),
-                            );
-                            state.insert_event(Event::new(WindowEvent::Redraw).target(Entity::root()));
+                            state.dispatch_input(WindowEvent::WindowResize(
+                                physical_size.width as f32,
+                                physical_size.height as f32,
+                            ));
                         }
 
                         glutin::event::WindowEvent::CursorMoved {
@@ -410,23 +378,7 @@ impl Application {
                             let cursorx = (position.x) as f32;
                             let cursory = (position.y) as f32;
 
-                            state.mouse.cursorx = cursorx as f32;
-                            state.mouse.cursory = cursory as f32;
-
-                            apply_hover(&mut state);
-
-                            if state.captured != Entity::null() {
-                                state.insert_event(
-                                    Event::new(WindowEvent::MouseMove(cursorx, cursory))
-                                        .target(state.captured)
-                                        .propagate(Propagation::Direct),
-                                );
-                            } else if state.hovered != Entity::root() {
-                                state.insert_event(
-                                    Event::new(WindowEvent::MouseMove(cursorx, cursory))
-                                        .target(state.hovered),
-                                );
-                            }
+                            state.dispatch_input(WindowEvent::MouseMove(cursorx, cursory));
                         }
 
                         glutin::event::WindowEvent::MouseInput {
@@ -435,11 +387,6 @@ impl Application {
                             button,
                             modifiers: _,
                         } => {
-                            let s = match s {
-                                glutin::event::ElementState::Pressed => MouseButtonState::Pressed,
-                                glutin::event::ElementState::Released => MouseButtonState::Released,
-                            };
-
                             let b = match button {
                                 glutin::event::MouseButton::Left => MouseButton::Left,
                                 glutin::event::MouseButton::Right => MouseButton::Right,
@@ -447,105 +394,13 @@ impl Application {
                                 glutin::event::MouseButton::Other(id) => MouseButton::Other(id),
                             };
 
-                            match b {
-                                MouseButton::Left => {
-                                    state.mouse.left.state = s;
-                                }
-
-                                MouseButton::Right => {
-                                    state.mouse.right.state = s;
-                                }
-
-                                MouseButton::Middle => {
-                                    state.mouse.middle.state = s;
-                                }
-
-                                _ => {}
-                            }
-
                             match s {
-                                MouseButtonState::Pressed => {
-                                    if state.hovered != Entity::null()
-                                        && state.active != state.hovered
-                                    {
-                                        state.active = state.hovered;
-                                        state.insert_event(Event::new(WindowEvent::Restyle).target(Entity::root()));
-                                    }
-
-                                    if state.captured != Entity::null() {
-                                        state.insert_event(
-                                            Event::new(WindowEvent::MouseDown(b))
-                                                .target(state.captured)
-                                                .propagate(Propagation::Direct),
-                                        );
-                                    } else {
-                                        state.insert_event(
-                                            Event::new(WindowEvent::MouseDown(b))
-                                                .target(state.hovered),
-                                        );
-                                    }
-
-                                    match b {
-                                        MouseButton::Left => {
-                                            state.mouse.left.pos_down =
-                                                (state.mouse.cursorx, state.mouse.cursory);
-                                            state.mouse.left.pressed = state.hovered;
-                                        }
-
-                                        MouseButton::Middle => {
-                                            state.mouse.middle.pos_down =
-                                                (state.mouse.cursorx, state.mouse.cursory);
-                                            state.mouse.left.pressed = state.hovered;
-                                        }
-
-                                        MouseButton::Right => {
-                                            state.mouse.right.pos_down =
-                                                (state.mouse.cursorx, state.mouse.cursory);
-                                            state.mouse.left.pressed = state.hovered;
-                                        }
-
-                                        _ => {}
-                                    }
+                                glutin::event::ElementState::Pressed => {
+                                    state.dispatch_input(WindowEvent::MouseDown(b));
                                 }
 
-                                MouseButtonState::Released => {
-                                    state.active = Entity::null();
-                                    state.insert_event(Event::new(WindowEvent::Restyle));
-
-                                    if state.captured != Entity::null() {
-                                        state.insert_event(
-                                            Event::new(WindowEvent::MouseUp(b))
-                                                .target(state.captured)
-                                                .propagate(Propagation::Direct),
-                                        );
-                                    } else {
-                                        state.insert_event(
-                                            Event::new(WindowEvent::MouseUp(b))
-                                                .target(state.hovered),
-                                        );
-                                    }
-
-                                    match b {
-                                        MouseButton::Left => {
-                                            state.mouse.left.pos_up =
-                                                (state.mouse.cursorx, state.mouse.cursory);
-                                            state.mouse.left.released = state.hovered;
-                                        }
-
-                                        MouseButton::Middle => {
-                                            state.mouse.middle.pos_up =
-                                                (state.mouse.cursorx, state.mouse.cursory);
-                                            state.mouse.left.released = state.hovered;
-                                        }
-
-                                        MouseButton::Right => {
-                                            state.mouse.right.pos_up =
-                                                (state.mouse.cursorx, state.mouse.cursory);
-                                            state.mouse.left.released = state.hovered;
-                                        }
-
-                                        _ => {}
-                                    }
+                                glutin::event::ElementState::Released => {
+                                    state.dispatch_input(WindowEvent::MouseUp(b));
                                 }
                             }
                         }
@@ -561,18 +416,7 @@ impl Application {
                                 _ => (0.0, 0.0),
                             };
 
-                            if state.captured != Entity::null() {
-                                state.insert_event(
-                                    Event::new(WindowEvent::MouseScroll(x, y))
-                                        .target(state.captured)
-                                        .propagate(Propagation::Direct),
-                                );
-                            } else {
-                                state.insert_event(
-                                    Event::new(WindowEvent::MouseScroll(x, y))
-                                        .target(state.hovered),
-                                );
-                            }
+                            state.dispatch_input(WindowEvent::MouseScroll(x, y));
                         }
 
                         _ => {}
@@ -0,0 +1,31 @@
+use tuix_core::CursorIcon;
+
+pub fn cursor_icon(cursor: CursorIcon) -> glutin::window::CursorIcon {
+    use glutin::window::CursorIcon as Icon;
+    match cursor {
+        CursorIcon::Arrow => Icon::Default,
+        CursorIcon::Hand => Icon::Hand,
+        CursorIcon::Text => Icon::Text,
+        CursorIcon::Crosshair => Icon::Crosshair,
+        CursorIcon::Move => Icon::Move,
+        CursorIcon::Grab => Icon::Grab,
+        CursorIcon::Grabbing => Icon::Grabbing,
+        CursorIcon::NotAllowed => Icon::NotAllowed,
+        CursorIcon::Wait => Icon::Wait,
+        CursorIcon::Help => Icon::Help,
+        CursorIcon::NResize => Icon::NResize,
+        CursorIcon::EResize => Icon::EResize,
+        CursorIcon::SResize => Icon::SResize,
+        CursorIcon::WResize => Icon::WResize,
+        CursorIcon::NeResize => Icon::NeResize,
+        CursorIcon::NwResize => Icon::NwResize,
+        CursorIcon::SeResize => Icon::SeResize,
+        CursorIcon::SwResize => Icon::SwResize,
+        CursorIcon::EwResize => Icon::EwResize,
+        CursorIcon::NsResize => Icon::NsResize,
+        CursorIcon::NeswResize => Icon::NeswResize,
+        CursorIcon::NwseResize => Icon::NwseResize,
+        CursorIcon::ColResize => Icon::ColResize,
+        CursorIcon::RowResize => Icon::RowResize,
+    }
+}
@@ -1,3 +1,4 @@
 pub mod application;
+mod cursor;
 mod keyboard;
 mod window;
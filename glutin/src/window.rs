@@ -5,11 +5,11 @@ use glutin::ContextBuilder;
 
 use femtovg::{renderer::OpenGl, Canvas, Color};
 
-use tuix_core::WindowDescription;
+use tuix_core::{FemtovgCanvas, WindowCommand, WindowDescription};
 
 pub struct Window {
     pub handle: glutin::WindowedContext<glutin::PossiblyCurrent>,
-    pub canvas: Canvas<OpenGl>,
+    pub canvas: FemtovgCanvas<OpenGl>,
 }
 
 impl Window {
@@ -73,6 +73,35 @@ impl Window {
         // let height = size.height as f32;
         // let width = size.width as f32;
 
-        Window { handle, canvas }
+        Window {
+            handle,
+            canvas: FemtovgCanvas::new(canvas),
+        }
+    }
+
+    // Applies a window command, except for closing which is handled by the event loop
+    pub fn apply_command(&self, command: WindowCommand) {
+        let window = self.handle.window();
+        match command {
+            WindowCommand::SetTitle(title) => window.set_title(&title),
+            WindowCommand::SetInnerSize(width, height) => {
+                window.set_inner_size(PhysicalSize::new(width, height))
+            }
+            WindowCommand::SetMinInnerSize(size) => window.set_min_inner_size(
+                size.map(|(width, height)| PhysicalSize::new(width, height)),
+            ),
+            WindowCommand::SetMaxInnerSize(size) => window.set_max_inner_size(
+                size.map(|(width, height)| PhysicalSize::new(width, height)),
+            ),
+            WindowCommand::SetPosition(x, y) => {
+                window.set_outer_position(PhysicalPosition::new(x, y))
+            }
+            WindowCommand::SetFullscreen(fullscreen) => window.set_fullscreen(if fullscreen {
+                Some(glutin::window::Fullscreen::Borderless(None))
+            } else {
+                None
+            }),
+            WindowCommand::Close => {}
+        }
     }
 }
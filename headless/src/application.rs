@@ -0,0 +1,425 @@
+use keyboard_types::{Code, Key};
+
+use tuix_core::{Entity, State};
+use tuix_core::Length;
+
+use tuix_core::state::mouse::{ModifiersState, MouseButton};
+use tuix_core::state::Fonts;
+
+use tuix_core::renderer::{Canvas, Pixmap, SoftwareCanvas};
+
+use tuix_core::events::{
+    Event, EventManager, EventProxy, InputPlayer, InputRecording, Propagation,
+};
+
+use tuix_core::{WindowCommand, WindowDescription, WindowEvent, WindowWidget};
+
+use std::time::{Duration, Instant};
+
+use tuix_core::systems::{
+    apply_clipping, apply_layout, apply_styles, apply_visibility, apply_z_ordering,
+};
+
+/// A tuix application without a window or a rendering context
+///
+/// The headless application owns the state and event manager like the windowed backends but,
+/// instead of being driven by an OS event loop, input is synthesized by calling methods on it
+/// and the styling, layout, hover and event systems are run on demand. This allows widget logic
+/// to be exercised on machines without a display or GPU, such as CI.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut app = Application::new(|win_desc, state, window| {
+///     Button::new().build(state, window, |builder| builder.set_text("Button"));
+///     win_desc
+/// });
+///
+/// app.mouse_move(20.0, 20.0);
+/// app.mouse_down(MouseButton::Left);
+/// app.mouse_up(MouseButton::Left);
+/// app.update();
+/// ```
+pub struct Application {
+    pub state: State,
+    pub event_manager: EventManager,
+    pub window_description: WindowDescription,
+    // Simulated time used for timers, which only moves forward when advanced
+    time: Instant,
+    // Copies of the events processed by flush_events, used by the test harness
+    pub(crate) event_log: Option<Vec<Event>>,
+    // Set when a window command closes the simulated window
+    closed: bool,
+}
+
+impl Application {
+    pub fn new<F: FnOnce(WindowDescription, &mut State, Entity) -> WindowDescription>(
+        app: F,
+    ) -> Self {
+        let mut state = State::new();
+
+        let event_manager = EventManager::new();
+
+        let root = Entity::root();
+        state.hierarchy.add(Entity::root(), None);
+
+        let window_description = app(WindowDescription::new(), &mut state, root);
+
+        state.style.width.insert(
+            Entity::root(),
+            Length::Pixels(window_description.inner_size.width as f32),
+        );
+        state.style.height.insert(
+            Entity::root(),
+            Length::Pixels(window_description.inner_size.height as f32),
+        );
+
+        state
+            .data
+            .set_width(Entity::root(), window_description.inner_size.width as f32);
+        state
+            .data
+            .set_height(Entity::root(), window_description.inner_size.height as f32);
+        state.data.set_opacity(Entity::root(), 1.0);
+
+        WindowWidget::new().build_window(&mut state);
+
+        let time = state.timers.time();
+
+        let mut application = Application {
+            state,
+            event_manager,
+            window_description,
+            time,
+            event_log: None,
+            closed: false,
+        };
+
+        application
+            .state
+            .insert_event(Event::new(WindowEvent::Restyle).target(Entity::root()));
+        application
+            .state
+            .insert_event(Event::new(WindowEvent::Relayout).target(Entity::root()));
+
+        application.apply_styles();
+        application.flush_events();
+
+        application
+    }
+
+    /// Returns a handle which can be used to send events to the application from other threads
+    ///
+    /// Events sent through the proxy are processed by the next call to `update`.
+    pub fn event_proxy(&self) -> EventProxy {
+        self.state.event_proxy()
+    }
+
+    /// Runs pending animations and processes all queued events
+    ///
+    /// This is the equivalent of a single iteration of a backend event loop. Returns true if a
+    /// redraw was requested while processing the events.
+    pub fn update(&mut self) -> bool {
+        self.state.receive_proxy_events();
+        self.state.receive_values();
+        self.state.update_timers(self.time);
+
+        if self.state.apply_animations() {
+            self.state.insert_event(
+                Event::new(WindowEvent::Relayout)
+                    .target(Entity::root())
+                    .origin(Entity::root()),
+            );
+        }
+
+        self.flush_events()
+    }
+
+    /// Moves the simulated time forward and runs an update
+    ///
+    /// Timers only fire when the time is advanced, so tests of time-based behaviour such as
+    /// hover delays and key repeat do not depend on the speed of the machine. Timers which fall
+    /// due part way through the duration fire in order, each followed by processing of the
+    /// events they send.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// app.advance_time(Duration::from_millis(500));
+    /// ```
+    pub fn advance_time(&mut self, duration: Duration) -> bool {
+        let end = self.time + duration;
+        let mut needs_redraw = false;
+
+        while let Some(deadline) = self.state.next_timer() {
+            if deadline > end {
+                break;
+            }
+
+            self.time = deadline.max(self.time);
+            if self.update() {
+                needs_redraw = true;
+            }
+        }
+
+        self.time = end;
+        if self.update() {
+            needs_redraw = true;
+        }
+
+        needs_redraw
+    }
+
+    /// Processes queued events until the event queue is empty
+    ///
+    /// Window commands sent by the events are applied to the window description, as a windowed
+    /// backend would apply them to the window. Returns true if a redraw was requested while
+    /// processing the events.
+    pub fn flush_events(&mut self) -> bool {
+        let mut needs_redraw = false;
+        loop {
+            while !self.state.event_queue.is_empty() {
+                if let Some(event_log) = self.event_log.as_mut() {
+                    event_log.extend(self.state.event_queue.iter().cloned());
+                }
+
+                if self.event_manager.flush_events(&mut self.state) {
+                    needs_redraw = true;
+                }
+            }
+
+            // Applying a command can send more events, such as a resize
+            let commands = self.state.take_window_commands();
+            if commands.is_empty() {
+                break;
+            }
+
+            for command in commands {
+                self.apply_window_command(command);
+            }
+        }
+
+        needs_redraw
+    }
+
+    fn apply_window_command(&mut self, command: WindowCommand) {
+        match command {
+            WindowCommand::SetTitle(title) => self.window_description.title = title,
+            WindowCommand::SetInnerSize(width, height) => self.set_window_size(width, height),
+            WindowCommand::SetMinInnerSize(size) => {
+                let (width, height) = size.unwrap_or((0, 0));
+                self.window_description.min_inner_size.width = width;
+                self.window_description.min_inner_size.height = height;
+            }
+            // The window description has no maximum size, position or fullscreen state
+            WindowCommand::SetMaxInnerSize(_)
+            | WindowCommand::SetPosition(_, _)
+            | WindowCommand::SetFullscreen(_) => {}
+            WindowCommand::Close => {
+                if !self.closed {
+                    self.closed = true;
+                    self.state.insert_event(
+                        Event::new(WindowEvent::WindowClose)
+                            .target(Entity::root())
+                            .propagate(Propagation::All),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Simulates the user closing the window, which widgets can veto by consuming the
+    /// `WindowEvent::WindowCloseRequested` event
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// app.request_close();
+    /// app.update();
+    /// assert!(!app.is_closed());
+    /// ```
+    pub fn request_close(&mut self) {
+        self.state.request_window_close();
+    }
+
+    /// Returns true if the window has been closed
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Loads the default fonts into the given canvas
+    ///
+    /// Widgets expect the default fonts to be available when drawing, so this must be called
+    /// once with a canvas before it is passed to `draw`.
+    pub fn load_fonts(&mut self, canvas: &mut dyn Canvas) {
+        let regular_font = include_bytes!("../../resources/Roboto-Regular.ttf");
+        let bold_font = include_bytes!("../../resources/Roboto-Bold.ttf");
+        let icon_font = include_bytes!("../../resources/entypo.ttf");
+        let emoji_font = include_bytes!("../../resources/OpenSansEmoji.ttf");
+
+        self.state.fonts = Fonts {
+            regular: Some(canvas.add_font_mem(regular_font).expect("Cannot add font")),
+            bold: Some(canvas.add_font_mem(bold_font).expect("Cannot add font")),
+            icons: Some(canvas.add_font_mem(icon_font).expect("Cannot add font")),
+            emoji: Some(canvas.add_font_mem(emoji_font).expect("Cannot add font")),
+        };
+    }
+
+    /// Draws the current frame to the given canvas
+    pub fn draw(&mut self, canvas: &mut dyn Canvas) {
+        let hierarchy = self.state.hierarchy.clone();
+        self.event_manager.draw(&mut self.state, &hierarchy, canvas);
+    }
+
+    /// Renders the current frame on the CPU
+    ///
+    /// Used to produce screenshots for visual regression tests on machines without a GPU.
+    pub fn render(&mut self) -> Pixmap {
+        let mut canvas = SoftwareCanvas::new(
+            self.window_description.inner_size.width,
+            self.window_description.inner_size.height,
+        );
+        self.load_fonts(&mut canvas);
+        self.draw(&mut canvas);
+
+        canvas.pixmap()
+    }
+
+    /// Applies the stylesheet rules to every entity
+    pub fn apply_styles(&mut self) {
+        let hierarchy = self.state.hierarchy.clone();
+        apply_styles(&mut self.state, &hierarchy);
+        apply_visibility(&mut self.state, &hierarchy);
+    }
+
+    /// Computes the position and size of every entity
+    pub fn apply_layout(&mut self) {
+        let hierarchy = self.state.hierarchy.clone();
+        apply_z_ordering(&mut self.state, &hierarchy);
+        apply_visibility(&mut self.state, &hierarchy);
+        apply_clipping(&mut self.state, &hierarchy);
+        apply_layout(&mut self.state, &hierarchy);
+    }
+
+    /// Determines the hovered entity from the current cursor position
+    pub fn apply_hover(&mut self) {
+        tuix_core::systems::apply_hover(&mut self.state);
+    }
+
+    /// Simulates a resize of the window
+    pub fn set_window_size(&mut self, width: u32, height: u32) {
+        self.window_description.inner_size.width = width;
+        self.window_description.inner_size.height = height;
+
+        self.state
+            .dispatch_input(WindowEvent::WindowResize(width as f32, height as f32));
+    }
+
+    /// Simulates a change in the state of the keyboard modifiers
+    ///
+    /// Each modifier which changes is pressed or released with the key on the left side of the
+    /// keyboard, so the change reaches the widgets and any recording as key events.
+    pub fn set_modifiers(&mut self, modifiers: ModifiersState) {
+        let current = self.state.modifiers;
+        let changes = [
+            (current.shift, modifiers.shift, Code::ShiftLeft, Key::Shift),
+            (current.ctrl, modifiers.ctrl, Code::ControlLeft, Key::Control),
+            (current.alt, modifiers.alt, Code::AltLeft, Key::Alt),
+            (current.logo, modifiers.logo, Code::MetaLeft, Key::Meta),
+        ];
+
+        for (was_pressed, pressed, code, key) in changes.iter().cloned() {
+            if pressed && !was_pressed {
+                self.key_down(code, Some(key));
+            } else if was_pressed && !pressed {
+                self.key_up(code, Some(key));
+            }
+        }
+    }
+
+    /// Simulates moving the mouse cursor to the specified position in window coordinates
+    pub fn mouse_move(&mut self, cursorx: f32, cursory: f32) {
+        self.state
+            .dispatch_input(WindowEvent::MouseMove(cursorx, cursory));
+    }
+
+    /// Simulates pressing a mouse button at the current cursor position
+    pub fn mouse_down(&mut self, button: MouseButton) {
+        self.state.dispatch_input(WindowEvent::MouseDown(button));
+    }
+
+    /// Simulates releasing a mouse button at the current cursor position
+    pub fn mouse_up(&mut self, button: MouseButton) {
+        self.state.dispatch_input(WindowEvent::MouseUp(button));
+    }
+
+    /// Simulates scrolling the mouse wheel by the specified number of lines
+    pub fn mouse_scroll(&mut self, x: f32, y: f32) {
+        self.state.dispatch_input(WindowEvent::MouseScroll(x, y));
+    }
+
+    /// Simulates typing a character
+    pub fn char_input(&mut self, input: char) {
+        self.state.dispatch_input(WindowEvent::CharInput(input));
+    }
+
+    /// Simulates an input method updating the text being composed
+    ///
+    /// The cursor range is a byte range within the text. An empty text ends the composition.
+    pub fn ime_preedit(&mut self, text: &str, cursor_range: Option<(usize, usize)>) {
+        self.state.dispatch_input(WindowEvent::ImePreedit {
+            text: text.to_string(),
+            cursor_range,
+        });
+    }
+
+    /// Simulates an input method committing the composed text
+    pub fn ime_commit(&mut self, text: &str) {
+        self.state.dispatch_input(WindowEvent::ImeCommit(text.to_string()));
+    }
+
+    /// Simulates pressing a keyboard key
+    ///
+    /// Modifier keys update the modifiers state and the tab key moves the keyboard focus,
+    /// in the same way as the windowed backends.
+    pub fn key_down(&mut self, code: Code, key: Option<Key>) {
+        self.state.dispatch_input(WindowEvent::KeyDown(code, key));
+    }
+
+    /// Simulates releasing a keyboard key
+    pub fn key_up(&mut self, code: Code, key: Option<Key>) {
+        self.state.dispatch_input(WindowEvent::KeyUp(code, key));
+    }
+
+    /// Replays a recording of inputs, advancing the simulated time between the inputs
+    ///
+    /// The events sent by each input are processed before the next input is replayed, as they
+    /// would be by a windowed backend.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let recording = InputRecording::load("bug_report.txt").unwrap();
+    /// app.replay(&recording);
+    /// ```
+    pub fn replay(&mut self, recording: &InputRecording) {
+        let mut player = InputPlayer::new(recording.clone());
+        let mut time = Duration::from_secs(0);
+
+        while let Some(next_time) = player.next_time() {
+            if next_time > time {
+                self.advance_time(next_time - time);
+                time = next_time;
+            }
+
+            if let Some(recorded) = player.step(&mut self.state) {
+                if let WindowEvent::WindowResize(width, height) = recorded.input {
+                    self.window_description.inner_size.width = width as u32;
+                    self.window_description.inner_size.height = height as u32;
+                }
+            }
+
+            self.update();
+        }
+    }
+}
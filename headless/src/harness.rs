@@ -0,0 +1,562 @@
+use keyboard_types::{Code, Key};
+
+use tuix_core::events::{Event, Message};
+use tuix_core::state::mouse::MouseButton;
+use tuix_core::systems::entity_at;
+use tuix_core::{Entity, IntoParentIterator, State, Visibility, WindowDescription};
+
+use crate::Application;
+
+/// Identifies an entity for the test harness, either directly or by a selector
+pub trait Locator {
+    fn locate(&self, state: &State) -> Option<Entity>;
+
+    // Used in the messages of failed assertions
+    fn describe(&self) -> String;
+}
+
+impl Locator for Entity {
+    fn locate(&self, state: &State) -> Option<Entity> {
+        if state.is_alive(*self) {
+            Some(*self)
+        } else {
+            None
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!("entity {}", self)
+    }
+}
+
+// Selectors use the same syntax as stylesheets and match the first entity in hierarchy order.
+// An invalid selector is a mistake in the test and panics.
+impl Locator for &str {
+    fn locate(&self, state: &State) -> Option<Entity> {
+        state
+            .query_one(self)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    fn describe(&self) -> String {
+        format!("\"{}\"", self)
+    }
+}
+
+impl Locator for String {
+    fn locate(&self, state: &State) -> Option<Entity> {
+        self.as_str().locate(state)
+    }
+
+    fn describe(&self) -> String {
+        format!("\"{}\"", self)
+    }
+}
+
+/// The computed position and size of an entity in window coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+impl Bounds {
+    pub fn center(&self) -> (f32, f32) {
+        (self.x + self.w / 2.0, self.y + self.h / 2.0)
+    }
+
+    // Returns the overlapping area of two bounds, or None if they do not overlap
+    fn intersect(&self, other: &Bounds) -> Option<Bounds> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let w = (self.x + self.w).min(other.x + other.w) - x;
+        let h = (self.y + self.h).min(other.y + other.h) - y;
+
+        if w > 0.0 && h > 0.0 {
+            Some(Bounds { x, y, w, h })
+        } else {
+            None
+        }
+    }
+}
+
+/// A harness for testing widgets by simulating user interactions
+///
+/// Interactions target widgets by entity or by selector and are converted into the same mouse
+/// and keyboard input the windowed backends produce, at coordinates computed from the layout.
+/// This means the hover, capture and focus handling and the real event handlers of widgets such
+/// as `Button`, `Checkbox`, `Textbox` and `Slider` are exercised. Each interaction processes the
+/// resulting events before returning, and the events are kept so that tests can assert on them.
+///
+/// Failed lookups and assertions panic with a description of the problem, so the harness can be
+/// used directly in `#[test]` functions.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut harness = Harness::new(|win_desc, state, window| {
+///     Button::with_label("Save")
+///         .on_press(Event::new(AppEvent::Save))
+///         .build(state, window, |builder| builder.set_id("save"));
+///     Textbox::new("").build(state, window, |builder| builder.class("search"));
+///     win_desc
+/// });
+///
+/// harness.click("#save");
+/// harness.assert_emitted(&AppEvent::Save);
+///
+/// harness.type_text("textbox.search", "abc");
+/// harness.press(Code::Enter);
+/// assert_eq!(harness.text("textbox.search"), "abc");
+/// ```
+pub struct Harness {
+    pub app: Application,
+}
+
+impl Harness {
+    pub fn new<F: FnOnce(WindowDescription, &mut State, Entity) -> WindowDescription>(
+        app: F,
+    ) -> Self {
+        Harness::from_application(Application::new(app))
+    }
+
+    pub fn from_application(mut app: Application) -> Self {
+        app.event_log = Some(Vec::new());
+
+        let mut harness = Harness { app };
+        harness.update();
+        harness
+    }
+
+    pub fn state(&self) -> &State {
+        &self.app.state
+    }
+
+    pub fn state_mut(&mut self) -> &mut State {
+        &mut self.app.state
+    }
+
+    /// Processes any queued events, including the restyle and relayout of changed widgets
+    pub fn update(&mut self) {
+        self.app.update();
+    }
+
+    /// Returns the entity identified by the locator, or None if there is no such entity
+    pub fn find<L: Locator>(&self, locator: L) -> Option<Entity> {
+        locator.locate(&self.app.state)
+    }
+
+    /// Returns the entity identified by the locator, panicking if there is no such entity
+    pub fn entity<L: Locator>(&self, locator: L) -> Entity {
+        match locator.locate(&self.app.state) {
+            Some(entity) => entity,
+            None => panic!("No entity found for {}", locator.describe()),
+        }
+    }
+
+    /// Returns the computed bounds of an entity
+    pub fn bounds<L: Locator>(&self, locator: L) -> Bounds {
+        let entity = self.entity(locator);
+        self.entity_bounds(entity)
+    }
+
+    /// Returns the text of an entity
+    pub fn text<L: Locator>(&self, locator: L) -> String {
+        let entity = self.entity(locator);
+        self.app
+            .state
+            .style
+            .text
+            .get(entity)
+            .map(|text| text.text.clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns true if the entity has the pseudo-class
+    ///
+    /// The pseudo-class is named as in a stylesheet, e.g. `"hover"` or `"checked"`.
+    pub fn has_pseudo_class<L: Locator>(&self, locator: L, pseudo_class: &str) -> bool {
+        let entity = self.entity(locator);
+        let mut pseudo_classes = self
+            .app
+            .state
+            .style
+            .pseudo_classes
+            .get(entity)
+            .cloned()
+            .unwrap_or_default();
+
+        match pseudo_class {
+            "hover" => pseudo_classes.get_hover(),
+            "over" => pseudo_classes.get_over(),
+            "active" => pseudo_classes.get_active(),
+            "focus" => pseudo_classes.get_focus(),
+            "enabled" => pseudo_classes.get_enabled(),
+            "disabled" => pseudo_classes.get_disabled(),
+            "checked" => pseudo_classes.get_checked(),
+            _ => panic!("Unknown pseudo-class: {}", pseudo_class),
+        }
+    }
+
+    /// Moves the cursor to the center of the visible part of an entity
+    pub fn hover<L: Locator>(&mut self, locator: L) {
+        let (x, y) = self.target_point(&locator);
+        self.app.mouse_move(x, y);
+        self.update();
+    }
+
+    /// Clicks the left mouse button on an entity
+    pub fn click<L: Locator>(&mut self, locator: L) {
+        self.click_button(locator, MouseButton::Left);
+    }
+
+    /// Clicks a mouse button on an entity
+    pub fn click_button<L: Locator>(&mut self, locator: L, button: MouseButton) {
+        self.hover(locator);
+
+        self.app.mouse_down(button);
+        self.update();
+        self.app.mouse_up(button);
+        self.update();
+    }
+
+    /// Presses the left mouse button on an entity, moves the cursor by the offset and releases it
+    ///
+    /// The cursor is moved in several steps so that widgets receive intermediate mouse moves.
+    pub fn drag<L: Locator>(&mut self, locator: L, dx: f32, dy: f32) {
+        const STEPS: u32 = 4;
+
+        let (x, y) = self.target_point(&locator);
+        self.app.mouse_move(x, y);
+        self.update();
+
+        self.app.mouse_down(MouseButton::Left);
+        self.update();
+
+        for step in 1..=STEPS {
+            let t = step as f32 / STEPS as f32;
+            self.app.mouse_move(x + dx * t, y + dy * t);
+            self.update();
+        }
+
+        self.app.mouse_up(MouseButton::Left);
+        self.update();
+    }
+
+    /// Scrolls the mouse wheel over an entity by the specified number of lines
+    pub fn scroll<L: Locator>(&mut self, locator: L, x: f32, y: f32) {
+        self.hover(locator);
+
+        self.app.mouse_scroll(x, y);
+        self.update();
+    }
+
+    /// Clicks an entity to focus it and then types the text
+    pub fn type_text<L: Locator>(&mut self, locator: L, text: &str) {
+        self.click(locator);
+
+        for character in text.chars() {
+            let key = Some(Key::Character(character.to_string()));
+            let code = char_to_code(character);
+
+            self.app.key_down(code, key.clone());
+            self.app.char_input(character);
+            self.update();
+            self.app.key_up(code, key);
+            self.update();
+        }
+    }
+
+    /// Types text through an input method into the entity, after clicking it
+    ///
+    /// Each of the compositions is sent as preedit text with the cursor at its end, as an input
+    /// method shows the text while it is being typed, and then the text is committed.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// harness.compose_text("textbox", &["k", "か", "かn", "かな"], "仮名");
+    /// assert_eq!(harness.text("textbox"), "仮名");
+    /// ```
+    pub fn compose_text<L: Locator>(&mut self, locator: L, compositions: &[&str], text: &str) {
+        self.click(locator);
+
+        for composition in compositions {
+            let cursor = composition.len();
+            self.app.ime_preedit(composition, Some((cursor, cursor)));
+            self.update();
+        }
+
+        self.app.ime_commit(text);
+        self.update();
+    }
+
+    /// Sends preedit text from an input method to the focused entity
+    pub fn ime_preedit(&mut self, text: &str, cursor_range: Option<(usize, usize)>) {
+        self.app.ime_preedit(text, cursor_range);
+        self.update();
+    }
+
+    /// Commits text from an input method to the focused entity
+    pub fn ime_commit(&mut self, text: &str) {
+        self.app.ime_commit(text);
+        self.update();
+    }
+
+    /// Presses and releases a key
+    ///
+    /// The key value is derived from the code for named keys such as `Code::Enter` and
+    /// `Code::ArrowLeft`, and for letters, digits and space.
+    pub fn press(&mut self, code: Code) {
+        self.press_key(code, code_to_key(code));
+    }
+
+    /// Presses and releases a key with the given key value
+    pub fn press_key(&mut self, code: Code, key: Option<Key>) {
+        self.app.key_down(code, key.clone());
+        self.update();
+        self.app.key_up(code, key);
+        self.update();
+    }
+
+    /// Asks the window to close, returning false if a widget kept it open by consuming
+    /// `WindowEvent::WindowCloseRequested`
+    pub fn close_window(&mut self) -> bool {
+        self.app.request_close();
+        self.update();
+        self.app.is_closed()
+    }
+
+    /// Returns the events processed since the harness was created or the events were cleared
+    pub fn events(&self) -> &[Event] {
+        self.app
+            .event_log
+            .as_deref()
+            .unwrap_or(&[])
+    }
+
+    /// Returns the messages of type `M` processed since the events were cleared, in order
+    pub fn emitted<M: Message + Clone>(&self) -> Vec<M> {
+        self.events()
+            .iter()
+            .filter_map(|event| {
+                let mut message = event.message.clone();
+                message.downcast::<M>().cloned()
+            })
+            .collect()
+    }
+
+    /// Forgets the processed events
+    pub fn clear_events(&mut self) {
+        if let Some(events) = self.app.event_log.as_mut() {
+            events.clear();
+        }
+    }
+
+    /// Panics if no event with the message has been processed since the events were cleared
+    pub fn assert_emitted<M: Message + Clone + PartialEq>(&self, message: &M) {
+        let emitted = self.emitted::<M>();
+        if !emitted.contains(message) {
+            panic!(
+                "Expected {:?} to be emitted, but the emitted messages of its type were {:?}",
+                message, emitted
+            );
+        }
+    }
+
+    /// Panics if an event with the message has been processed since the events were cleared
+    pub fn assert_not_emitted<M: Message + Clone + PartialEq>(&self, message: &M) {
+        if self.emitted::<M>().contains(message) {
+            panic!("Expected {:?} not to be emitted", message);
+        }
+    }
+
+    /// Panics if the entity does not have the pseudo-class, or has it when `expected` is false
+    pub fn assert_pseudo_class<L: Locator>(&self, locator: L, pseudo_class: &str, expected: bool) {
+        if self.has_pseudo_class(&locator, pseudo_class) != expected {
+            panic!(
+                "Expected {} {} the :{} pseudo-class",
+                locator.describe(),
+                if expected { "to have" } else { "not to have" },
+                pseudo_class
+            );
+        }
+    }
+
+    /// Panics if the computed bounds of the entity differ from the expected bounds by more than
+    /// half a pixel
+    pub fn assert_bounds<L: Locator>(&self, locator: L, x: f32, y: f32, w: f32, h: f32) {
+        let bounds = self.bounds(&locator);
+        let expected = Bounds { x, y, w, h };
+
+        let close = |a: f32, b: f32| (a - b).abs() <= 0.5;
+        if !(close(bounds.x, x) && close(bounds.y, y) && close(bounds.w, w) && close(bounds.h, h))
+        {
+            panic!(
+                "Expected {} to have bounds {:?}, but it has {:?}",
+                locator.describe(),
+                expected,
+                bounds
+            );
+        }
+    }
+
+    fn entity_bounds(&self, entity: Entity) -> Bounds {
+        let data = &self.app.state.data;
+        Bounds {
+            x: data.get_posx(entity),
+            y: data.get_posy(entity),
+            w: data.get_width(entity),
+            h: data.get_height(entity),
+        }
+    }
+
+    // Returns the center of the visible part of an entity, panicking if the entity cannot be
+    // interacted with there, so that tests fail at the interaction rather than at a later
+    // assertion
+    fn target_point<L: Locator>(&self, locator: &L) -> (f32, f32) {
+        let state = &self.app.state;
+        let entity = self.entity(locator);
+
+        if state.data.get_visibility(entity) == Visibility::Invisible {
+            panic!("Cannot interact with {}, it is invisible", locator.describe());
+        }
+
+        let clip_widget = state.data.get_clip_widget(entity);
+        let visible = self
+            .entity_bounds(entity)
+            .intersect(&self.entity_bounds(clip_widget))
+            .and_then(|bounds| bounds.intersect(&self.entity_bounds(Entity::root())));
+
+        let (x, y) = match visible {
+            Some(bounds) => bounds.center(),
+            None => panic!(
+                "Cannot interact with {}, it is outside of the window or clipped",
+                locator.describe()
+            ),
+        };
+
+        // Check that the entity, or one of its descendants, would receive the mouse events
+        let hovered = entity_at(state, x, y);
+        if !hovered
+            .parent_iter(&state.hierarchy)
+            .any(|ancestor| ancestor == entity)
+        {
+            panic!(
+                "Cannot interact with {}, it is covered by another entity at ({}, {})",
+                locator.describe(),
+                x,
+                y
+            );
+        }
+
+        (x, y)
+    }
+}
+
+impl<L: Locator> Locator for &L {
+    fn locate(&self, state: &State) -> Option<Entity> {
+        (*self).locate(state)
+    }
+
+    fn describe(&self) -> String {
+        (*self).describe()
+    }
+}
+
+// Returns the key code of the key which types a character on a US keyboard layout
+fn char_to_code(character: char) -> Code {
+    match character.to_ascii_lowercase() {
+        'a' => Code::KeyA,
+        'b' => Code::KeyB,
+        'c' => Code::KeyC,
+        'd' => Code::KeyD,
+        'e' => Code::KeyE,
+        'f' => Code::KeyF,
+        'g' => Code::KeyG,
+        'h' => Code::KeyH,
+        'i' => Code::KeyI,
+        'j' => Code::KeyJ,
+        'k' => Code::KeyK,
+        'l' => Code::KeyL,
+        'm' => Code::KeyM,
+        'n' => Code::KeyN,
+        'o' => Code::KeyO,
+        'p' => Code::KeyP,
+        'q' => Code::KeyQ,
+        'r' => Code::KeyR,
+        's' => Code::KeyS,
+        't' => Code::KeyT,
+        'u' => Code::KeyU,
+        'v' => Code::KeyV,
+        'w' => Code::KeyW,
+        'x' => Code::KeyX,
+        'y' => Code::KeyY,
+        'z' => Code::KeyZ,
+        '0' => Code::Digit0,
+        '1' => Code::Digit1,
+        '2' => Code::Digit2,
+        '3' => Code::Digit3,
+        '4' => Code::Digit4,
+        '5' => Code::Digit5,
+        '6' => Code::Digit6,
+        '7' => Code::Digit7,
+        '8' => Code::Digit8,
+        '9' => Code::Digit9,
+        ' ' => Code::Space,
+        '-' => Code::Minus,
+        '=' => Code::Equal,
+        '.' => Code::Period,
+        ',' => Code::Comma,
+        '/' => Code::Slash,
+        _ => Code::Unidentified,
+    }
+}
+
+// Returns the key value of a key code without modifiers
+fn code_to_key(code: Code) -> Option<Key> {
+    let key = match code {
+        Code::Enter | Code::NumpadEnter => Key::Enter,
+        Code::Tab => Key::Tab,
+        Code::Backspace => Key::Backspace,
+        Code::Delete => Key::Delete,
+        Code::Escape => Key::Escape,
+        Code::ArrowLeft => Key::ArrowLeft,
+        Code::ArrowRight => Key::ArrowRight,
+        Code::ArrowUp => Key::ArrowUp,
+        Code::ArrowDown => Key::ArrowDown,
+        Code::Home => Key::Home,
+        Code::End => Key::End,
+        Code::PageUp => Key::PageUp,
+        Code::PageDown => Key::PageDown,
+        Code::Insert => Key::Insert,
+        Code::ShiftLeft | Code::ShiftRight => Key::Shift,
+        Code::ControlLeft | Code::ControlRight => Key::Control,
+        Code::AltLeft | Code::AltRight => Key::Alt,
+        Code::MetaLeft | Code::MetaRight => Key::Meta,
+        Code::F1 => Key::F1,
+        Code::F2 => Key::F2,
+        Code::F3 => Key::F3,
+        Code::F4 => Key::F4,
+        Code::F5 => Key::F5,
+        Code::F6 => Key::F6,
+        Code::F7 => Key::F7,
+        Code::F8 => Key::F8,
+        Code::F9 => Key::F9,
+        Code::F10 => Key::F10,
+        Code::F11 => Key::F11,
+        Code::F12 => Key::F12,
+        _ => {
+            let character = (b'a'..=b'z')
+                .chain(b'0'..=b'9')
+                .chain(std::iter::once(b' '))
+                .map(|byte| byte as char)
+                .find(|character| char_to_code(*character) == code)?;
+            Key::Character(character.to_string())
+        }
+    };
+
+    Some(key)
+}
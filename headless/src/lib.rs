@@ -0,0 +1,6 @@
+pub mod application;
+
+pub use application::Application;
+
+pub mod harness;
+pub use harness::{Bounds, Harness, Locator};
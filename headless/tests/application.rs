@@ -0,0 +1,35 @@
+use tuix_core::state::mouse::ModifiersState;
+use tuix_core::*;
+use tuix_headless::Application;
+
+#[test]
+fn modifier_changes_are_recorded_and_replayed() {
+    let mut app = Application::new(|window, _, _| window);
+    let ctrl = ModifiersState {
+        ctrl: true,
+        ..ModifiersState::default()
+    };
+
+    app.state.start_recording();
+    app.set_modifiers(ctrl);
+    assert!(app.state.modifiers.ctrl);
+    app.set_modifiers(ModifiersState::default());
+    assert!(!app.state.modifiers.ctrl);
+    let recording = app.state.stop_recording().unwrap();
+
+    let inputs: Vec<WindowEvent> = recording.inputs.iter().map(|r| r.input.clone()).collect();
+    assert_eq!(
+        inputs,
+        vec![
+            WindowEvent::KeyDown(Code::ControlLeft, Some(Key::Control)),
+            WindowEvent::KeyUp(Code::ControlLeft, Some(Key::Control)),
+        ]
+    );
+
+    // Replaying the first half of the recording leaves the modifier pressed
+    let mut pressed = recording.clone();
+    pressed.inputs.truncate(1);
+    let mut replayed = Application::new(|window, _, _| window);
+    replayed.replay(&pressed);
+    assert!(replayed.state.modifiers.ctrl);
+}
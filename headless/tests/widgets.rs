@@ -0,0 +1,150 @@
+use tuix_core::*;
+use tuix_headless::Harness;
+
+#[derive(Debug, Clone, PartialEq)]
+enum AppEvent {
+    Save,
+    Slid(f32),
+}
+
+// A column of one of each widget, sized so that the layout is easy to check
+fn form() -> Harness {
+    Harness::new(|window, state, root| {
+        Button::with_label("Save")
+            .on_press(Event::new(AppEvent::Save))
+            .build(state, root, |builder| {
+                builder
+                    .set_id("save")
+                    .set_width(Length::Pixels(100.0))
+                    .set_height(Length::Pixels(30.0))
+            });
+        Checkbox::new(false).build(state, root, |builder| {
+            builder
+                .set_id("check")
+                .set_width(Length::Pixels(20.0))
+                .set_height(Length::Pixels(20.0))
+        });
+        Textbox::new("").build(state, root, |builder| {
+            builder
+                .class("search")
+                .set_width(Length::Pixels(100.0))
+                .set_height(Length::Pixels(30.0))
+        });
+        Slider::new()
+            .on_change(|value| Event::new(AppEvent::Slid(value)))
+            .build(state, root, |builder| {
+                builder
+                    .set_id("slider")
+                    .set_width(Length::Pixels(200.0))
+                    .set_height(Length::Pixels(20.0))
+            });
+        window
+    })
+}
+
+#[test]
+fn widgets_are_laid_out_in_a_column() {
+    let h = form();
+
+    h.assert_bounds("#save", 0.0, 0.0, 100.0, 30.0);
+    h.assert_bounds("#check", 0.0, 30.0, 20.0, 20.0);
+    h.assert_bounds("textbox.search", 0.0, 50.0, 100.0, 30.0);
+    h.assert_bounds("#slider", 0.0, 80.0, 200.0, 20.0);
+}
+
+#[test]
+fn button_click_emits_press_event() {
+    let mut h = form();
+
+    h.assert_not_emitted(&AppEvent::Save);
+    h.click("#save");
+    h.assert_emitted(&AppEvent::Save);
+    h.assert_pseudo_class("#save", "hover", true);
+
+    h.hover("#check");
+    h.assert_pseudo_class("#save", "hover", false);
+}
+
+#[test]
+fn checkbox_click_toggles_checked() {
+    let mut h = form();
+
+    h.assert_pseudo_class("#check", "checked", false);
+
+    h.click("#check");
+    h.assert_pseudo_class("#check", "checked", true);
+    h.assert_emitted(&CheckboxEvent::Checked);
+
+    h.clear_events();
+    h.click("#check");
+    h.assert_pseudo_class("#check", "checked", false);
+    h.assert_emitted(&CheckboxEvent::Unchecked);
+    h.assert_not_emitted(&CheckboxEvent::Checked);
+}
+
+#[test]
+fn textbox_edits_typed_text() {
+    let mut h = form();
+
+    h.type_text("textbox.search", "abc");
+    assert_eq!(h.text("textbox.search"), "abc");
+
+    h.press(Code::Backspace);
+    assert_eq!(h.text("textbox.search"), "ab");
+
+    h.press(Code::Enter);
+    h.assert_emitted(&TextboxEvent::ValueChanged("ab".to_string()));
+}
+
+#[test]
+fn textbox_exposes_live_text() {
+    let mut h = form();
+    let textbox = h.entity("textbox.search");
+    let text = |h: &Harness| {
+        h.state()
+            .with_widget::<Textbox, _>(textbox, |textbox| textbox.text().to_string())
+            .unwrap()
+    };
+
+    h.type_text("textbox.search", "abc");
+    assert_eq!(text(&h), "abc");
+
+    h.press(Code::Backspace);
+    assert_eq!(text(&h), "ab");
+
+    // Cancelling the edit restores the text from before editing started
+    h.press(Code::Escape);
+    assert_eq!(text(&h), "");
+    assert_eq!(h.text("textbox.search"), "");
+}
+
+#[test]
+fn slider_drag_emits_changes() {
+    let mut h = form();
+
+    h.drag("#slider", 50.0, 0.0);
+
+    let values = h.emitted::<AppEvent>();
+    assert!(!values.is_empty());
+    match values.last() {
+        Some(AppEvent::Slid(value)) => assert!(*value > 0.0, "slider value {}", value),
+        other => panic!("expected a slider change, got {:?}", other),
+    }
+}
+
+#[test]
+fn tab_moves_focus_forward() {
+    let mut h = form();
+
+    let before = h.state().focused;
+    h.press(Code::Tab);
+    assert_ne!(h.state().focused, before);
+}
+
+#[test]
+#[should_panic(expected = "#missing")]
+fn missing_widget_panics() {
+    let mut h = form();
+
+    h.click("#missing");
+}
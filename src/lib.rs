@@ -8,3 +8,6 @@ pub use tuix_glutin::application::Application;
 pub use tuix_baseview::Application;
 
 pub use tuix_core::*;
+
+#[cfg(feature = "headless")]
+pub use tuix_headless as headless;
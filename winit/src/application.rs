@@ -1,23 +1,26 @@
 use winit::event_loop::{ControlFlow, EventLoop};
 
+use crate::cursor::cursor_icon;
 use crate::keyboard::{scan_to_code, vk_to_key};
 use crate::window::Window;
 
-use tuix_core::events::{Event, EventManager, Propagation};
-use tuix_core::state::hierarchy::IntoHierarchyIterator;
+use tuix_core::events::{Event, EventManager, EventProxy, Propagation};
 use tuix_core::state::mouse::{MouseButton, MouseButtonState};
 use tuix_core::state::Fonts;
-use tuix_core::{Entity, State};
-use tuix_core::{Length, Visibility};
 
-use tuix_core::state::style::prop::*;
+use tuix_core::renderer::Canvas;
+use tuix_core::{Entity, State, SystemClipboard};
+use tuix_core::Length;
+
 use tuix_core::systems::{apply_styles};
-use tuix_core::{WindowDescription, WindowEvent, WindowWidget};
+use tuix_core::{CursorIcon, WindowCommand, WindowDescription, WindowEvent, WindowWidget};
 
 type WEvent<'a, T> = winit::event::Event<'a, T>;
 
 use winit::event::VirtualKeyCode;
 
+use std::time::Instant;
+
 pub struct Application {
     pub window: Window,
     pub state: State,
@@ -31,6 +34,7 @@ impl Application {
     ) -> Self {
         let event_loop = EventLoop::new();
         let mut state = State::new();
+        state.set_clipboard(SystemClipboard::new());
 
         let event_manager = EventManager::new();
 
@@ -95,6 +99,12 @@ impl Application {
 
         WindowWidget::new().build_window(&mut state);
 
+        // Wake the event loop when an event is sent from another thread
+        let event_loop_proxy = event_loop.create_proxy();
+        state.set_event_proxy_waker(move || {
+            event_loop_proxy.send_event(()).ok();
+        });
+
         Application {
             window,
             event_loop,
@@ -103,6 +113,11 @@ impl Application {
         }
     }
 
+    /// Returns a handle which can be used to send events to the application from other threads
+    pub fn event_proxy(&self) -> EventProxy {
+        self.state.event_proxy()
+    }
+
     pub fn run(self) {
 
         let mut state = self.state;
@@ -118,6 +133,12 @@ impl Application {
 
         let mut first_time = true;
 
+        // The last caret area sent to the input method
+        let mut ime_caret = None;
+
+        // The cursor icon shown by the window
+        let mut window_cursor = CursorIcon::default();
+
         self.event_loop.run(move |event, _, control_flow| {
             match event {
                 WEvent::LoopDestroyed => return,
@@ -125,6 +146,11 @@ impl Application {
                 WEvent::UserEvent(_) => {}
 
                 WEvent::MainEventsCleared => {
+                    // The event loop polls so timers are checked on every iteration
+                    state.receive_proxy_events();
+                    state.receive_values();
+                    state.update_timers(Instant::now());
+
                     let mut needs_redraw = false;
 
                     if state.apply_animations() {
@@ -148,10 +174,35 @@ impl Application {
                         }
                     }
 
+                    for command in state.take_window_commands() {
+                        if command == WindowCommand::Close {
+                            should_quit = true;
+                        } else {
+                            window.apply_command(command);
+                        }
+                    }
+
+                    // Let every widget know the window is closing before exiting
+                    if should_quit {
+                        state.insert_event(
+                            Event::new(WindowEvent::WindowClose)
+                                .target(Entity::root())
+                                .propagate(Propagation::All),
+                        );
+                        while !state.event_queue.is_empty() {
+                            event_manager.flush_events(&mut state);
+                        }
+                    }
+
                     if needs_redraw {
                         window.window.request_redraw();
                     }
 
+                    if state.cursor() != window_cursor {
+                        window_cursor = state.cursor();
+                        window.window.set_cursor_icon(cursor_icon(window_cursor));
+                    }
+
                     // event_manager.flush_events(&mut state);
 
                     // apply_z_ordering(&mut state, &hierarchy);
@@ -174,6 +225,18 @@ impl Application {
 
                     window.context.swap_buffers();
                     window.context.make_not_current();
+
+                    // Position the candidate window of the input method below the caret
+                    let caret = state.caret_rect();
+                    if caret != ime_caret {
+                        if let Some(rect) = caret {
+                            window.window.set_ime_position(winit::dpi::PhysicalPosition::new(
+                                rect.x,
+                                rect.y + rect.h,
+                            ));
+                        }
+                        ime_caret = caret;
+                    }
                 }
 
                 WEvent::WindowEvent {
@@ -185,8 +248,7 @@ impl Application {
                         // Close Window //
                         //////////////////
                         winit::event::WindowEvent::CloseRequested => {
-                            state.insert_event(Event::new(WindowEvent::WindowClose));
-                            should_quit = true;
+                            state.request_window_close();
                         }
 
                         //TODO
@@ -215,11 +277,7 @@ impl Application {
                         // Focused Window //
                         ////////////////////
                         winit::event::WindowEvent::ReceivedCharacter(input) => {
-                            state.insert_event(
-                                Event::new(WindowEvent::CharInput(input))
-                                    .target(state.focused)
-                                    .propagate(Propagation::Down),
-                            );
+                            state.dispatch_input(WindowEvent::CharInput(input));
                         }
 
                         winit::event::WindowEvent::KeyboardInput {
@@ -243,120 +301,24 @@ impl Application {
                                 {
                                     state.reload_styles().unwrap();
                                 }
-
-                                if virtual_keycode == VirtualKeyCode::Tab
-                                    && s == MouseButtonState::Pressed
-                                {
-                                    let next_focus = state
-                                        .style
-                                        .focus_order
-                                        .get(state.focused)
-                                        .cloned()
-                                        .unwrap_or_default()
-                                        .next;
-                                    let prev_focus = state
-                                        .style
-                                        .focus_order
-                                        .get(state.focused)
-                                        .cloned()
-                                        .unwrap_or_default()
-                                        .prev;
-
-                                    if state.modifiers.shift {
-                                        if prev_focus != Entity::null() {
-                                            state.focused.set_focus(&mut state, false);
-                                            state.focused = prev_focus;
-                                            state.focused.set_focus(&mut state, true);
-                                        } else {
-                                            // TODO impliment reverse iterator for hierarchy
-                                            // state.focused = match state.focused.into_iter(&state.hierarchy).next() {
-                                            //     Some(val) => val,
-                                            //     None => Entity::root(),
-                                            // };
-                                        }
-                                    } else {
-                                        if next_focus != Entity::null() {
-                                            state.focused.set_focus(&mut state, false);
-                                            state.focused = next_focus;
-                                            state.focused.set_focus(&mut state, true);
-                                        } else {
-                                            state.focused.set_focus(&mut state, false);
-                                            state.focused =
-                                                match state.focused.into_iter(&hierarchy).next() {
-                                                    Some(val) => val,
-                                                    None => Entity::root(),
-                                                };
-                                            state.focused.set_focus(&mut state, true);
-                                        }
-                                    }
-
-                                    state.insert_event(
-                                        Event::new(WindowEvent::Restyle)
-                                            .target(Entity::root())
-                                            .origin(Entity::root()),
-                                    );
-                                }
                             }
 
                             match s {
                                 MouseButtonState::Pressed => {
-                                    if state.focused != Entity::null() {
-                                        state.insert_event(
-                                            Event::new(WindowEvent::KeyDown(code, key))
-                                                .target(state.focused)
-                                                .propagate(Propagation::DownUp),
-                                        );
-                                    } else {
-                                        state.insert_event(
-                                            Event::new(WindowEvent::KeyDown(code, key))
-                                                .target(state.hovered)
-                                                .propagate(Propagation::DownUp),
-                                        );
-                                    }
+                                    state.dispatch_input(WindowEvent::KeyDown(code, key));
                                 }
 
                                 MouseButtonState::Released => {
-                                    if state.focused != Entity::null() {
-                                        state.insert_event(
-                                            Event::new(WindowEvent::KeyUp(code, key))
-                                                .target(state.focused)
-                                                .propagate(Propagation::DownUp),
-                                        );
-                                    } else {
-                                        state.insert_event(
-                                            Event::new(WindowEvent::KeyUp(code, key))
-                                                .target(state.hovered)
-                                                .propagate(Propagation::DownUp),
-                                        );
-                                    }
+                                    state.dispatch_input(WindowEvent::KeyUp(code, key));
                                 }
                             }
                         }
 
                         winit::event::WindowEvent::Resized(physical_size) => {
-                            state
-                                .style
-                                .width
-                                .insert(Entity::root(), Length::Pixels(physical_size.width as f32));
-                            state.style.height.insert(
-                                Entity::root(),
-                                Length::Pixels(physical_size.height as f32),
-                            );
-
-                            state
-                                .data
-                                .set_width(Entity::root(), physical_size.width as f32);
-                            state
-                                .data
-                                .set_height(Entity::root(), physical_size.height as f32);
-
-                            state.insert_event(
-                                Event::new(WindowEvent::Restyle).origin(Entity::root()),
-                            );
-                            state.insert_event(
-                                Event::new(WindowEvent::Relayout).target(Entity::null()),
-                            );
-                            state.insert_event(Event::new(WindowEvent::Redraw));
+                            state.dispatch_input(WindowEvent::WindowResize(
+                                physical_size.width as f32,
+                                physical_size.height as f32,
+                            ));
                         }
 
                         winit::event::WindowEvent::CursorMoved {
@@ -367,147 +329,7 @@ impl Application {
                             let cursorx = (position.x) as f32;
                             let cursory = (position.y) as f32;
 
-                            state.mouse.cursorx = cursorx as f32;
-                            state.mouse.cursory = cursory as f32;
-
-                            let mut hovered_widget = Entity::root();
-
-                            // This only really needs to be computed when the hierarchy changes
-                            // Can be optimised
-                            let mut draw_hierarchy: Vec<Entity> =
-                                state.hierarchy.into_iter().collect();
-
-                            draw_hierarchy
-                                .sort_by_cached_key(|entity| state.data.get_z_order(*entity));
-
-                            for widget in draw_hierarchy.into_iter() {
-                                // Skip invisible widgets
-                                if state.data.get_visibility(widget) == Visibility::Invisible {
-                                    continue;
-                                }
-
-                                // This shouldn't be here but there's a bug if it isn't
-                                if state.data.get_opacity(widget) == 0.0 {
-                                    continue;
-                                }
-
-                                // Skip non-hoverable widgets
-                                if state.data.get_hoverability(widget) != true {
-                                    continue;
-                                }
-
-                                let border_width = match state
-                                    .style
-                                    .border_width
-                                    .get(widget)
-                                    .cloned()
-                                    .unwrap_or_default()
-                                {
-                                    Length::Pixels(val) => val,
-                                    //Length::Percentage(val) => parent_width * val,
-                                    _ => 0.0,
-                                };
-
-                                let posx = state.data.get_posx(widget) - (border_width / 2.0);
-                                let posy = state.data.get_posy(widget) - (border_width / 2.0);
-                                let width = state.data.get_width(widget) + (border_width);
-                                let height = state.data.get_height(widget) + (border_width);
-
-                                let clip_widget = state.data.get_clip_widget(widget);
-
-                                let clip_posx = state.data.get_posx(clip_widget);
-                                let clip_posy = state.data.get_posy(clip_widget);
-                                let clip_width = state.data.get_width(clip_widget);
-                                let clip_height = state.data.get_height(clip_widget);
-
-                                if cursorx >= posx
-                                    && cursorx >= clip_posx
-                                    && cursorx < (posx + width)
-                                    && cursorx < (clip_posx + clip_width)
-                                    && cursory >= posy
-                                    && cursory >= clip_posy
-                                    && cursory < (posy + height)
-                                    && cursory < (clip_posy + clip_height)
-                                {
-                                    hovered_widget = widget;
-                                    if let Some(pseudo_classes) =
-                                        state.style.pseudo_classes.get_mut(hovered_widget)
-                                    {
-                                        pseudo_classes.set_over(true);
-                                    }
-                                } else {
-                                    if let Some(pseudo_classes) =
-                                        state.style.pseudo_classes.get_mut(hovered_widget)
-                                    {
-                                        pseudo_classes.set_over(false);
-                                    }
-                                }
-                            }
-
-                            if hovered_widget != state.hovered {
-                                // Useful for debugging
-
-                                // println!(
-                                //     "Hover changed to {:?} parent: {:?}, posx: {}, posy: {} width: {} height: {} z_order: {}",
-                                //     hovered_widget,
-                                //     state.hierarchy.get_parent(hovered_widget),
-                                //     state.transform.get_posx(hovered_widget),
-                                //     state.transform.get_posy(hovered_widget),
-                                //     state.transform.get_width(hovered_widget),
-                                //     state.transform.get_height(hovered_widget),
-                                //     state.transform.get_z_order(hovered_widget),
-                                // );
-
-                                if let Some(pseudo_classes) =
-                                    state.style.pseudo_classes.get_mut(hovered_widget)
-                                {
-                                    pseudo_classes.set_hover(true);
-                                }
-
-                                if let Some(pseudo_classes) =
-                                    state.style.pseudo_classes.get_mut(state.hovered)
-                                {
-                                    pseudo_classes.set_hover(false);
-                                }
-
-                                state.insert_event(
-                                    Event::new(WindowEvent::MouseOver).target(hovered_widget),
-                                );
-                                state.insert_event(
-                                    Event::new(WindowEvent::MouseOut).target(state.hovered),
-                                );
-
-                                state.insert_event(
-                                    Event::new(WindowEvent::Restyle)
-                                        .origin(hovered_widget)
-                                        .target(Entity::root()),
-                                );
-                                state.insert_event(
-                                    Event::new(WindowEvent::Restyle)
-                                        .origin(state.hovered)
-                                        .target(Entity::root()),
-                                );
-
-                                state.hovered = hovered_widget;
-                                state.active = Entity::null();
-
-                                state.insert_event(
-                                    Event::new(WindowEvent::Redraw).target(Entity::root()),
-                                );
-                            }
-
-                            if state.captured != Entity::null() {
-                                state.insert_event(
-                                    Event::new(WindowEvent::MouseMove(cursorx, cursory))
-                                        .target(state.captured)
-                                        .propagate(Propagation::Direct),
-                                );
-                            } else if state.hovered != Entity::root() {
-                                state.insert_event(
-                                    Event::new(WindowEvent::MouseMove(cursorx, cursory))
-                                        .target(state.hovered),
-                                );
-                            }
+                            state.dispatch_input(WindowEvent::MouseMove(cursorx, cursory));
                         }
 
                         winit::event::WindowEvent::MouseInput {
@@ -516,11 +338,6 @@ impl Application {
                             button,
                             ..
                         } => {
-                            let s = match s {
-                                winit::event::ElementState::Pressed => MouseButtonState::Pressed,
-                                winit::event::ElementState::Released => MouseButtonState::Released,
-                            };
-
                             let b = match button {
                                 winit::event::MouseButton::Left => MouseButton::Left,
                                 winit::event::MouseButton::Right => MouseButton::Right,
@@ -528,105 +345,13 @@ impl Application {
                                 winit::event::MouseButton::Other(id) => MouseButton::Other(id),
                             };
 
-                            match b {
-                                MouseButton::Left => {
-                                    state.mouse.left.state = s;
-                                }
-
-                                MouseButton::Right => {
-                                    state.mouse.right.state = s;
-                                }
-
-                                MouseButton::Middle => {
-                                    state.mouse.middle.state = s;
-                                }
-
-                                _ => {}
-                            }
-
                             match s {
-                                MouseButtonState::Pressed => {
-                                    if state.hovered != Entity::null()
-                                        && state.active != state.hovered
-                                    {
-                                        state.active = state.hovered;
-                                        state.insert_event(Event::new(WindowEvent::Restyle));
-                                    }
-
-                                    if state.captured != Entity::null() {
-                                        state.insert_event(
-                                            Event::new(WindowEvent::MouseDown(b))
-                                                .target(state.captured)
-                                                .propagate(Propagation::Direct),
-                                        );
-                                    } else {
-                                        state.insert_event(
-                                            Event::new(WindowEvent::MouseDown(b))
-                                                .target(state.hovered),
-                                        );
-                                    }
-
-                                    match b {
-                                        MouseButton::Left => {
-                                            state.mouse.left.pos_down =
-                                                (state.mouse.cursorx, state.mouse.cursory);
-                                            state.mouse.left.pressed = state.hovered;
-                                        }
-
-                                        MouseButton::Middle => {
-                                            state.mouse.middle.pos_down =
-                                                (state.mouse.cursorx, state.mouse.cursory);
-                                            state.mouse.left.pressed = state.hovered;
-                                        }
-
-                                        MouseButton::Right => {
-                                            state.mouse.right.pos_down =
-                                                (state.mouse.cursorx, state.mouse.cursory);
-                                            state.mouse.left.pressed = state.hovered;
-                                        }
-
-                                        _ => {}
-                                    }
+                                winit::event::ElementState::Pressed => {
+                                    state.dispatch_input(WindowEvent::MouseDown(b));
                                 }
 
-                                MouseButtonState::Released => {
-                                    state.active = Entity::null();
-                                    state.insert_event(Event::new(WindowEvent::Restyle));
-
-                                    if state.captured != Entity::null() {
-                                        state.insert_event(
-                                            Event::new(WindowEvent::MouseUp(b))
-                                                .target(state.captured)
-                                                .propagate(Propagation::Direct),
-                                        );
-                                    } else {
-                                        state.insert_event(
-                                            Event::new(WindowEvent::MouseUp(b))
-                                                .target(state.hovered),
-                                        );
-                                    }
-
-                                    match b {
-                                        MouseButton::Left => {
-                                            state.mouse.left.pos_up =
-                                                (state.mouse.cursorx, state.mouse.cursory);
-                                            state.mouse.left.released = state.hovered;
-                                        }
-
-                                        MouseButton::Middle => {
-                                            state.mouse.middle.pos_up =
-                                                (state.mouse.cursorx, state.mouse.cursory);
-                                            state.mouse.left.released = state.hovered;
-                                        }
-
-                                        MouseButton::Right => {
-                                            state.mouse.right.pos_up =
-                                                (state.mouse.cursorx, state.mouse.cursory);
-                                            state.mouse.left.released = state.hovered;
-                                        }
-
-                                        _ => {}
-                                    }
+                                winit::event::ElementState::Released => {
+                                    state.dispatch_input(WindowEvent::MouseUp(b));
                                 }
                             }
                         }
@@ -642,18 +367,7 @@ impl Application {
                                 _ => (0.0, 0.0),
                             };
 
-                            if state.captured != Entity::null() {
-                                state.insert_event(
-                                    Event::new(WindowEvent::MouseScroll(x, y))
-                                        .target(state.captured)
-                                        .propagate(Propagation::Direct),
-                                );
-                            } else {
-                                state.insert_event(
-                                    Event::new(WindowEvent::MouseScroll(x, y))
-                                        .target(state.hovered),
-                                );
-                            }
+                            state.dispatch_input(WindowEvent::MouseScroll(x, y));
                         }
 
                         _ => {}
@@ -1,4 +1,4 @@
-use winit::dpi::PhysicalSize;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
 use winit::event_loop::EventLoop;
 use winit::window::Icon;
 use winit::window::WindowBuilder;
@@ -6,11 +6,11 @@ use winit::window::WindowBuilder;
 use femtovg::{renderer::OpenGl, Canvas, Color};
 use raw_gl_context::{GlConfig, GlContext};
 
-use tuix_core::WindowDescription;
+use tuix_core::{FemtovgCanvas, WindowCommand, WindowDescription};
 
 pub struct Window {
     pub context: GlContext,
-    pub canvas: Canvas<OpenGl>,
+    pub canvas: FemtovgCanvas<OpenGl>,
     pub window: winit::window::Window,
 }
 
@@ -71,8 +71,34 @@ impl Window {
 
         Window {
             context,
-            canvas,
+            canvas: FemtovgCanvas::new(canvas),
             window,
         }
     }
+
+    // Applies a window command, except for closing which is handled by the event loop
+    pub fn apply_command(&self, command: WindowCommand) {
+        let window = &self.window;
+        match command {
+            WindowCommand::SetTitle(title) => window.set_title(&title),
+            WindowCommand::SetInnerSize(width, height) => {
+                window.set_inner_size(PhysicalSize::new(width, height))
+            }
+            WindowCommand::SetMinInnerSize(size) => window.set_min_inner_size(
+                size.map(|(width, height)| PhysicalSize::new(width, height)),
+            ),
+            WindowCommand::SetMaxInnerSize(size) => window.set_max_inner_size(
+                size.map(|(width, height)| PhysicalSize::new(width, height)),
+            ),
+            WindowCommand::SetPosition(x, y) => {
+                window.set_outer_position(PhysicalPosition::new(x, y))
+            }
+            WindowCommand::SetFullscreen(fullscreen) => window.set_fullscreen(if fullscreen {
+                Some(winit::window::Fullscreen::Borderless(None))
+            } else {
+                None
+            }),
+            WindowCommand::Close => {}
+        }
+    }
 }